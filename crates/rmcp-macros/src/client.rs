@@ -0,0 +1,187 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    Expr, FnArg, Ident, ItemTrait, LitStr, Pat, ReturnType, Token, TraitItem, TraitItemFn,
+    parse::Parse, spanned::Spanned,
+};
+
+const TOOL_IDENT: &str = "tool";
+
+#[derive(Default)]
+struct ClientFnItemAttrs {
+    name: Option<Expr>,
+}
+
+impl Parse for ClientFnItemAttrs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut name = None;
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            match key.to_string().as_str() {
+                "name" => {
+                    let value: Expr = input.parse()?;
+                    name = Some(value);
+                }
+                _ => {
+                    return Err(syn::Error::new(key.span(), "unknown attribute"));
+                }
+            }
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(ClientFnItemAttrs { name })
+    }
+}
+
+pub(crate) fn mcp_client(attr: TokenStream, input: TokenStream) -> syn::Result<TokenStream> {
+    if !attr.is_empty() {
+        return Err(syn::Error::new(attr.span(), "unknown attribute"));
+    }
+    let item = syn::parse2::<ItemTrait>(input)?;
+    let vis = &item.vis;
+    let client_ident = format_ident!("{}Client", item.ident);
+    let trait_doc = format!(
+        "A typed client of the tools described by `{}`, generated by `#[mcp_client]`.",
+        item.ident
+    );
+    let mut methods = Vec::new();
+    for trait_item in &item.items {
+        let TraitItem::Fn(method) = trait_item else {
+            return Err(syn::Error::new(
+                trait_item.span(),
+                "only tool methods are allowed in an mcp client trait",
+            ));
+        };
+        methods.push(client_method(method)?);
+    }
+    Ok(quote! {
+        #[doc = #trait_doc]
+        #[derive(Debug, Clone)]
+        #vis struct #client_ident {
+            peer: rmcp::Peer<rmcp::RoleClient>,
+        }
+
+        impl #client_ident {
+            pub fn new(peer: rmcp::Peer<rmcp::RoleClient>) -> Self {
+                Self { peer }
+            }
+
+            pub fn peer(&self) -> &rmcp::Peer<rmcp::RoleClient> {
+                &self.peer
+            }
+
+            #(#methods)*
+        }
+    })
+}
+
+fn client_method(method: &TraitItemFn) -> syn::Result<TokenStream> {
+    let sig = &method.sig;
+    if sig.asyncness.is_none() {
+        return Err(syn::Error::new(
+            sig.fn_token.span(),
+            "tool methods of an mcp client must be async",
+        ));
+    }
+    let mut attrs = ClientFnItemAttrs::default();
+    let mut docs = Vec::new();
+    for attr in &method.attrs {
+        if attr.path().is_ident(TOOL_IDENT) {
+            attrs = attr.parse_args()?;
+        } else if attr.path().is_ident("doc") {
+            docs.push(attr);
+        }
+    }
+    let ident = &sig.ident;
+    let tool_name = match attrs.name {
+        Some(name) => quote! { #name },
+        None => {
+            let name = LitStr::new(&ident.to_string(), ident.span());
+            quote! { #name }
+        }
+    };
+
+    let mut inputs = sig.inputs.iter();
+    match inputs.next() {
+        Some(FnArg::Receiver(receiver))
+            if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+        _ => {
+            return Err(syn::Error::new(
+                sig.inputs.span(),
+                "tool methods of an mcp client take `&self` first",
+            ));
+        }
+    }
+    let mut params = Vec::new();
+    let mut pushes = Vec::new();
+    for input in inputs {
+        let FnArg::Typed(pat_type) = input else {
+            unreachable!("only the first argument can be a receiver");
+        };
+        let Pat::Ident(pat_ident) = pat_type.pat.as_ref() else {
+            return Err(syn::Error::new(
+                pat_type.pat.span(),
+                "arguments of an mcp client tool must be plain identifiers",
+            ));
+        };
+        let param = &pat_ident.ident;
+        let ty = &pat_type.ty;
+        let name = LitStr::new(&param.to_string(), param.span());
+        params.push(quote! { #param: #ty });
+        pushes.push(quote! {
+            rmcp::handler::client::typed::push_argument(&mut arguments, TOOL, #name, &#param)?;
+        });
+    }
+    let output = match &sig.output {
+        ReturnType::Default => quote! { () },
+        ReturnType::Type(_, ty) => quote! { #ty },
+    };
+    Ok(quote! {
+        #(#docs)*
+        pub async fn #ident(
+            &self,
+            #(#params),*
+        ) -> Result<#output, rmcp::handler::client::typed::TypedToolError> {
+            const TOOL: &str = #tool_name;
+            #[allow(unused_mut)]
+            let mut arguments = rmcp::model::JsonObject::new();
+            #(#pushes)*
+            rmcp::handler::client::typed::call_tool(&self.peer, TOOL, arguments).await
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mcp_client() -> syn::Result<()> {
+        let input = quote! {
+            pub trait Calculator {
+                /// Calculate the sum of two numbers
+                #[tool(name = "sum")]
+                async fn add(&self, a: i32, b: i32) -> i32;
+                async fn reset(&self);
+            }
+        };
+        let output = mcp_client(TokenStream::new(), input)?.to_string();
+        assert!(output.contains("pub struct CalculatorClient"));
+        assert!(output.contains("\"sum\""));
+        assert!(output.contains("\"reset\""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mcp_client_requires_async() {
+        let input = quote! {
+            trait Calculator {
+                fn sum(&self, a: i32, b: i32) -> i32;
+            }
+        };
+        assert!(mcp_client(TokenStream::new(), input).is_err());
+    }
+}
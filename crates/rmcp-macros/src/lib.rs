@@ -1,6 +1,8 @@
 #[allow(unused_imports)]
 use proc_macro::TokenStream;
 
+mod client;
+mod schema;
 mod tool;
 
 #[proc_macro_attribute]
@@ -9,3 +11,26 @@ pub fn tool(attr: TokenStream, input: TokenStream) -> TokenStream {
         .unwrap_or_else(|err| err.to_compile_error())
         .into()
 }
+
+/// Generate a typed client for the tools described by a trait.
+///
+/// The client of `trait Calculator` is a `CalculatorClient` wrapping a `Peer<RoleClient>`,
+/// each `async fn` of the trait becomes a method calling the tool of the same name, or the one
+/// given with `#[tool(name = "...")]`, with its arguments as an object and its result parsed as
+/// the return type.
+#[proc_macro_attribute]
+pub fn mcp_client(attr: TokenStream, input: TokenStream) -> TokenStream {
+    client::mcp_client(attr.into(), input.into())
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// The derive of `rmcp::schemars::JsonSchema` without the `schemars` feature, which only warns
+/// about the `#[schemars]` attributes it ignores since every type already implements it.
+#[doc(hidden)]
+#[proc_macro_derive(JsonSchema, attributes(schemars))]
+pub fn json_schema(input: TokenStream) -> TokenStream {
+    schema::json_schema(input.into())
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
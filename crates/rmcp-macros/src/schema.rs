@@ -0,0 +1,67 @@
+use proc_macro2::TokenStream;
+use quote::quote_spanned;
+use syn::{Attribute, Data, DeriveInput, Fields, spanned::Spanned};
+
+/// The stand-in `JsonSchema` derive, every type already implements the stand-in trait.
+///
+/// The `#[schemars]` attributes have nothing to apply to, each of them gets a deprecation
+/// warning so the constraints and descriptions aren't lost silently.
+pub(crate) fn json_schema(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = syn::parse2(input)?;
+    let mut attrs = input.attrs.iter().collect::<Vec<_>>();
+    match &input.data {
+        Data::Struct(data) => attrs.extend(field_attrs(&data.fields)),
+        Data::Enum(data) => {
+            for variant in &data.variants {
+                attrs.extend(&variant.attrs);
+                attrs.extend(field_attrs(&variant.fields));
+            }
+        }
+        Data::Union(data) => attrs.extend(data.fields.named.iter().flat_map(|field| &field.attrs)),
+    }
+    Ok(attrs
+        .into_iter()
+        .filter(|attr| attr.path().is_ident("schemars"))
+        .map(ignored_attribute_warning)
+        .collect())
+}
+
+fn field_attrs(fields: &Fields) -> impl Iterator<Item = &Attribute> {
+    fields.iter().flat_map(|field| &field.attrs)
+}
+
+fn ignored_attribute_warning(attr: &Attribute) -> TokenStream {
+    quote_spanned! {attr.span()=>
+        const _: () = {
+            #[deprecated(
+                note = "`#[schemars]` is ignored without the `schemars` feature of rmcp, the schema is `{\"type\": \"object\"}`"
+            )]
+            #[allow(non_upper_case_globals)]
+            const schemars_attribute_ignored: () = ();
+            schemars_attribute_ignored
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use quote::quote;
+
+    use super::*;
+
+    #[test]
+    fn test_ignored_schemars_attributes_warn() -> syn::Result<()> {
+        let input = quote! {
+            #[schemars(description = "a range")]
+            struct Range {
+                #[schemars(range(min = 0))]
+                start: u32,
+                #[serde(default)]
+                end: u32,
+            }
+        };
+        let result = json_schema(input)?.to_string();
+        assert_eq!(result.matches("deprecated").count(), 2);
+        Ok(())
+    }
+}
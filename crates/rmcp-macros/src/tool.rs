@@ -44,16 +44,37 @@ impl Parse for ToolImplItemAttrs {
 struct ToolFnItemAttrs {
     name: Option<Expr>,
     description: Option<Expr>,
+    deprecated: Option<Expr>,
+    timeout_ms: Option<Expr>,
     vis: Option<Visibility>,
+    blocking: bool,
+    positional: bool,
 }
 
 impl Parse for ToolFnItemAttrs {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let mut name = None;
         let mut description = None;
+        let mut deprecated = None;
+        let mut timeout_ms = None;
         let mut vis = None;
+        let mut blocking = false;
+        let mut positional = false;
         while !input.is_empty() {
             let key: Ident = input.parse()?;
+            // flags don't take a value
+            if key == BLOCKING_IDENT || key == POSITIONAL_IDENT {
+                if key == BLOCKING_IDENT {
+                    blocking = true;
+                } else {
+                    positional = true;
+                }
+                if input.is_empty() {
+                    break;
+                }
+                input.parse::<Token![,]>()?;
+                continue;
+            }
             input.parse::<Token![=]>()?;
             match key.to_string().as_str() {
                 "name" => {
@@ -64,6 +85,14 @@ impl Parse for ToolFnItemAttrs {
                     let value: Expr = input.parse()?;
                     description = Some(value);
                 }
+                "deprecated" => {
+                    let value: Expr = input.parse()?;
+                    deprecated = Some(value);
+                }
+                "timeout_ms" => {
+                    let value: Expr = input.parse()?;
+                    timeout_ms = Some(value);
+                }
                 "vis" => {
                     let value: Visibility = input.parse()?;
                     vis = Some(value);
@@ -81,7 +110,11 @@ impl Parse for ToolFnItemAttrs {
         Ok(ToolFnItemAttrs {
             name,
             description,
+            deprecated,
+            timeout_ms,
             vis,
+            blocking,
+            positional,
         })
     }
 }
@@ -131,6 +164,8 @@ const SCHEMARS_IDENT: &str = "schemars";
 const PARAM_IDENT: &str = "param";
 const AGGREGATED_IDENT: &str = "aggr";
 const REQ_IDENT: &str = "req";
+const BLOCKING_IDENT: &str = "blocking";
+const POSITIONAL_IDENT: &str = "positional";
 
 pub enum ParamMarker {
     Param,
@@ -181,10 +216,19 @@ pub(crate) fn tool_impl_item(attr: TokenStream, mut input: ItemImpl) -> syn::Res
 
     // get all tool function ident
     let mut tool_fn_idents = Vec::new();
+    let mut tool_names = HashSet::new();
     for item in &input.items {
         if let syn::ImplItem::Fn(method) = item {
             for attr in &method.attrs {
                 if attr.path().is_ident(TOOL_IDENT) {
+                    if let Some(name) = literal_tool_name(attr, &method.sig.ident)? {
+                        if !tool_names.insert(name.clone()) {
+                            return Err(syn::Error::new(
+                                attr.span(),
+                                format!("duplicate tool name `{name}`, tool names must be unique"),
+                            ));
+                        }
+                    }
                     tool_fn_idents.push(method.sig.ident.clone());
                 }
             }
@@ -219,6 +263,15 @@ pub(crate) fn tool_impl_item(attr: TokenStream, mut input: ItemImpl) -> syn::Res
                         self.list_tools_inner(request, context).await
                     }
                 });
+
+                // generate supported_methods method
+                input.items.push(parse_quote! {
+                    fn supported_methods(&self) -> Vec<&'static str> {
+                        rmcp::handler::server::tool::with_tool_methods(
+                            rmcp::ServerHandler::get_info(self).capabilities.supported_methods(),
+                        )
+                    }
+                });
             } else {
                 // if there are no generic parameters, add tool box derive
                 input.items.push(parse_quote!(
@@ -296,6 +349,22 @@ pub(crate) fn tool_impl_item(attr: TokenStream, mut input: ItemImpl) -> syn::Res
     })
 }
 
+/// The name a `#[tool]` method registers, `None` when it's not known before runtime
+fn literal_tool_name(attr: &syn::Attribute, ident: &Ident) -> syn::Result<Option<String>> {
+    let attrs = match &attr.meta {
+        syn::Meta::List(meta_list) => meta_list.parse_args::<ToolFnItemAttrs>()?,
+        _ => ToolFnItemAttrs::default(),
+    };
+    Ok(match attrs.name {
+        None => Some(ident.to_string()),
+        Some(Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(name),
+            ..
+        })) => Some(name.value()),
+        Some(_) => None,
+    })
+}
+
 // extract doc line from attribute
 fn extract_doc_line(attr: &syn::Attribute) -> Option<String> {
     if !attr.path().is_ident("doc") {
@@ -468,6 +537,12 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
                 }
             }
         };
+        let annotations = match &tool_macro_attrs.fn_item.deprecated {
+            Some(message) => quote! {
+                Some(rmcp::model::ToolAnnotations::new().deprecated(#message))
+            },
+            None => quote! { None },
+        };
         let input_fn_attrs = &input_fn.attrs;
         let input_fn_vis = &input_fn.vis;
         quote! {
@@ -477,7 +552,7 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
                     name: #name.into(),
                     description: Some(#description.into()),
                     input_schema: #schema.into(),
-                    annotations: None
+                    annotations: #annotations
                 }
             }
         }
@@ -545,7 +620,16 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
         let trivial_arg_extraction_part = quote! {
             #(#trivial_args)*
         };
+        // positional tools also accept their arguments as a JSON array, in declaration order
+        let positional = tool_macro_attrs.fn_item.positional;
         let processed_arg_extraction_part = match &mut tool_macro_attrs.params {
+            ToolParams::Aggregated { rust_type } if positional => {
+                let PatType { pat, ty, .. } = rust_type;
+                quote! {
+                    let (__rmcp_tool_req, context) = rmcp::model::ToolArguments::from_tool_call_context_part(context)?;
+                    let #pat: #ty = parse_positional_arguments(__rmcp_tool_req, &Self::#tool_attr_fn_ident().input_schema)?;
+                }
+            }
             ToolParams::Aggregated { rust_type } => {
                 let PatType { pat, ty, .. } = rust_type;
                 quote! {
@@ -557,17 +641,35 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
                     create_request_type(attrs, input_fn.sig.ident.to_string());
 
                 let params_ident = attrs.iter().map(|attr| &attr.ident).collect::<Vec<_>>();
+                let parse_arguments = if positional {
+                    quote! {
+                        let (__rmcp_tool_req, context) = rmcp::model::ToolArguments::from_tool_call_context_part(context)?;
+                        let #temp_param_type_name {
+                            #(#params_ident,)*
+                        } = parse_positional_arguments(__rmcp_tool_req, &Self::#tool_attr_fn_ident().input_schema)?;
+                    }
+                } else {
+                    quote! {
+                        let (__rmcp_tool_req, context) = rmcp::model::JsonObject::from_tool_call_context_part(context)?;
+                        let #temp_param_type_name {
+                            #(#params_ident,)*
+                        } = parse_json_object(__rmcp_tool_req)?;
+                    }
+                };
                 quote! {
                     #param_type
-                    let (__rmcp_tool_req, context) = rmcp::model::JsonObject::from_tool_call_context_part(context)?;
-                    let #temp_param_type_name {
-                        #(#params_ident,)*
-                    } = parse_json_object(__rmcp_tool_req)?;
+                    #parse_arguments
                 }
             }
-            ToolParams::NoParam => {
+            ToolParams::NoParam if positional => {
                 quote! {}
             }
+            // the arguments are ignored, but an array is still rejected
+            ToolParams::NoParam => {
+                quote! {
+                    context.named_arguments()?;
+                }
+            }
         };
         // generate the execution part
         // has receiver?
@@ -587,7 +689,41 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
             })
             .collect::<Vec<_>>();
         let raw_fn_ident = &input_fn.sig.ident;
-        let call = if is_async {
+        let call = if tool_macro_attrs.fn_item.blocking {
+            if is_async {
+                return Err(syn::Error::new(
+                    input_fn.sig.asyncness.span(),
+                    "blocking tool must be a sync function",
+                ));
+            }
+            // the closure must be 'static, so it takes a clone of the receiver
+            let receiver = input_fn.sig.inputs.iter().find_map(|fn_arg| match fn_arg {
+                FnArg::Receiver(r) => Some(r),
+                FnArg::Typed(_) => None,
+            });
+            let receiver_clone = receiver.map(|r| {
+                let pat = receiver_ident();
+                if r.reference.is_some() {
+                    quote! { let #pat = ::std::clone::Clone::clone(#pat); }
+                } else {
+                    quote! {}
+                }
+            });
+            let params =
+                input_fn
+                    .sig
+                    .inputs
+                    .iter()
+                    .zip(params)
+                    .map(|(fn_arg, param)| match fn_arg {
+                        FnArg::Receiver(r) if r.reference.is_some() => quote! { &#param },
+                        _ => quote! { #param },
+                    });
+            quote! {
+                #receiver_clone
+                call_blocking(move || Self::#raw_fn_ident(#(#params),*).into_call_tool_result()).await
+            }
+        } else if is_async {
             quote! {
                 Self::#raw_fn_ident(#(#params),*).await.into_call_tool_result()
             }
@@ -596,6 +732,17 @@ pub(crate) fn tool_fn_item(attr: TokenStream, mut input_fn: ItemFn) -> syn::Resu
                 Self::#raw_fn_ident(#(#params),*).into_call_tool_result()
             }
         };
+        // the tool future is dropped on timeout, which cancels it
+        let call = match &tool_macro_attrs.fn_item.timeout_ms {
+            Some(timeout_ms) => quote! {
+                call_with_timeout(
+                    ::std::time::Duration::from_millis(#timeout_ms),
+                    async move { #call },
+                )
+                .await
+            },
+            None => call,
+        };
         // assemble the whole function
         let tool_call_fn_ident = Ident::new(
             &format!("{}_tool_call", input_fn.sig.ident),
@@ -668,6 +815,30 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_duplicate_tool_name() {
+        let attr = quote! {
+            tool_box
+        };
+        let input = quote! {
+            impl Calculator {
+                #[tool(description = "Add two numbers")]
+                fn sum(&self) -> String {
+                    unimplemented!()
+                }
+                #[tool(name = "sum", description = "Add two numbers, merged from another module")]
+                fn add(&self) -> String {
+                    unimplemented!()
+                }
+            }
+        };
+        let error = tool(attr, input).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "duplicate tool name `sum`, tool names must be unique"
+        );
+    }
+
     #[test]
     fn test_trait_tool_macro() -> syn::Result<()> {
         let attr = quote! {
@@ -708,6 +879,75 @@ mod test {
 
         Ok(())
     }
+    #[test]
+    fn test_blocking_tool_macro() -> syn::Result<()> {
+        let attr = quote! {
+            blocking, description = "blocking tool"
+        };
+        let input = quote! {
+            fn hash(&self, #[tool(param)] data: String) -> String {
+                data
+            }
+        };
+        let result = tool(attr, input)?;
+        assert!(result.to_string().contains("call_blocking"));
+
+        let attr = quote! { blocking };
+        let input = quote! {
+            async fn hash(&self) -> String {
+                String::new()
+            }
+        };
+        assert!(tool(attr, input).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_deprecated_tool_macro() -> syn::Result<()> {
+        let attr = quote! {
+            description = "old tool", deprecated = "use new_tool instead"
+        };
+        let input = quote! {
+            async fn old_tool(&self) -> String {
+                String::new()
+            }
+        };
+        let result = tool(attr, input)?.to_string();
+        assert!(result.contains("ToolAnnotations"));
+        assert!(result.contains("\"use new_tool instead\""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_timeout_tool_macro() -> syn::Result<()> {
+        let attr = quote! {
+            description = "slow tool", timeout_ms = 5000
+        };
+        let input = quote! {
+            async fn slow(&self) -> String {
+                String::new()
+            }
+        };
+        let result = tool(attr, input)?.to_string();
+        assert!(result.contains("call_with_timeout"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_positional_tool_macro() -> syn::Result<()> {
+        let attr = quote! {
+            positional, description = "sum two numbers"
+        };
+        let input = quote! {
+            fn sum(&self, #[tool(param)] a: i32, #[tool(param)] b: i32) -> String {
+                (a + b).to_string()
+            }
+        };
+        let result = tool(attr, input)?.to_string();
+        assert!(result.contains("parse_positional_arguments"));
+        Ok(())
+    }
+
     #[test]
     fn test_explicit_description_priority() -> syn::Result<()> {
         let attr = quote! {
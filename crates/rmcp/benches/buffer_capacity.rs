@@ -0,0 +1,81 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use rmcp::{
+    RoleServer, ServerHandler, ServiceExt,
+    model::{
+        ReadResourceRequestParam, ReadResourceResult, ResourceContents, ServerCapabilities,
+        ServerInfo,
+    },
+    service::RequestContext,
+    transport::io::AsyncRwTransport,
+};
+
+const SIZE: usize = 1024 * 1024;
+
+/// Answer a read with the text of the uri, so the request and the response are as big
+pub struct Echo;
+
+impl ServerHandler for Echo {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_resources().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        ReadResourceRequestParam { uri }: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, rmcp::Error> {
+        let text = uri.trim_start_matches("echo://").to_owned();
+        Ok(ReadResourceResult::new(vec![ResourceContents::text(
+            text, uri,
+        )]))
+    }
+}
+
+/// Read a resource of `SIZE` bytes through transports with `capacity` byte buffers
+fn bench_buffer_capacity(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().expect("fail to start the runtime");
+    let uri = format!("echo://{}", "x".repeat(SIZE));
+    let mut group = c.benchmark_group("buffer_capacity");
+    // the request and the response
+    group.throughput(Throughput::Bytes(2 * SIZE as u64));
+    group.sample_size(20);
+    for capacity in [
+        512,
+        AsyncRwTransport::<(), ()>::DEFAULT_BUFFER_CAPACITY,
+        256 * 1024,
+    ] {
+        let (server, client) = runtime.block_on(async {
+            let (server_stream, client_stream) = tokio::io::duplex(64 * 1024);
+            let transport = |stream| {
+                let (read, write) = tokio::io::split(stream);
+                AsyncRwTransport::new(read, write)
+                    .read_buffer_capacity(capacity)
+                    .write_buffer_capacity(capacity)
+            };
+            tokio::try_join!(
+                Echo.serve(transport(server_stream)),
+                ().serve(transport(client_stream))
+            )
+            .expect("fail to serve")
+        });
+        group.bench_with_input(BenchmarkId::from_parameter(capacity), &uri, |b, uri| {
+            b.to_async(&runtime).iter(|| async {
+                client
+                    .read_resource(ReadResourceRequestParam { uri: uri.clone() })
+                    .await
+                    .expect("fail to read the resource")
+            })
+        });
+        runtime.block_on(async {
+            client.cancel().await.expect("fail to cancel the client");
+            server.cancel().await.expect("fail to cancel the server");
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_buffer_capacity);
+criterion_main!(benches);
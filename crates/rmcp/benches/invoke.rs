@@ -0,0 +1,36 @@
+#[path = "../tests/common/calculator.rs"]
+mod calculator;
+
+use calculator::Calculator;
+use criterion::{Criterion, criterion_group, criterion_main};
+use rmcp::{
+    model::{CallToolRequest, CallToolRequestParam, ClientRequest, object},
+    testing::invoke,
+};
+use serde_json::json;
+
+fn sum_request(a: i32, b: i32) -> ClientRequest {
+    ClientRequest::CallToolRequest(CallToolRequest {
+        method: Default::default(),
+        params: CallToolRequestParam {
+            name: "sum".into(),
+            arguments: Some(object(json!({ "a": a, "b": b })).into()),
+        },
+        extensions: Default::default(),
+    })
+}
+
+/// A tool call without a transport, the overhead of the handler itself
+fn bench_invoke_tool(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().expect("fail to start the runtime");
+    c.bench_function("invoke_tool", |b| {
+        b.to_async(&runtime).iter(|| async {
+            invoke(&Calculator, sum_request(1, 2))
+                .await
+                .expect("fail to call the tool")
+        })
+    });
+}
+
+criterion_group!(benches, bench_invoke_tool);
+criterion_main!(benches);
@@ -0,0 +1,158 @@
+//! # Blocking client
+//!
+//! A client for programs which aren't async, like a CLI tool: [`BlockingClient`] owns a
+//! single-threaded tokio runtime and blocks on it for each call.
+//!
+//! ```rust,ignore
+//! let client = BlockingClient::connect(child_process_transport)?;
+//! for tool in client.list_tools()? {
+//!     println!("{}", tool.name);
+//! }
+//! let result = client.call_tool(CallToolRequestParam {
+//!     name: "sum".into(),
+//!     arguments: Some(object(json!({ "a": 1, "b": 2 })).into()),
+//! })?;
+//! ```
+use std::future::Future;
+
+use tokio::runtime::Runtime;
+
+use crate::{
+    Peer, RoleClient, ServiceError, ServiceExt,
+    model::{
+        CallToolRequestParam, CallToolResult, GetPromptRequestParam, GetPromptResult, Prompt,
+        ReadResourceRequestParam, ReadResourceResult, Resource, ServerInfo, Tool,
+    },
+    service::{CloseReason, RunningService, Service},
+    transport::IntoTransport,
+};
+
+/// A client whose methods block the calling thread until the server answers.
+///
+/// The connection only makes progress while a method is blocking: the server's requests and
+/// notifications are handled during the next call, so a server pinging an idle client may
+/// drop it.
+///
+/// # Panic
+/// The methods and the drop panic when called from within an async context, where blocking
+/// would stall the executor.
+pub struct BlockingClient<S: Service<RoleClient> = ()> {
+    // taken on drop, to be cancelled before the runtime shuts down
+    service: Option<RunningService<RoleClient, S>>,
+    runtime: Runtime,
+}
+
+impl BlockingClient {
+    /// Connect over `transport` and run the initialization, without replying to the server's
+    /// requests.
+    pub fn connect<T, E, A>(transport: T) -> Result<Self, E>
+    where
+        T: IntoTransport<RoleClient, E, A>,
+        E: std::error::Error + From<std::io::Error> + Send + Sync + 'static,
+    {
+        Self::serve((), transport)
+    }
+}
+
+impl<S: Service<RoleClient>> BlockingClient<S> {
+    /// Serve `handler` over `transport` and run the initialization.
+    pub fn serve<T, E, A>(handler: S, transport: T) -> Result<Self, E>
+    where
+        T: IntoTransport<RoleClient, E, A>,
+        E: std::error::Error + From<std::io::Error> + Send + Sync + 'static,
+    {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let service = runtime.block_on(handler.serve(transport))?;
+        Ok(Self {
+            service: Some(service),
+            runtime,
+        })
+    }
+
+    fn running(&self) -> &RunningService<RoleClient, S> {
+        self.service
+            .as_ref()
+            .expect("the service is only taken on close")
+    }
+
+    pub fn peer(&self) -> &Peer<RoleClient> {
+        self.running().peer()
+    }
+
+    /// The server information from the initialization
+    pub fn peer_info(&self) -> &ServerInfo {
+        self.peer().peer_info()
+    }
+
+    /// Block on a future made from the peer, for the requests without a method here.
+    pub fn block_on<F, Fut>(&self, f: F) -> Fut::Output
+    where
+        F: FnOnce(Peer<RoleClient>) -> Fut,
+        Fut: Future,
+    {
+        self.runtime.block_on(f(self.peer().clone()))
+    }
+
+    /// List the tools, following the pagination.
+    pub fn list_tools(&self) -> Result<Vec<Tool>, ServiceError> {
+        self.runtime.block_on(self.peer().list_all_tools())
+    }
+
+    pub fn call_tool(&self, params: CallToolRequestParam) -> Result<CallToolResult, ServiceError> {
+        self.runtime.block_on(self.peer().call_tool(params))
+    }
+
+    /// List the resources, following the pagination.
+    pub fn list_resources(&self) -> Result<Vec<Resource>, ServiceError> {
+        self.runtime.block_on(self.peer().list_all_resources())
+    }
+
+    pub fn read_resource(
+        &self,
+        params: ReadResourceRequestParam,
+    ) -> Result<ReadResourceResult, ServiceError> {
+        self.runtime.block_on(self.peer().read_resource(params))
+    }
+
+    /// List the prompts, following the pagination.
+    pub fn list_prompts(&self) -> Result<Vec<Prompt>, ServiceError> {
+        self.runtime.block_on(self.peer().list_all_prompts())
+    }
+
+    pub fn get_prompt(
+        &self,
+        params: GetPromptRequestParam,
+    ) -> Result<GetPromptResult, ServiceError> {
+        self.runtime.block_on(self.peer().get_prompt(params))
+    }
+
+    /// Close the connection and shut the runtime down.
+    pub fn close(mut self) -> Result<CloseReason, tokio::task::JoinError> {
+        let service = self
+            .service
+            .take()
+            .expect("the service is only taken on close");
+        self.runtime.block_on(service.cancel())
+    }
+}
+
+impl<S: Service<RoleClient>> Drop for BlockingClient<S> {
+    fn drop(&mut self) {
+        let Some(service) = self.service.take() else {
+            return;
+        };
+        if let Err(error) = self.runtime.block_on(service.cancel()) {
+            tracing::warn!(%error, "fail to stop the blocking client");
+        }
+    }
+}
+
+impl<S: Service<RoleClient>> std::fmt::Debug for BlockingClient<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockingClient")
+            .field("peer", &self.service.as_ref().map(RunningService::peer))
+            .finish_non_exhaustive()
+    }
+}
@@ -1,9 +1,11 @@
 use crate::{
     error::Error as McpError,
     model::*,
-    service::{Peer, RequestContext, RoleClient, Service, ServiceRole},
+    service::{Peer, RequestContext, RequestTiming, RoleClient, Service, ServiceRole},
 };
 
+pub mod typed;
+
 impl<H: ClientHandler> Service<RoleClient> for H {
     async fn handle_request(
         &self,
@@ -11,11 +13,24 @@ impl<H: ClientHandler> Service<RoleClient> for H {
         context: RequestContext<RoleClient>,
     ) -> Result<<RoleClient as ServiceRole>::Resp, McpError> {
         match request {
-            ServerRequest::PingRequest(_) => self.ping(context).await.map(ClientResult::empty),
-            ServerRequest::CreateMessageRequest(request) => self
-                .create_message(request.params, context)
-                .await
-                .map(ClientResult::CreateMessageResult),
+            ServerRequest::PingRequest(_) => {
+                let echo = EmptyResult::ping_echo(&context.meta);
+                self.ping(context)
+                    .await
+                    .map(|()| ClientResult::EmptyResult(echo))
+            }
+            ServerRequest::CreateMessageRequest(request) => {
+                // will be rejected with `McpError::capability_not_advertised` in the next release
+                if self.get_info().capabilities.sampling.is_none() {
+                    tracing::warn!(
+                        "sampling/createMessage handled although the sampling capability is not \
+                         advertised, advertise it in `get_info`"
+                    );
+                }
+                self.create_message(request.params, context)
+                    .await
+                    .map(ClientResult::CreateMessageResult)
+            }
             ServerRequest::ListRootsRequest(_) => self
                 .list_roots(context)
                 .await
@@ -49,6 +64,18 @@ impl<H: ClientHandler> Service<RoleClient> for H {
             ServerNotification::PromptListChangedNotification(_notification_no_param) => {
                 self.on_prompt_list_changed().await
             }
+            #[cfg(feature = "instructions-changed")]
+            ServerNotification::InstructionsChangedNotification(notification) => {
+                self.on_instructions_changed(notification.params).await
+            }
+            #[cfg(feature = "resource-delta")]
+            ServerNotification::ResourceListDeltaNotification(notification) => {
+                self.on_resource_list_delta(notification.params).await
+            }
+            #[cfg(feature = "shutdown-notification")]
+            ServerNotification::ShutdownNotification(_notification) => {
+                self.on_peer_shutdown().await
+            }
         };
         Ok(())
     }
@@ -64,17 +91,59 @@ impl<H: ClientHandler> Service<RoleClient> for H {
     fn get_info(&self) -> <RoleClient as ServiceRole>::Info {
         self.get_info()
     }
+
+    fn on_peer_initialized(&self, server_info: &ServerInfo) {
+        let requested = self.get_info().protocol_version;
+        if requested != server_info.protocol_version {
+            self.on_version_negotiated(&requested, &server_info.protocol_version);
+        }
+        self.on_initialized(server_info)
+    }
+
+    fn on_orphan_response(&self, id: &RequestId) {
+        self.on_orphan_response(id)
+    }
+
+    fn on_request_completed(
+        &self,
+        result: &Result<ClientResult, McpError>,
+        timing: &RequestTiming,
+    ) {
+        self.on_request_completed(result, timing)
+    }
 }
 
 #[allow(unused_variables)]
 pub trait ClientHandler: Sized + Send + Sync + 'static {
+    /// Answer a ping of the server, right away unless
+    /// [`auto_respond_ping`](Self::auto_respond_ping) is turned off, then it stays unanswered
+    /// until the server cancels it.
     fn ping(
         &self,
         context: RequestContext<RoleClient>,
     ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
-        std::future::ready(Ok(()))
+        let auto_respond = self.auto_respond_ping();
+        async move {
+            if !auto_respond {
+                context.ct.cancelled().await;
+                return Err(McpError::internal_error("ping cancelled", None));
+            }
+            Ok(())
+        }
+    }
+    /// Whether the default [`ping`](Self::ping) answers, turn it off to see how the server
+    /// copes with unanswered pings.
+    fn auto_respond_ping(&self) -> bool {
+        true
     }
 
+    /// Answer a `sampling/createMessage` request of the server.
+    ///
+    /// Advertise the `sampling` capability in [`get_info`](Self::get_info) when implementing
+    /// it. A request is still handled without it, with a warning, but the next release will
+    /// reject it with a method-not-found error naming the capability.
+    /// The handler is given to `serve`, so a request the server sends right after the handshake
+    /// waits for the client to be serving instead of racing it.
     fn create_message(
         &self,
         params: CreateMessageRequestParam,
@@ -125,6 +194,51 @@ pub trait ClientHandler: Sized + Send + Sync + 'static {
     fn on_prompt_list_changed(&self) -> impl Future<Output = ()> + Send + '_ {
         std::future::ready(())
     }
+    /// Called on the `notifications/instructions_changed` extension notification.
+    #[cfg(feature = "instructions-changed")]
+    fn on_instructions_changed(
+        &self,
+        params: InstructionsChangedNotificationParam,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        std::future::ready(())
+    }
+    /// Called on the `notifications/resources/list_delta` extension notification, see
+    /// [`LiveResourceSet`](crate::service::LiveResourceSet) to apply it.
+    #[cfg(feature = "resource-delta")]
+    fn on_resource_list_delta(
+        &self,
+        params: ResourceListDeltaNotificationParam,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        std::future::ready(())
+    }
+    /// Called on the `notifications/shutdown` extension notification, the server is about to
+    /// close the connection.
+    #[cfg(feature = "shutdown-notification")]
+    fn on_peer_shutdown(&self) -> impl Future<Output = ()> + Send + '_ {
+        std::future::ready(())
+    }
+
+    /// Called once after a successful handshake with what the server advertised,
+    /// before any other message is processed.
+    fn on_initialized(&self, server_info: &ServerInfo) {}
+
+    /// Called before [`on_initialized`](Self::on_initialized) when the server answered with
+    /// another protocol version than the one of [`get_info`](Self::get_info), usually an older
+    /// one, so the features of newer versions can be turned off.
+    fn on_version_negotiated(&self, requested: &ProtocolVersion, negotiated: &ProtocolVersion) {}
+
+    /// Called when the server responds to a request which isn't pending, the response is
+    /// dropped.
+    fn on_orphan_response(&self, id: &RequestId) {}
+
+    /// Called when the response to a request of the server is ready to be sent, with how long
+    /// it took to handle.
+    fn on_request_completed(
+        &self,
+        result: &Result<ClientResult, McpError>,
+        timing: &RequestTiming,
+    ) {
+    }
 
     fn get_peer(&self) -> Option<Peer<RoleClient>>;
 
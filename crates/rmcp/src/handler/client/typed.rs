@@ -0,0 +1,107 @@
+//! The runtime of the typed clients generated by `#[mcp_client]`
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    Peer, RoleClient, ServiceError,
+    model::{CallToolRequestParam, CallToolResult, JsonObject, RawContent},
+};
+
+#[derive(Debug, Error)]
+pub enum TypedToolError {
+    #[error(transparent)]
+    Service(#[from] ServiceError),
+    #[error("invalid argument {name} of tool {tool}: {error}")]
+    InvalidArgument {
+        tool: &'static str,
+        name: &'static str,
+        error: serde_json::Error,
+    },
+    /// The tool ran but reported an error, `result` has what it said about it
+    #[error("tool {tool} failed")]
+    Tool {
+        tool: &'static str,
+        result: CallToolResult,
+    },
+    #[error("invalid result of tool {tool}: {error}")]
+    InvalidResult {
+        tool: &'static str,
+        error: serde_json::Error,
+    },
+}
+
+/// Add an argument of a tool call
+pub fn push_argument<T: Serialize>(
+    arguments: &mut JsonObject,
+    tool: &'static str,
+    name: &'static str,
+    value: &T,
+) -> Result<(), TypedToolError> {
+    let value = serde_json::to_value(value).map_err(|error| TypedToolError::InvalidArgument {
+        tool,
+        name,
+        error,
+    })?;
+    arguments.insert(name.to_owned(), value);
+    Ok(())
+}
+
+/// Call a tool and parse its result as `T`.
+///
+/// The result is the structured content when there's one, otherwise the text of the first
+/// content, as json or else as a plain string, and null when there's no content at all.
+pub async fn call_tool<T: DeserializeOwned>(
+    peer: &Peer<RoleClient>,
+    tool: &'static str,
+    arguments: JsonObject,
+) -> Result<T, TypedToolError> {
+    let result = peer
+        .call_tool(CallToolRequestParam {
+            name: tool.into(),
+            arguments: Some(arguments.into()),
+        })
+        .await?;
+    if result.is_tool_error() {
+        return Err(TypedToolError::Tool { tool, result });
+    }
+    parse_result(result).map_err(|error| TypedToolError::InvalidResult { tool, error })
+}
+
+fn parse_result<T: DeserializeOwned>(result: CallToolResult) -> Result<T, serde_json::Error> {
+    if let Some(structured_content) = result.structured_content {
+        return serde_json::from_value(structured_content);
+    }
+    let Some(content) = result.content.into_iter().next() else {
+        return serde_json::from_value(Value::Null);
+    };
+    match content.raw {
+        RawContent::Text(text) => serde_json::from_str(&text.text)
+            .or_else(|_| serde_json::from_value(Value::String(text.text))),
+        raw => serde_json::from_value(serde_json::to_value(raw)?),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::model::Content;
+
+    #[test]
+    fn test_parse_result() {
+        let text = |text: &str| CallToolResult::success(vec![Content::text(text)]);
+        assert_eq!(parse_result::<i32>(text("3")).unwrap(), 3);
+        assert_eq!(parse_result::<String>(text("hello")).unwrap(), "hello");
+        assert_eq!(parse_result::<Vec<u8>>(text("[1, 2]")).unwrap(), [1, 2]);
+        assert!(parse_result::<i32>(text("three")).is_err());
+
+        let structured = CallToolResult::structured(json!({ "celsius": 21 }));
+        assert_eq!(
+            parse_result::<Value>(structured).unwrap(),
+            json!({ "celsius": 21 })
+        );
+        parse_result::<()>(CallToolResult::success(vec![])).unwrap();
+    }
+}
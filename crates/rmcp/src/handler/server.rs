@@ -1,7 +1,7 @@
 use crate::{
     error::Error as McpError,
     model::*,
-    service::{Peer, RequestContext, RoleServer, Service, ServiceRole},
+    service::{Peer, RequestContext, RequestTiming, RoleServer, Service, ServiceRole},
 };
 
 mod resource;
@@ -19,7 +19,10 @@ impl<H: ServerHandler> Service<RoleServer> for H {
                 .await
                 .map(ServerResult::InitializeResult),
             ClientRequest::PingRequest(_request) => {
-                self.ping(context).await.map(ServerResult::empty)
+                let echo = EmptyResult::ping_echo(&context.meta);
+                self.ping(context)
+                    .await
+                    .map(|()| ServerResult::EmptyResult(echo))
             }
             ClientRequest::CompleteRequest(request) => self
                 .complete(request.params, context)
@@ -45,10 +48,18 @@ impl<H: ServerHandler> Service<RoleServer> for H {
                 .list_resource_templates(request.params, context)
                 .await
                 .map(ServerResult::ListResourceTemplatesResult),
-            ClientRequest::ReadResourceRequest(request) => self
-                .read_resource(request.params, context)
-                .await
-                .map(ServerResult::ReadResourceResult),
+            ClientRequest::ReadResourceRequest(request) => {
+                let mut context = context;
+                match context.meta.take_uris() {
+                    Some(uris) => Ok(ServerResult::ReadResourceResult(
+                        read_resources(self, uris, context).await,
+                    )),
+                    None => self
+                        .read_resource(request.params, context)
+                        .await
+                        .map(ServerResult::ReadResourceResult),
+                }
+            }
             ClientRequest::SubscribeRequest(request) => self
                 .subscribe(request.params, context)
                 .await
@@ -57,10 +68,14 @@ impl<H: ServerHandler> Service<RoleServer> for H {
                 .unsubscribe(request.params, context)
                 .await
                 .map(ServerResult::empty),
-            ClientRequest::CallToolRequest(request) => self
-                .call_tool(request.params, context)
-                .await
-                .map(ServerResult::CallToolResult),
+            ClientRequest::CallToolRequest(request) => {
+                let peer = context.peer.clone();
+                let mut result = self.call_tool(request.params, context).await?;
+                result.content = self
+                    .content_negotiation()
+                    .apply(result.content, &peer.peer_info().capabilities)?;
+                Ok(ServerResult::CallToolResult(result))
+            }
             ClientRequest::ListToolsRequest(request) => self
                 .list_tools(request.params, context)
                 .await
@@ -68,6 +83,8 @@ impl<H: ServerHandler> Service<RoleServer> for H {
         }
     }
 
+    // existing overrides of `on_initialized` are still called
+    #[allow(deprecated)]
     async fn handle_notification(
         &self,
         notification: <RoleServer as ServiceRole>::PeerNot,
@@ -85,6 +102,10 @@ impl<H: ServerHandler> Service<RoleServer> for H {
             ClientNotification::RootsListChangedNotification(_notification) => {
                 self.on_roots_list_changed().await
             }
+            #[cfg(feature = "shutdown-notification")]
+            ClientNotification::ShutdownNotification(_notification) => {
+                self.on_peer_shutdown().await
+            }
         };
         Ok(())
     }
@@ -100,15 +121,70 @@ impl<H: ServerHandler> Service<RoleServer> for H {
     fn get_info(&self) -> <RoleServer as ServiceRole>::Info {
         self.get_info()
     }
+
+    fn supported_methods(&self) -> Vec<&'static str> {
+        self.supported_methods()
+    }
+
+    fn on_orphan_response(&self, id: &RequestId) {
+        self.on_orphan_response(id)
+    }
+
+    fn on_request_completed(
+        &self,
+        result: &Result<ServerResult, McpError>,
+        timing: &RequestTiming,
+    ) {
+        self.on_request_completed(result, timing)
+    }
+
+    fn max_concurrent_requests(&self) -> Option<std::num::NonZeroUsize> {
+        self.max_concurrent_requests()
+    }
+}
+
+/// Read the uris of a batch one by one with [`ServerHandler::read_resource`], the failure of
+/// one is reported in its entry.
+async fn read_resources<H: ServerHandler>(
+    handler: &H,
+    uris: Vec<String>,
+    context: RequestContext<RoleServer>,
+) -> ReadResourceResult {
+    let mut entries = Vec::with_capacity(uris.len());
+    for uri in uris {
+        let result = handler
+            .read_resource(
+                ReadResourceRequestParam { uri: uri.clone() },
+                context.clone(),
+            )
+            .await;
+        entries.push(ReadResourceEntry::new(uri, result));
+    }
+    ReadResourceResult::from_entries(entries)
 }
 
 #[allow(unused_variables)]
 pub trait ServerHandler: Sized + Send + Sync + 'static {
+    /// Answer a ping of the client, right away unless
+    /// [`auto_respond_ping`](Self::auto_respond_ping) is turned off, then it stays unanswered
+    /// until the client cancels it.
     fn ping(
         &self,
         context: RequestContext<RoleServer>,
     ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
-        std::future::ready(Ok(()))
+        let auto_respond = self.auto_respond_ping();
+        async move {
+            if !auto_respond {
+                context.ct.cancelled().await;
+                return Err(McpError::internal_error("ping cancelled", None));
+            }
+            Ok(())
+        }
+    }
+    /// Whether the default [`ping`](Self::ping) answers, turn it off to see how the client
+    /// copes with unanswered pings.
+    fn auto_respond_ping(&self) -> bool {
+        true
     }
     // handle requests
     fn initialize(
@@ -116,13 +192,35 @@ pub trait ServerHandler: Sized + Send + Sync + 'static {
         request: InitializeRequestParam,
         context: RequestContext<RoleServer>,
     ) -> impl Future<Output = Result<InitializeResult, McpError>> + Send + '_ {
-        std::future::ready(Ok(self.get_info()))
+        std::future::ready(self.on_initialize(&request))
     }
     fn complete(
         &self,
         request: CompleteRequestParam,
         context: RequestContext<RoleServer>,
     ) -> impl Future<Output = Result<CompleteResult, McpError>> + Send + '_ {
+        async move {
+            let Reference::Resource(ResourceReference { uri }) = &request.r#ref else {
+                return Err(McpError::method_not_found::<CompleteRequestMethod>());
+            };
+            let uri_template = uri.clone();
+            let completion = self
+                .complete_resource(uri_template, request, context)
+                .await?;
+            Ok(CompleteResult { completion })
+        }
+    }
+    /// Complete a variable of a resource template, called by the default
+    /// [`ServerHandler::complete`] for a `ref/resource` reference.
+    ///
+    /// `uri_template` is the uri of the reference, `request.argument` is the variable with the
+    /// value typed so far, and the variables already resolved are in `request.context`.
+    fn complete_resource(
+        &self,
+        uri_template: String,
+        request: CompleteRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<CompletionInfo, McpError>> + Send + '_ {
         std::future::ready(Err(McpError::method_not_found::<CompleteRequestMethod>()))
     }
     fn set_level(
@@ -153,6 +251,9 @@ pub trait ServerHandler: Sized + Send + Sync + 'static {
     ) -> impl Future<Output = Result<ListResourcesResult, McpError>> + Send + '_ {
         std::future::ready(Ok(ListResourcesResult::default()))
     }
+    /// List the templates of the resources the server can read, like `file:///{path}`.
+    ///
+    /// They are listed apart from the concrete resources of [`ServerHandler::list_resources`].
     fn list_resource_templates(
         &self,
         request: Option<PaginatedRequestParam>,
@@ -210,13 +311,28 @@ pub trait ServerHandler: Sized + Send + Sync + 'static {
     ) -> impl Future<Output = ()> + Send + '_ {
         std::future::ready(())
     }
+    #[deprecated(note = "override `on_client_initialized` instead")]
     fn on_initialized(&self) -> impl Future<Output = ()> + Send + '_ {
+        self.on_client_initialized()
+    }
+    /// Called once when `notifications/initialized` arrives, after the response to `initialize`.
+    ///
+    /// The client is ready for requests from then on, like sampling, so this is where to start
+    /// the background work using the peer. The requests the client pipelined before the
+    /// notification are handled after this returns.
+    fn on_client_initialized(&self) -> impl Future<Output = ()> + Send + '_ {
         tracing::info!("client initialized");
         std::future::ready(())
     }
     fn on_roots_list_changed(&self) -> impl Future<Output = ()> + Send + '_ {
         std::future::ready(())
     }
+    /// Called on the `notifications/shutdown` extension notification, the client is about to
+    /// close the connection.
+    #[cfg(feature = "shutdown-notification")]
+    fn on_peer_shutdown(&self) -> impl Future<Output = ()> + Send + '_ {
+        std::future::ready(())
+    }
 
     fn get_peer(&self) -> Option<Peer<RoleServer>> {
         None
@@ -229,4 +345,44 @@ pub trait ServerHandler: Sized + Send + Sync + 'static {
     fn get_info(&self) -> ServerInfo {
         ServerInfo::default()
     }
+
+    /// Answer the initialize request of the client, called by the default
+    /// [`ServerHandler::initialize`].
+    ///
+    /// `params` has the client info, capabilities and protocol version as sent by the client, so
+    /// the server can tailor its info to the client or refuse it with an error, which fails the
+    /// initialization. The default answers [`ServerHandler::get_info`].
+    fn on_initialize(&self, params: &InitializeRequestParam) -> Result<InitializeResult, McpError> {
+        Ok(self.get_info())
+    }
+
+    /// What to do with the content of a tool result the client didn't declare support for
+    fn content_negotiation(&self) -> ContentNegotiation {
+        ContentNegotiation::default()
+    }
+
+    /// The request methods this server handles, by default derived from the capabilities it
+    /// advertises.
+    fn supported_methods(&self) -> Vec<&'static str> {
+        self.get_info().capabilities.supported_methods()
+    }
+
+    /// Called when the client responds to a request which isn't pending, the response is
+    /// dropped.
+    fn on_orphan_response(&self, id: &RequestId) {}
+
+    /// Called when the response to a request of the client is ready to be sent, with how long
+    /// it took to handle.
+    fn on_request_completed(
+        &self,
+        result: &Result<ServerResult, McpError>,
+        timing: &RequestTiming,
+    ) {
+    }
+
+    /// The most requests of the client handled at once, the others wait in a queue, see
+    /// [`RequestMetrics`](crate::service::RequestMetrics). Not limited by default.
+    fn max_concurrent_requests(&self) -> Option<std::num::NonZeroUsize> {
+        None
+    }
 }
@@ -1,49 +1,141 @@
 use std::{
     any::TypeId, borrow::Cow, collections::HashMap, future::Ready, marker::PhantomData, sync::Arc,
+    time::Duration,
 };
 
 use futures::future::BoxFuture;
+use indexmap::IndexMap;
+#[cfg(feature = "schemars")]
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use tokio_util::sync::CancellationToken;
 
+#[cfg(not(feature = "schemars"))]
+use crate::schemars::JsonSchema;
 use crate::{
     RoleServer,
-    model::{CallToolRequestParam, CallToolResult, ConstString, IntoContents, JsonObject},
-    service::RequestContext,
+    model::{
+        CallToolRequestMethod, CallToolRequestParam, CallToolResult, ConstString, IntoContents,
+        JsonObject, ListToolsRequestMethod, ToolArguments,
+    },
+    service::{ConnectionState, RequestContext},
 };
+
+mod dynamic;
+pub use dynamic::*;
+
 /// A shortcut for generating a JSON schema for a type.
+#[cfg(feature = "schemars")]
 pub fn schema_for_type<T: JsonSchema>() -> JsonObject {
     let schema = schemars::r#gen::SchemaGenerator::default().into_root_schema_for::<T>();
     let object = serde_json::to_value(schema).expect("failed to serialize schema");
     match object {
-        serde_json::Value::Object(object) => object,
+        serde_json::Value::Object(mut object) => {
+            merge_flattened_schemas(&mut object);
+            object
+        }
         _ => panic!("unexpected schema value"),
     }
 }
 
+/// Without the `schemars` feature no schema is generated, every type gets the permissive
+/// `{"type": "object"}`.
+#[cfg(not(feature = "schemars"))]
+pub fn schema_for_type<T: JsonSchema>() -> JsonObject {
+    let mut object = JsonObject::new();
+    object.insert("type".into(), serde_json::Value::from("object"));
+    object
+}
+
+/// Merge the sub-schemas of `#[serde(flatten)]` fields into the top level `properties` and
+/// `required`.
+///
+/// A flattened type whose schema is a reference ends up as a `$ref` or an `allOf` entry next
+/// to the other properties, which many clients ignore, so their fields would be missing.
+#[cfg(feature = "schemars")]
+fn merge_flattened_schemas(schema: &mut JsonObject) {
+    let mut flattened = Vec::new();
+    if let Some(reference) = schema.remove("$ref") {
+        flattened.push(serde_json::json!({ "$ref": reference }));
+    }
+    if let Some(serde_json::Value::Array(all_of)) = schema.remove("allOf") {
+        flattened.extend(all_of);
+    }
+    let mut unmerged = Vec::new();
+    for sub_schema in flattened {
+        let resolved = match sub_schema
+            .get("$ref")
+            .and_then(|reference| reference.as_str())
+        {
+            Some(reference) => resolve_local_reference(schema, reference).cloned(),
+            None => sub_schema.as_object().cloned(),
+        };
+        let Some(resolved) = resolved.filter(|resolved| resolved.contains_key("properties")) else {
+            unmerged.push(sub_schema);
+            continue;
+        };
+        if let Some(serde_json::Value::Object(sub_properties)) = resolved.get("properties") {
+            let properties = schema
+                .entry("properties")
+                .or_insert_with(|| serde_json::Value::Object(JsonObject::new()));
+            if let Some(properties) = properties.as_object_mut() {
+                for (name, property) in sub_properties {
+                    properties
+                        .entry(name.clone())
+                        .or_insert_with(|| property.clone());
+                }
+            }
+        }
+        if let Some(serde_json::Value::Array(sub_required)) = resolved.get("required") {
+            let required = schema
+                .entry("required")
+                .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+            if let Some(required) = required.as_array_mut() {
+                for name in sub_required {
+                    if !required.contains(name) {
+                        required.push(name.clone());
+                    }
+                }
+            }
+        }
+        schema
+            .entry("type")
+            .or_insert_with(|| serde_json::Value::from("object"));
+    }
+    if !unmerged.is_empty() {
+        schema.insert("allOf".into(), serde_json::Value::Array(unmerged));
+    }
+}
+
+/// Find `#/definitions/Name` or `#/$defs/Name` in the root schema.
+#[cfg(feature = "schemars")]
+fn resolve_local_reference<'a>(root: &'a JsonObject, reference: &str) -> Option<&'a JsonObject> {
+    let (section, name) = reference.strip_prefix("#/")?.split_once('/')?;
+    root.get(section)?.get(name)?.as_object()
+}
+
 /// Call [`schema_for_type`] with a cache
 pub fn cached_schema_for_type<T: JsonSchema + std::any::Any>() -> Arc<JsonObject> {
-    thread_local! {
-        static CACHE_FOR_TYPE: std::sync::RwLock<HashMap<TypeId, Arc<JsonObject>>> = Default::default();
-    };
-    CACHE_FOR_TYPE.with(|cache| {
-        if let Some(x) = cache
-            .read()
+    // shared by the threads, so the schema of a type is one `Arc` the validators are cached by
+    static CACHE_FOR_TYPE: std::sync::OnceLock<
+        std::sync::RwLock<HashMap<TypeId, Arc<JsonObject>>>,
+    > = std::sync::OnceLock::new();
+    let cache = CACHE_FOR_TYPE.get_or_init(Default::default);
+    if let Some(x) = cache
+        .read()
+        .expect("schema cache lock poisoned")
+        .get(&TypeId::of::<T>())
+    {
+        x.clone()
+    } else {
+        let schema = schema_for_type::<T>();
+        cache
+            .write()
             .expect("schema cache lock poisoned")
-            .get(&TypeId::of::<T>())
-        {
-            x.clone()
-        } else {
-            let schema = schema_for_type::<T>();
-            let schema = Arc::new(schema);
-            cache
-                .write()
-                .expect("schema cache lock poisoned")
-                .insert(TypeId::of::<T>(), schema.clone());
-            schema
-        }
-    })
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Arc::new(schema))
+            .clone()
+    }
 }
 
 /// Deserialize a JSON object into a type
@@ -55,11 +147,144 @@ pub fn parse_json_object<T: DeserializeOwned>(input: JsonObject) -> Result<T, cr
         )
     })
 }
+
+/// Deserialize the arguments of a `#[tool(positional)]` tool.
+///
+/// Arguments sent as a JSON array are named after the fields of `T` in declaration order and
+/// checked against the input schema like named arguments, an object is parsed as usual.
+pub fn parse_positional_arguments<T: DeserializeOwned>(
+    input: ToolArguments,
+    input_schema: &Arc<JsonObject>,
+) -> Result<T, crate::Error> {
+    let object = match input {
+        // named arguments are already checked by the tool box
+        ToolArguments::Named(object) => object,
+        ToolArguments::Positional(values) => {
+            let names = field_names::<T>();
+            if values.len() > names.len() {
+                return Err(crate::Error::invalid_params(
+                    format!(
+                        "expect at most {} positional arguments, got {}",
+                        names.len(),
+                        values.len()
+                    ),
+                    None,
+                ));
+            }
+            let object = names
+                .iter()
+                .map(|name| name.to_string())
+                .zip(values)
+                .collect::<JsonObject>();
+            #[cfg(feature = "schema-validation")]
+            validate_arguments(input_schema, Some(&object))?;
+            #[cfg(not(feature = "schema-validation"))]
+            let _ = input_schema;
+            object
+        }
+    };
+    parse_json_object(object)
+}
+
+/// The fields of a struct in declaration order, as serde names them.
+///
+/// Types which aren't deserialized as a struct, like a struct with flattened fields, have none.
+fn field_names<T: DeserializeOwned>() -> &'static [&'static str] {
+    use serde::de::{self, Visitor};
+
+    struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> de::Deserializer<'de> for FieldNames<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _: &'static str,
+            fields: &'static [&'static str],
+            _: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(de::Error::custom("only the field names are read"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+            option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+            ignored_any
+        }
+    }
+
+    let mut names: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut names));
+    names
+}
+
+/// Add the tool methods to `methods`, used by the servers with a tool box.
+pub fn with_tool_methods(mut methods: Vec<&'static str>) -> Vec<&'static str> {
+    for method in [ListToolsRequestMethod::VALUE, CallToolRequestMethod::VALUE] {
+        if !methods.contains(&method) {
+            methods.push(method);
+        }
+    }
+    methods
+}
+
+/// Run a blocking tool on the blocking thread pool, used by `#[tool(blocking)]`.
+///
+/// A panic in the tool is reported as an internal error.
+pub async fn call_blocking<F>(tool: F) -> Result<CallToolResult, crate::Error>
+where
+    F: FnOnce() -> Result<CallToolResult, crate::Error> + Send + 'static,
+{
+    tokio::task::spawn_blocking(tool).await.map_err(|e| {
+        tracing::error!(error = %e, "blocking tool failed");
+        crate::Error::internal_error(format!("blocking tool failed: {e}"), None)
+    })?
+}
+
+/// Run a tool with a time limit, used by `#[tool(timeout_ms = ..)]`.
+///
+/// The tool is cancelled once the limit is reached and the call fails with an internal error.
+pub async fn call_with_timeout<F>(
+    timeout: Duration,
+    tool: F,
+) -> Result<CallToolResult, crate::Error>
+where
+    F: Future<Output = Result<CallToolResult, crate::Error>>,
+{
+    tokio::time::timeout(timeout, tool)
+        .await
+        .unwrap_or_else(|_| {
+            let timeout_ms = timeout.as_millis() as u64;
+            tracing::warn!(timeout_ms, "tool timed out");
+            Err(crate::Error::internal_error(
+                format!("tool timed out after {timeout_ms}ms"),
+                Some(serde_json::json!({ "timeoutMs": timeout_ms })),
+            ))
+        })
+}
+
+/// Validate the arguments of a tool call against the tool's input schema.
+///
+/// This catches the constraints serde doesn't check, like ranges or string patterns. The
+/// schema is compiled on its first validation, and reused as long as it's alive.
+#[cfg(feature = "schema-validation")]
+pub fn validate_arguments(
+    schema: &Arc<JsonObject>,
+    arguments: Option<&JsonObject>,
+) -> Result<(), crate::Error> {
+    crate::model::validate_against_input_schema(schema, arguments)
+}
+
 pub struct ToolCallContext<'service, S> {
     request_context: RequestContext<RoleServer>,
     service: &'service S,
     name: Cow<'static, str>,
-    arguments: Option<JsonObject>,
+    arguments: Option<ToolArguments>,
 }
 
 impl<'service, S> ToolCallContext<'service, S> {
@@ -78,6 +303,28 @@ impl<'service, S> ToolCallContext<'service, S> {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// The arguments given by name, positional arguments are rejected as only the
+    /// `#[tool(positional)]` tools accept them.
+    pub fn named_arguments(&self) -> Result<Option<&JsonObject>, crate::Error> {
+        match &self.arguments {
+            Some(ToolArguments::Named(object)) => Ok(Some(object)),
+            Some(ToolArguments::Positional(_)) => Err(positional_arguments_rejected()),
+            None => Ok(None),
+        }
+    }
+
+    fn take_named_arguments(&mut self) -> Result<Option<JsonObject>, crate::Error> {
+        match self.arguments.take() {
+            Some(ToolArguments::Named(object)) => Ok(Some(object)),
+            Some(ToolArguments::Positional(_)) => Err(positional_arguments_rejected()),
+            None => Ok(None),
+        }
+    }
+}
+
+fn positional_arguments_rejected() -> crate::Error {
+    crate::Error::invalid_params("this tool doesn't accept positional arguments", None)
 }
 
 pub trait FromToolCallContextPart<'a, S>: Sized {
@@ -91,7 +338,7 @@ pub trait IntoCallToolResult {
 }
 impl IntoCallToolResult for () {
     fn into_call_tool_result(self) -> Result<CallToolResult, crate::Error> {
-        Ok(CallToolResult::success(vec![]))
+        Ok(CallToolResult::empty_success())
     }
 }
 
@@ -168,6 +415,7 @@ pub struct Parameter<K: ConstString, V>(pub K, pub V);
 #[serde(transparent)]
 pub struct Parameters<P>(pub P);
 
+#[cfg(feature = "schemars")]
 impl<P: JsonSchema> JsonSchema for Parameters<P> {
     fn schema_name() -> String {
         P::schema_name()
@@ -189,6 +437,14 @@ impl<'a, S> FromToolCallContextPart<'a, S> for CancellationToken {
     }
 }
 
+impl<'a, S> FromToolCallContextPart<'a, S> for ConnectionState {
+    fn from_tool_call_context_part(
+        context: ToolCallContext<'a, S>,
+    ) -> Result<(Self, ToolCallContext<'a, S>), crate::Error> {
+        Ok((context.request_context.connection_state().clone(), context))
+    }
+}
+
 impl<'a, S> FromToolCallContextPart<'a, S> for Callee<'a, S> {
     fn from_tool_call_context_part(
         context: ToolCallContext<'a, S>,
@@ -224,8 +480,7 @@ where
         context: ToolCallContext<'a, S>,
     ) -> Result<(Self, ToolCallContext<'a, S>), crate::Error> {
         let arguments = context
-            .arguments
-            .as_ref()
+            .named_arguments()?
             .ok_or(crate::Error::invalid_params(
                 format!("missing parameter {field}", field = K::VALUE),
                 None,
@@ -255,7 +510,7 @@ where
     fn from_tool_call_context_part(
         mut context: ToolCallContext<'a, S>,
     ) -> Result<(Self, ToolCallContext<'a, S>), crate::Error> {
-        let arguments = context.arguments.take().unwrap_or_default();
+        let arguments = context.take_named_arguments()?.unwrap_or_default();
         let value: P =
             serde_json::from_value(serde_json::Value::Object(arguments)).map_err(|e| {
                 crate::Error::invalid_params(
@@ -271,11 +526,20 @@ impl<'a, S> FromToolCallContextPart<'a, S> for JsonObject {
     fn from_tool_call_context_part(
         mut context: ToolCallContext<'a, S>,
     ) -> Result<(Self, ToolCallContext<'a, S>), crate::Error> {
-        let object = context.arguments.take().unwrap_or_default();
+        let object = context.take_named_arguments()?.unwrap_or_default();
         Ok((object, context))
     }
 }
 
+impl<'a, S> FromToolCallContextPart<'a, S> for ToolArguments {
+    fn from_tool_call_context_part(
+        mut context: ToolCallContext<'a, S>,
+    ) -> Result<(Self, ToolCallContext<'a, S>), crate::Error> {
+        let arguments = context.arguments.take().unwrap_or_default();
+        Ok((arguments, context))
+    }
+}
+
 impl<'s, S> ToolCallContext<'s, S> {
     pub fn invoke<H, A>(self, h: H) -> H::Fut
     where
@@ -386,24 +650,56 @@ impl<S: Send + Sync + 'static + Clone> ToolBoxItem<S> {
     }
 }
 
+/// The tools are kept in the order they are added, which is the order of `tools/list`.
 #[derive(Default)]
 pub struct ToolBox<S> {
-    #[allow(clippy::type_complexity)]
-    pub map: std::collections::HashMap<Cow<'static, str>, ToolBoxItem<S>>,
+    map: IndexMap<Cow<'static, str>, ToolBoxItem<S>>,
 }
 
 impl<S> ToolBox<S> {
     pub fn new() -> Self {
         Self {
-            map: std::collections::HashMap::new(),
+            map: IndexMap::new(),
         }
     }
+    /// Add a tool at the end, a tool with the same name is replaced in place.
     pub fn add(&mut self, item: ToolBoxItem<S>) {
         self.map.insert(item.attr.name.clone(), item);
     }
 
+    /// Add a tool at the end, panicking with its name if a tool with the same name is already
+    /// there, for example two tools of merged modules.
+    #[track_caller]
+    pub fn add_unique(&mut self, item: ToolBoxItem<S>) {
+        if self.map.contains_key(item.name()) {
+            panic!(
+                "duplicate tool name `{}`, tool names must be unique",
+                item.name()
+            );
+        }
+        self.add(item);
+    }
+
+    /// The tool with this name
+    pub fn get(&self, name: &str) -> Option<&ToolBoxItem<S>> {
+        self.map.get(name)
+    }
+
+    /// The tools, in the order of `tools/list`
+    pub fn items(&self) -> impl Iterator<Item = &ToolBoxItem<S>> {
+        self.map.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
     pub fn remove<H, A>(&mut self, name: &str) {
-        self.map.remove(name);
+        self.map.shift_remove(name);
     }
 
     pub async fn call(
@@ -411,9 +707,16 @@ impl<S> ToolBox<S> {
         context: ToolCallContext<'_, S>,
     ) -> Result<CallToolResult, crate::Error> {
         let item = self
-            .map
             .get(context.name())
             .ok_or_else(|| crate::Error::invalid_params("tool not found", None))?;
+        if let Some(message) = item.attr.deprecation() {
+            tracing::warn!(tool = %item.attr.name, "deprecated tool called: {message}");
+        }
+        // positional arguments are checked by the tool once named, or rejected
+        #[cfg(feature = "schema-validation")]
+        if let Ok(arguments) = context.named_arguments() {
+            validate_arguments(&item.attr.input_schema, arguments)?;
+        }
         (item.call)(context).await
     }
 
@@ -426,7 +729,7 @@ impl<S> ToolBox<S> {
 #[macro_export]
 macro_rules! tool_box {
     (@pin_add $callee: ident, $attr: expr, $f: expr) => {
-        $callee.add(ToolBoxItem::new($attr, |context| Box::pin($f(context))));
+        $callee.add_unique(ToolBoxItem::new($attr, |context| Box::pin($f(context))));
     };
     ($server: ident { $($tool: ident),* $(,)?} ) => {
         $crate::tool_box!($server { $($tool),* }  tool_box);
@@ -470,5 +773,11 @@ macro_rules! tool_box {
             let context = $crate::handler::server::tool::ToolCallContext::new(self, call_tool_request_param, context);
             Self::$tool_box().call(context).await
         }
+
+        fn supported_methods(&self) -> Vec<&'static str> {
+            $crate::handler::server::tool::with_tool_methods(
+                $crate::ServerHandler::get_info(self).capabilities.supported_methods(),
+            )
+        }
     }
 }
@@ -0,0 +1,187 @@
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use futures::{FutureExt, future::BoxFuture};
+use indexmap::IndexMap;
+
+use crate::{
+    RoleServer, ServerHandler,
+    model::{
+        CallToolRequestParam, CallToolResult, JsonObject, ListToolsResult, PaginatedRequestParam,
+        ServerCapabilities, ServerInfo, Tool, ToolArguments,
+    },
+    service::{Peer, RequestContext},
+};
+
+type DynamicToolCall = Arc<
+    dyn Fn(JsonObject) -> BoxFuture<'static, Result<CallToolResult, crate::Error>> + Send + Sync,
+>;
+
+struct DynamicTool {
+    attr: Tool,
+    call: DynamicToolCall,
+}
+
+#[derive(Default)]
+struct RegistryState {
+    tools: IndexMap<Cow<'static, str>, DynamicTool>,
+    // the connections told when the tools change
+    peers: Vec<Peer<RoleServer>>,
+}
+
+/// Tools registered and unregistered at runtime, for servers whose tools aren't known at
+/// compile time, like plugin hosts.
+///
+/// The registry is a [`ServerHandler`] answering `tools/list` and `tools/call`, clones share
+/// the same tools so one can be served on each connection while another registers. A handler
+/// with more to offer answers with [`tools`](Self::tools), delegates to
+/// [`call_tool`](Self::call_tool), and calls [`subscribe`](Self::subscribe) in its `set_peer`.
+///
+/// Every change sends `notifications/tools/list_changed` to the connections serving it.
+///
+/// ```rust,ignore
+/// let registry = DynamicToolRegistry::new();
+/// registry
+///     .register(Tool::new("echo", "Echo the arguments", schema), |arguments| async move {
+///         Ok(CallToolResult::success(vec![Content::json(arguments)?]))
+///     })
+///     .await;
+/// let server = registry.clone().serve(transport).await?;
+/// ```
+#[derive(Clone, Default)]
+pub struct DynamicToolRegistry {
+    state: Arc<Mutex<RegistryState>>,
+}
+
+impl std::fmt::Debug for DynamicToolRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.lock();
+        f.debug_struct("DynamicToolRegistry")
+            .field("tools", &state.tools.keys().collect::<Vec<_>>())
+            .field("peers", &state.peers.len())
+            .finish()
+    }
+}
+
+impl DynamicToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RegistryState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Add a tool at the end, a tool with the same name is replaced in place.
+    pub async fn register<F, Fut>(&self, tool: Tool, call: F)
+    where
+        F: Fn(JsonObject) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<CallToolResult, crate::Error>> + Send + 'static,
+    {
+        let call: DynamicToolCall = Arc::new(move |arguments| call(arguments).boxed());
+        self.lock()
+            .tools
+            .insert(tool.name.clone(), DynamicTool { attr: tool, call });
+        self.notify_list_changed().await;
+    }
+
+    /// Remove a tool, returning whether it was registered.
+    pub async fn unregister(&self, name: &str) -> bool {
+        let removed = self.lock().tools.shift_remove(name).is_some();
+        if removed {
+            self.notify_list_changed().await;
+        }
+        removed
+    }
+
+    /// The registered tools in the order they were added
+    pub fn tools(&self) -> Vec<Tool> {
+        self.lock()
+            .tools
+            .values()
+            .map(|tool| tool.attr.clone())
+            .collect()
+    }
+
+    /// Tell `peer` when the tools change, until its connection closes.
+    pub fn subscribe(&self, peer: Peer<RoleServer>) {
+        self.lock().peers.push(peer);
+    }
+
+    async fn notify_list_changed(&self) {
+        let peers = {
+            let mut state = self.lock();
+            state.peers.retain(|peer| !peer.is_closed());
+            state.peers.clone()
+        };
+        for peer in peers {
+            if let Err(error) = peer.notify_tool_list_changed().await {
+                tracing::warn!(%error, "fail to notify the tool list change");
+            }
+        }
+    }
+
+    /// Call a registered tool with the arguments of the request, an empty object if missing.
+    pub async fn call_tool(
+        &self,
+        CallToolRequestParam { name, arguments }: CallToolRequestParam,
+    ) -> Result<CallToolResult, crate::Error> {
+        let arguments = match arguments {
+            Some(ToolArguments::Named(object)) => Some(object),
+            Some(ToolArguments::Positional(_)) => {
+                return Err(super::positional_arguments_rejected());
+            }
+            None => None,
+        };
+        let call = {
+            let state = self.lock();
+            let tool = state
+                .tools
+                .get(name.as_ref())
+                .ok_or_else(|| crate::Error::invalid_params("tool not found", None))?;
+            #[cfg(feature = "schema-validation")]
+            super::validate_arguments(&tool.attr.input_schema, arguments.as_ref())?;
+            tool.call.clone()
+        };
+        call(arguments.unwrap_or_default()).await
+    }
+}
+
+impl ServerHandler for DynamicToolRegistry {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_tool_list_changed()
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleServer>) {
+        self.subscribe(peer);
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, crate::Error> {
+        Ok(ListToolsResult {
+            next_cursor: None,
+            tools: self.tools(),
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, crate::Error> {
+        DynamicToolRegistry::call_tool(self, request).await
+    }
+}
@@ -1,28 +1,247 @@
-use serde::Serialize;
+use serde::{Serialize, ser};
+use serde_json::json;
 
-use crate::model::IntoContents;
+use crate::{
+    handler::server::tool::IntoCallToolResult,
+    model::{CallToolResult, IntoContents},
+};
 
 /// Json wrapper
 ///
-/// This is used to tell the SDK to serialize the inner value into json
+/// This is used to tell the SDK to serialize the inner value into json.
+///
+/// If the value can't be serialized (e.g. a map with non-string keys, or a `NaN` that json has
+/// no way to write), the tool call fails with an internal error instead of sending a broken
+/// result to the client.
 pub struct Json<T>(pub T);
 
-impl<T> IntoContents for Json<T>
+impl<T> IntoCallToolResult for Json<T>
 where
     T: Serialize,
 {
-    fn into_contents(self) -> Vec<crate::model::Content> {
-        let result = crate::model::Content::json(self.0);
-        debug_assert!(
-            result.is_ok(),
-            "Json wrapped content should be able to serialized into json"
-        );
-        match result {
-            Ok(content) => vec![content],
+    fn into_call_tool_result(self) -> Result<CallToolResult, crate::Error> {
+        // serde_json silently writes non-finite floats as `null`, look for them first
+        if let Err(e) = self.0.serialize(FiniteFloats) {
+            tracing::error!(error = %e, "tool result contains a float json can't represent");
+            return Err(crate::Error::internal_error(
+                "fail to serialize response to json",
+                Some(json!({"reason": e.to_string()})),
+            ));
+        }
+        match crate::model::Content::json(self.0) {
+            Ok(content) => Ok(CallToolResult::success(vec![content])),
             Err(e) => {
-                tracing::error!("failed to convert json content: {e}");
-                vec![]
+                tracing::error!(error = %e, "failed to serialize tool result into json");
+                Err(e)
             }
         }
     }
 }
+
+impl<T, E> IntoCallToolResult for Result<Json<T>, E>
+where
+    T: Serialize,
+    E: IntoContents,
+{
+    fn into_call_tool_result(self) -> Result<CallToolResult, crate::Error> {
+        match self {
+            Ok(value) => value.into_call_tool_result(),
+            Err(error) => Ok(CallToolResult::error(error.into_contents())),
+        }
+    }
+}
+
+/// A serializer that writes nothing and fails on `NaN` and infinite floats
+struct FiniteFloats;
+
+type Check = Result<(), serde_json::Error>;
+
+fn check_float(value: f64) -> Check {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(ser::Error::custom(format!(
+            "{value} can't be represented in json"
+        )))
+    }
+}
+
+macro_rules! accept {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(fn $method(self, _: $ty) -> Check {
+            Ok(())
+        })*
+    };
+}
+
+impl ser::Serializer for FiniteFloats {
+    type Ok = ();
+    type Error = serde_json::Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    accept!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit_struct(&'static str),
+    );
+
+    fn serialize_f32(self, v: f32) -> Check {
+        check_float(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Check {
+        check_float(v)
+    }
+
+    fn serialize_none(self) -> Check {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Check {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Check {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Check {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Check {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Check {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self, serde_json::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, serde_json::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, serde_json::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(self)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self, serde_json::Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, serde_json::Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(self)
+    }
+}
+
+macro_rules! compound {
+    ($($trait:ident::$method:ident),* $(,)?) => {
+        $(impl ser::$trait for FiniteFloats {
+            type Ok = ();
+            type Error = serde_json::Error;
+
+            fn $method<T: ?Sized + Serialize>(&mut self, value: &T) -> Check {
+                value.serialize(FiniteFloats)
+            }
+
+            fn end(self) -> Check {
+                Ok(())
+            }
+        })*
+    };
+}
+
+compound!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field,
+);
+
+impl ser::SerializeMap for FiniteFloats {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Check {
+        key.serialize(FiniteFloats)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Check {
+        value.serialize(FiniteFloats)
+    }
+
+    fn end(self) -> Check {
+        Ok(())
+    }
+}
+
+macro_rules! compound_struct {
+    ($($trait:ident),* $(,)?) => {
+        $(impl ser::$trait for FiniteFloats {
+            type Ok = ();
+            type Error = serde_json::Error;
+
+            fn serialize_field<T: ?Sized + Serialize>(
+                &mut self,
+                _: &'static str,
+                value: &T,
+            ) -> Check {
+                value.serialize(FiniteFloats)
+            }
+
+            fn end(self) -> Check {
+                Ok(())
+            }
+        })*
+    };
+}
+
+compound_struct!(SerializeStruct, SerializeStructVariant);
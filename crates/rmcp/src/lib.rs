@@ -78,7 +78,10 @@
 //!     let tool_result = service
 //!         .call_tool(CallToolRequestParam {
 //!             name: "git_status".into(),
-//!             arguments: serde_json::json!({ "repo_path": "." }).as_object().cloned(),
+//!             arguments: serde_json::json!({ "repo_path": "." })
+//!                 .as_object()
+//!                 .cloned()
+//!                 .map(Into::into),
 //!         })
 //!         .await?;
 //!     println!("Tool result: {tool_result:#?}");
@@ -107,14 +110,46 @@ pub use service::{RoleServer, serve_server};
 
 pub mod handler;
 pub mod transport;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 
 // re-export
 #[cfg(all(feature = "macros", feature = "server"))]
 pub use paste::paste;
+#[cfg(all(feature = "macros", feature = "client"))]
+pub use rmcp_macros::mcp_client;
 #[cfg(all(feature = "macros", feature = "server"))]
 pub use rmcp_macros::tool;
-#[cfg(all(feature = "macros", feature = "server"))]
+#[cfg(all(feature = "macros", feature = "server", feature = "schemars"))]
 pub use schemars;
+// the schemas only degrade when asked to
+#[cfg(all(
+    feature = "server",
+    not(feature = "schemars"),
+    not(feature = "permissive-schema")
+))]
+compile_error!(
+    "rmcp generates the tool input schemas with `schemars`, enable the `schemars` feature, or \
+     `permissive-schema` to build without it and give every tool the `{\"type\": \"object\"}` schema"
+);
+/// Stands in for `schemars` when the `schemars` feature is disabled, so the code generated by
+/// the [`tool`] macro still compiles.
+///
+/// Every type implements this [`JsonSchema`](schemars::JsonSchema), and its schema is the
+/// permissive `{"type": "object"}`: the tools are listed and called as usual, but a client
+/// can't tell which arguments they take, and `schema-validation` has nothing to check. The
+/// `#[schemars]` attributes are ignored, with a warning.
+#[cfg(all(feature = "server", not(feature = "schemars")))]
+pub mod schemars {
+    #[cfg(feature = "macros")]
+    pub use rmcp_macros::JsonSchema;
+
+    pub trait JsonSchema {}
+
+    impl<T: ?Sized> JsonSchema for T {}
+}
 #[cfg(feature = "macros")]
 pub use serde;
 #[cfg(feature = "macros")]
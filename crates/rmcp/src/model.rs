@@ -1,4 +1,4 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, collections::BTreeMap, sync::Arc};
 mod annotated;
 mod capabilities;
 mod content;
@@ -6,8 +6,11 @@ mod extension;
 mod meta;
 mod prompt;
 mod resource;
+mod resource_template;
+mod sampling;
 mod serde_impl;
 mod tool;
+mod tool_result;
 pub use annotated::*;
 pub use capabilities::*;
 pub use content::*;
@@ -15,9 +18,12 @@ pub use extension::*;
 pub use meta::*;
 pub use prompt::*;
 pub use resource::*;
+pub use resource_template::*;
+pub use sampling::*;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 pub use tool::*;
+pub use tool_result::*;
 
 /// You can use [`crate::object!`] or [`crate::model::object`] to create a json object quickly.
 pub type JsonObject<F = Value> = serde_json::Map<String, F>;
@@ -45,7 +51,7 @@ macro_rules! object {
     };
 }
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Copy, Eq)]
-#[cfg_attr(feature = "server", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct EmptyObject {}
 
 pub trait ConstString: Default {
@@ -278,7 +284,8 @@ pub struct JsonRpcError {
     pub error: ErrorData,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+/// A notification, which is a message without an `id`, or a `null` one as JSON-RPC allows.
+#[derive(Debug, Serialize, Clone, PartialEq)]
 pub struct JsonRpcNotification<N = Notification> {
     pub jsonrpc: JsonRpcVersion2_0,
     #[serde(flatten)]
@@ -335,9 +342,35 @@ impl ErrorData {
     pub fn invalid_request(message: impl Into<Cow<'static, str>>, data: Option<Value>) -> Self {
         Self::new(ErrorCode::INVALID_REQUEST, message, data)
     }
+    /// The peer sent `initialize` again after the handshake
+    pub fn already_initialized() -> Self {
+        Self::invalid_request("already initialized", None)
+    }
+    /// The peer sent a request other than ping before the handshake
+    pub fn not_initialized() -> Self {
+        Self::invalid_request("not initialized", None)
+    }
+    /// The peer cancelled the request before its handler started
+    pub fn request_cancelled(reason: Option<&str>) -> Self {
+        Self::invalid_request(
+            "request cancelled",
+            reason.map(|reason| serde_json::json!({ "reason": reason })),
+        )
+    }
     pub fn method_not_found<M: ConstString>() -> Self {
         Self::new(ErrorCode::METHOD_NOT_FOUND, M::VALUE, None)
     }
+    /// The peer sent a request of a capability this side didn't advertise
+    pub fn capability_not_advertised<M: ConstString>(capability: &str) -> Self {
+        Self::new(
+            ErrorCode::METHOD_NOT_FOUND,
+            format!(
+                "{}: the {capability} capability is not advertised",
+                M::VALUE
+            ),
+            Some(serde_json::json!({ "capability": capability })),
+        )
+    }
     pub fn invalid_params(message: impl Into<Cow<'static, str>>, data: Option<Value>) -> Self {
         Self::new(ErrorCode::INVALID_PARAMS, message, data)
     }
@@ -389,7 +422,29 @@ pub enum JsonRpcMessage<Req = Request, Resp = DefaultResponse, Noti = Notificati
     Error(JsonRpcError),
 }
 
+/// The kind of a [`JsonRpcMessage`], without its content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageKind {
+    Request,
+    Response,
+    Notification,
+    BatchRequest,
+    BatchResponse,
+    Error,
+}
+
 impl<Req, Resp, Not> JsonRpcMessage<Req, Resp, Not> {
+    #[inline]
+    pub const fn classify(&self) -> MessageKind {
+        match self {
+            JsonRpcMessage::Request(_) => MessageKind::Request,
+            JsonRpcMessage::Response(_) => MessageKind::Response,
+            JsonRpcMessage::Notification(_) => MessageKind::Notification,
+            JsonRpcMessage::BatchRequest(_) => MessageKind::BatchRequest,
+            JsonRpcMessage::BatchResponse(_) => MessageKind::BatchResponse,
+            JsonRpcMessage::Error(_) => MessageKind::Error,
+        }
+    }
     #[inline]
     pub const fn request(request: Req, id: RequestId) -> Self {
         JsonRpcMessage::Request(JsonRpcRequest {
@@ -445,6 +500,30 @@ impl<Req, Resp, Not> JsonRpcMessage<Req, Resp, Not> {
             _ => None,
         }
     }
+    pub fn as_request(&self) -> Option<(&Req, &RequestId)> {
+        match self {
+            JsonRpcMessage::Request(r) => Some((&r.request, &r.id)),
+            _ => None,
+        }
+    }
+    pub fn as_response(&self) -> Option<(&Resp, &RequestId)> {
+        match self {
+            JsonRpcMessage::Response(r) => Some((&r.result, &r.id)),
+            _ => None,
+        }
+    }
+    pub fn as_notification(&self) -> Option<&Not> {
+        match self {
+            JsonRpcMessage::Notification(n) => Some(&n.notification),
+            _ => None,
+        }
+    }
+    pub fn as_error(&self) -> Option<(&ErrorData, &RequestId)> {
+        match self {
+            JsonRpcMessage::Error(e) => Some((&e.error, &e.id)),
+            _ => None,
+        }
+    }
     pub fn into_result(self) -> Option<(Result<Resp, ErrorData>, RequestId)> {
         match self {
             JsonRpcMessage::Response(r) => Some((Ok(r.result), r.id)),
@@ -456,12 +535,31 @@ impl<Req, Resp, Not> JsonRpcMessage<Req, Resp, Not> {
 }
 
 /// # Empty result
-/// A response that indicates success but carries no data.
-pub type EmptyResult = EmptyObject;
+/// A response that indicates success but carries no data, besides its `_meta`.
+///
+/// It used to be an alias of [`EmptyObject`], build it with `EmptyResult::default()` or from
+/// `()` instead of `EmptyResult {}`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct EmptyResult {
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+}
+
+impl EmptyResult {
+    /// The result of a ping, echoing the payload of the request if it carried one
+    pub fn ping_echo(request_meta: &Meta) -> Self {
+        let meta = request_meta.get_ping_payload().map(|payload| {
+            let mut meta = Meta::new();
+            meta.set_ping_payload(payload.clone());
+            meta
+        });
+        EmptyResult { meta }
+    }
+}
 
 impl From<()> for EmptyResult {
     fn from(_value: ()) -> Self {
-        EmptyResult {}
+        EmptyResult::default()
     }
 }
 
@@ -469,6 +567,18 @@ impl From<EmptyResult> for () {
     fn from(_value: EmptyResult) {}
 }
 
+impl From<EmptyObject> for EmptyResult {
+    fn from(_value: EmptyObject) -> Self {
+        EmptyResult::default()
+    }
+}
+
+impl From<EmptyResult> for EmptyObject {
+    fn from(_value: EmptyResult) -> Self {
+        EmptyObject {}
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CancelledNotificationParam {
@@ -518,6 +628,19 @@ pub struct InitializeResult {
 pub type ServerInfo = InitializeResult;
 pub type ClientInfo = InitializeRequestParam;
 
+impl InitializeResult {
+    /// The request methods of `requested` which the capabilities the server granted don't
+    /// cover, so a client can turn off what depends on them.
+    pub fn unsupported_client_requests<'a>(&self, requested: &[&'a str]) -> Vec<&'a str> {
+        let supported = self.capabilities.supported_methods();
+        requested
+            .iter()
+            .copied()
+            .filter(|method| !supported.contains(method))
+            .collect()
+    }
+}
+
 impl Default for ServerInfo {
     fn default() -> Self {
         ServerInfo {
@@ -599,6 +722,16 @@ macro_rules! paginated_result {
             pub next_cursor: Option<Cursor>,
             pub $i_item: $t_item,
         }
+
+        impl $t {
+            /// A single page with all the items
+            pub fn with_all_items(items: $t_item) -> Self {
+                Self {
+                    next_cursor: None,
+                    $i_item: items,
+                }
+            }
+        }
     };
 }
 
@@ -623,9 +756,115 @@ pub struct ReadResourceRequestParam {
     pub uri: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct ReadResourceResult {
     pub contents: Vec<ResourceContents>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+}
+
+impl ReadResourceResult {
+    pub fn new(contents: Vec<ResourceContents>) -> Self {
+        Self {
+            contents,
+            meta: None,
+        }
+    }
+
+    /// Honor the range a client asked for, usually `context.meta.get_range()`.
+    ///
+    /// When every content can be sliced, the contents are cut down and the range is echoed back
+    /// in `_meta`. Otherwise the full contents are kept, and the missing range tells the client
+    /// it received everything.
+    pub fn with_range(mut self, range: Option<ResourceRange>) -> Self {
+        let Some(range) = range else {
+            return self;
+        };
+        let Some(contents) = self
+            .contents
+            .iter()
+            .map(|content| content.slice(range))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return self;
+        };
+        self.contents = contents;
+        self.meta.get_or_insert_with(Meta::new).set_range(range);
+        self
+    }
+
+    /// Honor the encoding a client prefers, usually `context.meta.get_encoding()`.
+    ///
+    /// Each content which can be represented that way is re-encoded, the others are kept as
+    /// they are, so a binary blob stays a blob when text is preferred.
+    pub fn with_encoding(mut self, encoding: Option<ResourceEncoding>) -> Self {
+        if let Some(encoding) = encoding {
+            for content in &mut self.contents {
+                if let Some(encoded) = content.encode(encoding) {
+                    *content = encoded;
+                }
+            }
+        }
+        self
+    }
+
+    /// The result of a batch read, see [`Meta::get_uris`]: the contents of every resource read
+    /// in order, for clients unaware of batches, and one entry per uri in `_meta`.
+    pub fn from_entries(entries: Vec<ReadResourceEntry>) -> Self {
+        let contents = entries
+            .iter()
+            .flat_map(|entry| entry.contents.iter().cloned())
+            .collect();
+        let mut meta = Meta::new();
+        meta.set_read_entries(&entries);
+        Self {
+            contents,
+            meta: Some(meta),
+        }
+    }
+
+    /// The entries of a batch read in the order of the uris, `None` when the server read a
+    /// single resource.
+    pub fn entries(&self) -> Option<Vec<ReadResourceEntry>> {
+        self.meta.as_ref().and_then(Meta::get_read_entries)
+    }
+
+    /// The range these contents cover, `None` means the contents are complete
+    pub fn range(&self) -> Option<ResourceRange> {
+        self.meta.as_ref().and_then(Meta::get_range)
+    }
+}
+
+/// The outcome of reading one uri of a batch, its contents or why it failed
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadResourceEntry {
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contents: Vec<ResourceContents>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorData>,
+}
+
+impl ReadResourceEntry {
+    pub fn new(uri: impl Into<String>, result: Result<ReadResourceResult, ErrorData>) -> Self {
+        let (contents, error) = match result {
+            Ok(result) => (result.contents, None),
+            Err(error) => (Vec::new(), Some(error)),
+        };
+        Self {
+            uri: uri.into(),
+            contents,
+            error,
+        }
+    }
+
+    pub fn into_result(self) -> Result<Vec<ResourceContents>, ErrorData> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.contents),
+        }
+    }
 }
 
 pub type ReadResourceRequest = Request<ReadResourceRequestMethod, ReadResourceRequestParam>;
@@ -680,8 +919,49 @@ pub type PromptListChangedNotification = NotificationNoParam<PromptListChangedNo
 
 const_string!(ToolListChangedNotificationMethod = "notifications/tools/list_changed");
 pub type ToolListChangedNotification = NotificationNoParam<ToolListChangedNotificationMethod>;
+
+// Not part of the MCP specification, only the peers built with the same extension understand it
+#[cfg(feature = "instructions-changed")]
+const_string!(InstructionsChangedNotificationMethod = "notifications/instructions_changed");
+/// The new instructions of the server, `None` when it doesn't have any anymore
+#[cfg(feature = "instructions-changed")]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructionsChangedNotificationParam {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+}
+#[cfg(feature = "instructions-changed")]
+pub type InstructionsChangedNotification =
+    Notification<InstructionsChangedNotificationMethod, InstructionsChangedNotificationParam>;
+
+// Not part of the MCP specification either
+#[cfg(feature = "resource-delta")]
+const_string!(ResourceListDeltaNotificationMethod = "notifications/resources/list_delta");
+/// The URIs of the resources added to and removed from the list of the server since its
+/// previous delta, a client applies them in order instead of listing the resources again
+#[cfg(feature = "resource-delta")]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceListDeltaNotificationParam {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<String>,
+}
+#[cfg(feature = "resource-delta")]
+pub type ResourceListDeltaNotification =
+    Notification<ResourceListDeltaNotificationMethod, ResourceListDeltaNotificationParam>;
+
+// Not part of the MCP specification either, sent by both sides
+#[cfg(feature = "shutdown-notification")]
+const_string!(ShutdownNotificationMethod = "notifications/shutdown");
+/// Sent by a peer about to close the connection, so the other side can stop sending
+#[cfg(feature = "shutdown-notification")]
+pub type ShutdownNotification = NotificationNoParam<ShutdownNotificationMethod>;
 // 日志相关
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Copy)]
+/// The syslog severity of a log message, ordered from the least to the most severe.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Copy)]
 #[serde(rename_all = "lowercase")] //match spec
 pub enum LoggingLevel {
     Debug,
@@ -694,6 +974,13 @@ pub enum LoggingLevel {
     Emergency,
 }
 
+impl LoggingLevel {
+    /// Whether a message at this level passes a filter set to `min`.
+    pub fn enabled_for(self, min: LoggingLevel) -> bool {
+        self >= min
+    }
+}
+
 const_string!(SetLevelRequestMethod = "logging/setLevel");
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -759,15 +1046,19 @@ pub struct CreateMessageRequestParam {
     pub metadata: Option<Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct ModelPreferences {
+    /// Model names in order of preference, a name must not be empty
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hints: Option<Vec<ModelHint>>,
+    /// How much cost matters, from 0 to 1
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cost_priority: Option<f32>,
+    /// How much speed matters, from 0 to 1
     #[serde(skip_serializing_if = "Option::is_none")]
     pub speed_priority: Option<f32>,
+    /// How much intelligence matters, from 0 to 1
     #[serde(skip_serializing_if = "Option::is_none")]
     pub intelligence_priority: Option<f32>,
 }
@@ -783,6 +1074,28 @@ pub struct ModelHint {
 pub struct CompleteRequestParam {
     pub r#ref: Reference,
     pub argument: ArgumentInfo,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<CompletionContext>,
+}
+
+impl CompleteRequestParam {
+    /// The value already resolved for another argument, passed as context of the completion.
+    pub fn context_argument(&self, name: &str) -> Option<&str> {
+        self.context
+            .as_ref()?
+            .arguments
+            .as_ref()?
+            .get(name)
+            .map(String::as_str)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionContext {
+    /// The arguments already resolved, by name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<BTreeMap<String, String>>,
 }
 
 pub type CompleteRequest = Request<CompleteRequestMethod, CompleteRequestParam>;
@@ -850,10 +1163,13 @@ pub struct ListRootsResult {
 const_string!(RootsListChangedNotificationMethod = "notifications/roots/list_changed");
 pub type RootsListChangedNotification = NotificationNoParam<RootsListChangedNotificationMethod>;
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct CallToolResult {
     pub content: Vec<Content>,
+    /// A json value for clients which can use the result as data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
 }
@@ -862,15 +1178,38 @@ impl CallToolResult {
     pub fn success(content: Vec<Content>) -> Self {
         CallToolResult {
             content,
+            structured_content: None,
             is_error: Some(false),
         }
     }
     pub fn error(content: Vec<Content>) -> Self {
         CallToolResult {
             content,
+            structured_content: None,
             is_error: Some(true),
         }
     }
+    /// A successful result for a tool which doesn't produce anything.
+    ///
+    /// It carries a single empty text content: an empty `content` is valid, but some clients
+    /// reject it or show it as a failure. This is what a tool returning `()` sends.
+    pub fn empty_success() -> Self {
+        CallToolResult::success(vec![Content::text("")])
+    }
+    /// A successful result carrying `value` as structured content, with the serialized value as
+    /// a text content for clients which don't read structured content.
+    pub fn structured(value: Value) -> Self {
+        CallToolResult {
+            content: vec![Content::text(value.to_string())],
+            structured_content: Some(value),
+            is_error: Some(false),
+        }
+    }
+    /// Whether the tool ran and reported an error. Such a result is still a successful call,
+    /// unlike a failed request, e.g. to an unknown tool, which is an error response.
+    pub fn is_tool_error(&self) -> bool {
+        self.is_error == Some(true)
+    }
 }
 
 const_string!(ListToolsRequestMethod = "tools/list");
@@ -887,7 +1226,38 @@ const_string!(CallToolRequestMethod = "tools/call");
 pub struct CallToolRequestParam {
     pub name: Cow<'static, str>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub arguments: Option<JsonObject>,
+    pub arguments: Option<ToolArguments>,
+}
+
+impl CallToolRequestParam {
+    /// A call with the arguments given by name
+    pub fn named(name: impl Into<Cow<'static, str>>, arguments: JsonObject) -> Self {
+        Self {
+            name: name.into(),
+            arguments: Some(ToolArguments::Named(arguments)),
+        }
+    }
+
+    /// A call with the arguments given by position, for the `#[tool(positional)]` tools
+    pub fn positional(
+        name: impl Into<Cow<'static, str>>,
+        arguments: impl IntoIterator<Item = Value>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            arguments: Some(ToolArguments::Positional(arguments.into_iter().collect())),
+        }
+    }
+
+    /// The arguments, if they were given by name
+    pub fn named_arguments(&self) -> Option<&JsonObject> {
+        self.arguments.as_ref()?.as_named()
+    }
+
+    /// The arguments, if they were sent as a JSON array
+    pub fn positional_arguments(&self) -> Option<&Vec<Value>> {
+        self.arguments.as_ref()?.as_positional()
+    }
 }
 
 pub type CallToolRequest = Request<CallToolRequestMethod, CallToolRequestParam>;
@@ -919,12 +1289,12 @@ pub struct GetPromptResult {
 macro_rules! ts_union {
     (
         export type $U: ident =
-            $(|)?$($V: ident)|*;
+            $(|)?$($(#[$meta: meta])* $V: ident)|*;
     ) => {
         #[derive(Debug, Serialize, Deserialize, Clone)]
         #[serde(untagged)]
         pub enum $U {
-            $($V($V),)*
+            $($(#[$meta])* $V($V),)*
         }
     };
 }
@@ -951,7 +1321,8 @@ ts_union!(
     | CancelledNotification
     | ProgressNotification
     | InitializedNotification
-    | RootsListChangedNotification;
+    | RootsListChangedNotification
+    | #[cfg(feature = "shutdown-notification")] ShutdownNotification;
 );
 
 ts_union!(
@@ -960,7 +1331,7 @@ ts_union!(
 
 impl ClientResult {
     pub fn empty(_: ()) -> ClientResult {
-        ClientResult::EmptyResult(EmptyResult {})
+        ClientResult::EmptyResult(EmptyResult::default())
     }
 }
 
@@ -981,7 +1352,10 @@ ts_union!(
     | ResourceUpdatedNotification
     | ResourceListChangedNotification
     | ToolListChangedNotification
-    | PromptListChangedNotification;
+    | PromptListChangedNotification
+    | #[cfg(feature = "instructions-changed")] InstructionsChangedNotification
+    | #[cfg(feature = "resource-delta")] ResourceListDeltaNotification
+    | #[cfg(feature = "shutdown-notification")] ShutdownNotification;
 );
 
 ts_union!(
@@ -1001,7 +1375,7 @@ ts_union!(
 
 impl ServerResult {
     pub fn empty(_: ()) -> ServerResult {
-        ServerResult::EmptyResult(EmptyResult {})
+        ServerResult::EmptyResult(EmptyResult::default())
     }
 }
 
@@ -1065,6 +1439,117 @@ mod tests {
         assert_eq!(json, raw);
     }
 
+    #[test]
+    fn test_null_id() {
+        let raw = json!({ "jsonrpc": "2.0", "id": null, "method": "notifications/initialized" });
+        let message: ClientJsonRpcMessage =
+            serde_json::from_value(raw).expect("a null id is allowed on a notification");
+        assert!(matches!(
+            message.into_notification(),
+            Some(ClientNotification::InitializedNotification(_))
+        ));
+
+        let raw = json!({ "jsonrpc": "2.0", "id": 1, "method": "notifications/initialized" });
+        assert!(serde_json::from_value::<ClientJsonRpcMessage>(raw).is_err());
+
+        for raw in [
+            json!({ "jsonrpc": "2.0", "id": null, "result": {} }),
+            json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32600, "message": "no" } }),
+        ] {
+            let message = serde_json::from_value::<ServerJsonRpcMessage>(raw.clone());
+            assert!(message.is_err(), "{raw}");
+        }
+    }
+
+    #[test]
+    fn test_classify_message() {
+        let messages = [
+            (
+                json!({ "jsonrpc": "2.0", "id": 1, "method": "ping", "params": {} }),
+                MessageKind::Request,
+            ),
+            (
+                json!({ "jsonrpc": "2.0", "id": 1, "result": {} }),
+                MessageKind::Response,
+            ),
+            (
+                json!({ "jsonrpc": "2.0", "method": "notifications/initialized", "params": {} }),
+                MessageKind::Notification,
+            ),
+            (
+                json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32600, "message": "no" } }),
+                MessageKind::Error,
+            ),
+            (
+                json!([{ "jsonrpc": "2.0", "id": 1, "method": "ping", "params": {} }]),
+                MessageKind::BatchRequest,
+            ),
+            (
+                json!([{ "jsonrpc": "2.0", "id": 1, "result": {} }]),
+                MessageKind::BatchResponse,
+            ),
+        ];
+        for (raw, kind) in messages {
+            let message: JsonRpcMessage =
+                serde_json::from_value(raw.clone()).expect("invalid message");
+            let message: JsonRpcMessage =
+                serde_json::from_value(serde_json::to_value(&message).unwrap()).unwrap();
+            assert_eq!(message.classify(), kind, "{raw}");
+            assert_eq!(message.as_request().is_some(), kind == MessageKind::Request);
+            assert_eq!(
+                message.as_response().is_some(),
+                kind == MessageKind::Response
+            );
+            assert_eq!(
+                message.as_notification().is_some(),
+                kind == MessageKind::Notification
+            );
+            assert_eq!(message.as_error().is_some(), kind == MessageKind::Error);
+        }
+    }
+
+    #[test]
+    fn test_call_tool_result() {
+        let error = CallToolResult::error(vec![Content::text("boom")]);
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({ "content": [{ "type": "text", "text": "boom" }], "isError": true })
+        );
+        let success = CallToolResult::success(vec![Content::text("ok")]);
+        assert_eq!(
+            serde_json::to_value(&success).unwrap()["isError"],
+            json!(false)
+        );
+
+        let value = json!({ "temperature": 21.5, "unit": "celsius" });
+        let structured = CallToolResult::structured(value.clone());
+        let json = serde_json::to_value(&structured).unwrap();
+        assert_eq!(json["structuredContent"], value);
+        assert_eq!(json["isError"], json!(false));
+        let text = structured.content[0].as_text().unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&text.text).unwrap(), value);
+    }
+
+    #[test]
+    fn test_content_annotations() {
+        let content = Content::text("for the model only")
+            .with_annotations(Annotations::for_audience(vec![Role::Assistant], 0.8));
+        assert_eq!(content.audience(), Some(&vec![Role::Assistant]));
+        assert_eq!(content.priority(), Some(0.8));
+
+        // serialized as a string, as a `Value` would widen the priority to an f64
+        let json = serde_json::to_string(&content).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"text","text":"for the model only","annotations":{"audience":["assistant"],"priority":0.8}}"#
+        );
+        assert_eq!(serde_json::from_str::<Content>(&json).unwrap(), content);
+
+        // and left out when there's none
+        let json = serde_json::to_value(Content::text("plain")).unwrap();
+        assert_eq!(json, json!({ "type": "text", "text": "plain" }));
+    }
+
     #[test]
     fn test_request_conversion() {
         let raw = json!( {
@@ -1198,4 +1683,14 @@ mod tests {
         let v2 = ProtocolVersion::V_2025_03_26;
         assert!(v1 < v2);
     }
+
+    #[test]
+    fn test_logging_level_order() {
+        assert!(LoggingLevel::Error >= LoggingLevel::Warning);
+        assert!(LoggingLevel::Debug < LoggingLevel::Info);
+        assert!(LoggingLevel::Emergency > LoggingLevel::Alert);
+        assert!(LoggingLevel::Error.enabled_for(LoggingLevel::Warning));
+        assert!(LoggingLevel::Warning.enabled_for(LoggingLevel::Warning));
+        assert!(!LoggingLevel::Info.enabled_for(LoggingLevel::Warning));
+    }
 }
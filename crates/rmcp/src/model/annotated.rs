@@ -33,6 +33,20 @@ impl Annotations {
             audience: None,
         }
     }
+
+    /// Creates a new Annotations instance for content blocks, telling who the content is meant
+    /// for and how important it is, from 0.0 to 1.0
+    pub fn for_audience(audience: Vec<Role>, priority: f32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&priority),
+            "Priority {priority} must be between 0.0 and 1.0"
+        );
+        Annotations {
+            audience: Some(audience),
+            priority: Some(priority),
+            timestamp: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -63,6 +77,13 @@ impl<T: AnnotateAble> Annotated<T> {
     pub fn remove_annotation(&mut self) -> Option<Annotations> {
         self.annotations.take()
     }
+    /// Replace the annotations, if any
+    pub fn with_annotations(self, annotations: Annotations) -> Annotated<T> {
+        Annotated {
+            raw: self.raw,
+            annotations: Some(annotations),
+        }
+    }
     pub fn audience(&self) -> Option<&Vec<Role>> {
         self.annotations.as_ref().and_then(|a| a.audience.as_ref())
     }
@@ -3,7 +3,13 @@ use std::{collections::BTreeMap, marker::PhantomData};
 use paste::paste;
 use serde::{Deserialize, Serialize};
 
-use super::JsonObject;
+use super::{
+    CallToolRequestMethod, CompleteRequestMethod, ConstString, CreateMessageRequestMethod,
+    GetPromptRequestMethod, JsonObject, ListPromptsRequestMethod,
+    ListResourceTemplatesRequestMethod, ListResourcesRequestMethod, ListRootsRequestMethod,
+    ListToolsRequestMethod, PingRequestMethod, ReadResourceRequestMethod, SetLevelRequestMethod,
+    SubscribeRequestMethod, UnsubscribeRequestMethod,
+};
 pub type ExperimentalCapabilities = BTreeMap<String, JsonObject>;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
@@ -85,6 +91,188 @@ pub struct ServerCapabilities {
     pub tools: Option<ToolsCapability>,
 }
 
+/// A flag is only kept if it's set on both sides.
+fn intersect_flag(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+    (a == Some(true) && b == Some(true)).then_some(true)
+}
+
+fn intersect_with<T>(a: &Option<T>, b: &Option<T>, f: impl FnOnce(&T, &T) -> T) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(f(a, b)),
+        _ => None,
+    }
+}
+
+/// Keep the fields present on both sides with the same value.
+fn intersect_object(a: &JsonObject, b: &JsonObject) -> JsonObject {
+    a.iter()
+        .filter(|(key, value)| b.get(*key) == Some(*value))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+fn intersect_experimental(
+    a: &ExperimentalCapabilities,
+    b: &ExperimentalCapabilities,
+) -> ExperimentalCapabilities {
+    a.iter()
+        .filter_map(|(key, value)| Some((key.clone(), intersect_object(value, b.get(key)?))))
+        .collect()
+}
+
+impl PromptsCapability {
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self {
+            list_changed: intersect_flag(self.list_changed, other.list_changed),
+        }
+    }
+}
+
+impl ResourcesCapability {
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self {
+            subscribe: intersect_flag(self.subscribe, other.subscribe),
+            list_changed: intersect_flag(self.list_changed, other.list_changed),
+        }
+    }
+}
+
+impl ToolsCapability {
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self {
+            list_changed: intersect_flag(self.list_changed, other.list_changed),
+        }
+    }
+}
+
+impl RootsCapabilities {
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self {
+            list_changed: intersect_flag(self.list_changed, other.list_changed),
+        }
+    }
+}
+
+impl ClientCapabilities {
+    /// The experimental capability declaring the content types a client accepts
+    pub const CONTENT_TYPES_CAPABILITY: &str = "contentTypes";
+
+    /// The capabilities advertised by both `self` and `other`, for a proxy which can only offer
+    /// what all of its clients support.
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self {
+            experimental: intersect_with(
+                &self.experimental,
+                &other.experimental,
+                intersect_experimental,
+            ),
+            roots: intersect_with(&self.roots, &other.roots, RootsCapabilities::intersect),
+            sampling: intersect_with(&self.sampling, &other.sampling, intersect_object),
+        }
+    }
+
+    /// The request methods a client advertising these capabilities answers.
+    pub fn supported_methods(&self) -> Vec<&'static str> {
+        let mut methods = vec![PingRequestMethod::VALUE];
+        if self.roots.is_some() {
+            methods.push(ListRootsRequestMethod::VALUE);
+        }
+        if self.sampling.is_some() {
+            methods.push(CreateMessageRequestMethod::VALUE);
+        }
+        methods
+    }
+
+    /// The content types a client accepts, `None` when it didn't declare them and accepts any.
+    ///
+    /// They are declared in the `contentTypes` experimental capability, as the types of
+    /// [`RawContent`](super::RawContent) on the wire:
+    /// `{ "experimental": { "contentTypes": { "types": ["text"] } } }`.
+    pub fn content_types(&self) -> Option<Vec<&str>> {
+        let types = self
+            .experimental
+            .as_ref()?
+            .get(Self::CONTENT_TYPES_CAPABILITY)?
+            .get("types")?
+            .as_array()?;
+        Some(types.iter().filter_map(|kind| kind.as_str()).collect())
+    }
+
+    /// Declare the content types this client accepts, see [`ClientCapabilities::content_types`]
+    pub fn with_content_types<S: Into<String>>(
+        mut self,
+        types: impl IntoIterator<Item = S>,
+    ) -> Self {
+        let types = types
+            .into_iter()
+            .map(|kind| serde_json::Value::String(kind.into()))
+            .collect();
+        let mut capability = JsonObject::new();
+        capability.insert("types".to_owned(), serde_json::Value::Array(types));
+        self.experimental
+            .get_or_insert_with(Default::default)
+            .insert(Self::CONTENT_TYPES_CAPABILITY.to_owned(), capability);
+        self
+    }
+}
+
+impl ServerCapabilities {
+    /// The capabilities advertised by both `self` and `other`, for a proxy which can only offer
+    /// what all of its upstream servers support.
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self {
+            experimental: intersect_with(
+                &self.experimental,
+                &other.experimental,
+                intersect_experimental,
+            ),
+            logging: intersect_with(&self.logging, &other.logging, intersect_object),
+            completions: intersect_with(&self.completions, &other.completions, intersect_object),
+            prompts: intersect_with(&self.prompts, &other.prompts, PromptsCapability::intersect),
+            resources: intersect_with(
+                &self.resources,
+                &other.resources,
+                ResourcesCapability::intersect,
+            ),
+            tools: intersect_with(&self.tools, &other.tools, ToolsCapability::intersect),
+        }
+    }
+
+    /// The request methods a server advertising these capabilities answers.
+    pub fn supported_methods(&self) -> Vec<&'static str> {
+        let mut methods = vec![PingRequestMethod::VALUE];
+        if self.logging.is_some() {
+            methods.push(SetLevelRequestMethod::VALUE);
+        }
+        if self.completions.is_some() {
+            methods.push(CompleteRequestMethod::VALUE);
+        }
+        if self.prompts.is_some() {
+            methods.extend([
+                ListPromptsRequestMethod::VALUE,
+                GetPromptRequestMethod::VALUE,
+            ]);
+        }
+        if let Some(resources) = &self.resources {
+            methods.extend([
+                ListResourcesRequestMethod::VALUE,
+                ListResourceTemplatesRequestMethod::VALUE,
+                ReadResourceRequestMethod::VALUE,
+            ]);
+            if resources.subscribe == Some(true) {
+                methods.extend([
+                    SubscribeRequestMethod::VALUE,
+                    UnsubscribeRequestMethod::VALUE,
+                ]);
+            }
+        }
+        if self.tools.is_some() {
+            methods.extend([ListToolsRequestMethod::VALUE, CallToolRequestMethod::VALUE]);
+        }
+        methods
+    }
+}
+
 macro_rules! builder {
     ($Target: ident {$($f: ident: $T: ty),* $(,)?}) => {
         paste! {
@@ -301,4 +489,76 @@ mod test {
             })
         );
     }
+
+    #[test]
+    fn test_supported_methods() {
+        let capabilities = ServerCapabilities::builder()
+            .enable_prompts()
+            .enable_tools()
+            .build();
+        assert_eq!(
+            capabilities.supported_methods(),
+            vec![
+                "ping",
+                "prompts/list",
+                "prompts/get",
+                "tools/list",
+                "tools/call"
+            ]
+        );
+        let capabilities = ClientCapabilities::builder().enable_sampling().build();
+        assert_eq!(
+            capabilities.supported_methods(),
+            vec!["ping", "sampling/createMessage"]
+        );
+    }
+
+    #[test]
+    fn test_intersect() {
+        let mut experimental = ExperimentalCapabilities::new();
+        experimental.insert("shared".to_string(), JsonObject::new());
+        experimental.insert("left".to_string(), JsonObject::new());
+        let left = ServerCapabilities::builder()
+            .enable_experimental_with(experimental)
+            .enable_logging()
+            .enable_resources()
+            .enable_resources_subscribe()
+            .enable_resources_list_changed()
+            .enable_tools()
+            .enable_tool_list_changed()
+            .build();
+        let mut experimental = ExperimentalCapabilities::new();
+        experimental.insert("shared".to_string(), JsonObject::new());
+        let right = ServerCapabilities::builder()
+            .enable_experimental_with(experimental)
+            .enable_prompts()
+            .enable_resources()
+            .enable_resources_subscribe()
+            .enable_tools()
+            .build();
+        let intersection = left.intersect(&right);
+        assert_eq!(intersection, right.intersect(&left));
+        assert_eq!(
+            serde_json::to_value(&intersection).unwrap(),
+            serde_json::json!({
+                "experimental": { "shared": {} },
+                "resources": { "subscribe": true },
+                "tools": {},
+            })
+        );
+
+        let left = ClientCapabilities::builder()
+            .enable_roots()
+            .enable_roots_list_changed()
+            .enable_sampling()
+            .build();
+        let right = ClientCapabilities::builder().enable_roots().build();
+        assert_eq!(
+            left.intersect(&right),
+            ClientCapabilities {
+                roots: Some(RootsCapabilities::default()),
+                ..Default::default()
+            }
+        );
+    }
 }
@@ -4,7 +4,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use super::{AnnotateAble, Annotated, resource::ResourceContents};
+use super::{AnnotateAble, Annotated, ClientCapabilities, resource::ResourceContents};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -90,6 +90,7 @@ impl RawContent {
             resource: ResourceContents::TextResourceContents {
                 uri: uri.into(),
                 mime_type: Some("text".to_string()),
+                charset: None,
                 text: content.into(),
             },
         })
@@ -118,6 +119,73 @@ impl RawContent {
             _ => None,
         }
     }
+
+    /// The `type` of this content on the wire
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RawContent::Text(_) => "text",
+            RawContent::Image(_) => "image",
+            RawContent::Resource(_) => "resource",
+            RawContent::Audio(_) => "audio",
+        }
+    }
+}
+
+/// What to do with the content a client didn't declare support for, see
+/// [`ClientCapabilities::content_types`].
+///
+/// ```rust
+/// # use rmcp::model::*;
+/// let capabilities = ClientCapabilities::default().with_content_types(["text"]);
+/// let contents = vec![Content::text("a cat"), Content::image("aGk=", "image/png")];
+/// let contents = ContentNegotiation::Drop.apply(contents, &capabilities).unwrap();
+/// assert_eq!(contents, vec![Content::text("a cat")]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContentNegotiation {
+    /// Fail the request
+    Error,
+    /// Leave the content out
+    Drop,
+    /// Replace the content with a text saying it was left out
+    #[default]
+    Placeholder,
+}
+
+impl ContentNegotiation {
+    /// Keep the contents accepted by a client with `capabilities`, and handle the others
+    /// according to this policy.
+    pub fn apply(
+        self,
+        contents: Vec<Content>,
+        capabilities: &ClientCapabilities,
+    ) -> Result<Vec<Content>, crate::Error> {
+        let Some(accepted) = capabilities.content_types() else {
+            return Ok(contents);
+        };
+        let mut negotiated = Vec::with_capacity(contents.len());
+        for content in contents {
+            let kind = content.raw.kind();
+            if accepted.contains(&kind) {
+                negotiated.push(content);
+                continue;
+            }
+            match self {
+                ContentNegotiation::Error => {
+                    return Err(crate::Error::invalid_request(
+                        format!("the client doesn't accept {kind} content"),
+                        None,
+                    ));
+                }
+                ContentNegotiation::Drop => {}
+                ContentNegotiation::Placeholder => negotiated.push(Annotated {
+                    raw: RawContent::text(format!("[{kind} content not supported by the client]")),
+                    annotations: content.annotations,
+                }),
+            }
+        }
+        Ok(negotiated)
+    }
 }
 
 impl Content {
@@ -5,7 +5,8 @@ use serde_json::Value;
 
 use super::{
     ClientNotification, ClientRequest, Extensions, JsonObject, JsonRpcMessage, NumberOrString,
-    ProgressToken, ServerNotification, ServerRequest,
+    ProgressToken, ReadResourceEntry, ResourceEncoding, ResourceRange, ServerNotification,
+    ServerRequest,
 };
 
 pub trait GetMeta {
@@ -21,13 +22,14 @@ pub trait GetExtensions {
 macro_rules! variant_extension {
     (
         $Enum: ident {
-            $($variant: ident)*
+            $($(#[$meta: meta])* $variant: ident)*
         }
     ) => {
         impl GetExtensions for $Enum {
             fn extensions(&self) -> &Extensions {
                 match self {
                     $(
+                        $(#[$meta])*
                         $Enum::$variant(v) => &v.extensions,
                     )*
                 }
@@ -35,6 +37,7 @@ macro_rules! variant_extension {
             fn extensions_mut(&mut self) -> &mut Extensions {
                 match self {
                     $(
+                        $(#[$meta])*
                         $Enum::$variant(v) => &mut v.extensions,
                     )*
                 }
@@ -83,6 +86,8 @@ variant_extension! {
         ProgressNotification
         InitializedNotification
         RootsListChangedNotification
+        #[cfg(feature = "shutdown-notification")]
+        ShutdownNotification
     }
 }
 
@@ -95,12 +100,25 @@ variant_extension! {
         ResourceListChangedNotification
         ToolListChangedNotification
         PromptListChangedNotification
+        #[cfg(feature = "instructions-changed")]
+        InstructionsChangedNotification
+        #[cfg(feature = "resource-delta")]
+        ResourceListDeltaNotification
+        #[cfg(feature = "shutdown-notification")]
+        ShutdownNotification
     }
 }
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 #[serde(transparent)]
 pub struct Meta(pub JsonObject);
 const PROGRESS_TOKEN_FIELD: &str = "progressToken";
+const RANGE_FIELD: &str = "range";
+const ENCODING_FIELD: &str = "encoding";
+const URIS_FIELD: &str = "uris";
+const READ_ENTRIES_FIELD: &str = "entries";
+const PING_PAYLOAD_FIELD: &str = "pingPayload";
+const TRACEPARENT_FIELD: &str = "traceparent";
+const TRACESTATE_FIELD: &str = "tracestate";
 impl Meta {
     pub fn new() -> Self {
         Self(JsonObject::new())
@@ -133,6 +151,73 @@ impl Meta {
         };
     }
 
+    /// The payload of a ping, which the peer echoes in the `_meta` of its result
+    pub fn get_ping_payload(&self) -> Option<&Value> {
+        self.0.get(PING_PAYLOAD_FIELD)
+    }
+
+    pub fn set_ping_payload(&mut self, payload: Value) {
+        self.0.insert(PING_PAYLOAD_FIELD.to_string(), payload);
+    }
+
+    pub fn get_range(&self) -> Option<ResourceRange> {
+        self.0
+            .get(RANGE_FIELD)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    pub fn set_range(&mut self, range: ResourceRange) {
+        self.0.insert(
+            RANGE_FIELD.to_string(),
+            serde_json::to_value(range).expect("range should be serializable"),
+        );
+    }
+
+    pub fn get_encoding(&self) -> Option<ResourceEncoding> {
+        self.0
+            .get(ENCODING_FIELD)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    pub fn set_encoding(&mut self, encoding: ResourceEncoding) {
+        self.0.insert(
+            ENCODING_FIELD.to_string(),
+            serde_json::to_value(encoding).expect("encoding should be serializable"),
+        );
+    }
+
+    /// The uris of a batch read, the `uri` of the request is the first one, so servers unaware
+    /// of batches read that one only.
+    pub fn get_uris(&self) -> Option<Vec<String>> {
+        self.0
+            .get(URIS_FIELD)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    /// Remove the uris of a batch read, returning them
+    pub fn take_uris(&mut self) -> Option<Vec<String>> {
+        self.0
+            .remove(URIS_FIELD)
+            .and_then(|v| serde_json::from_value(v).ok())
+    }
+
+    pub fn set_uris(&mut self, uris: Vec<String>) {
+        self.0.insert(URIS_FIELD.to_string(), uris.into());
+    }
+
+    pub fn get_read_entries(&self) -> Option<Vec<ReadResourceEntry>> {
+        self.0
+            .get(READ_ENTRIES_FIELD)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    pub fn set_read_entries(&mut self, entries: &[ReadResourceEntry]) {
+        self.0.insert(
+            READ_ENTRIES_FIELD.to_string(),
+            serde_json::to_value(entries).expect("entries should be serializable"),
+        );
+    }
+
     pub fn extend(&mut self, other: Meta) {
         for (k, v) in other.0.into_iter() {
             self.0.insert(k, v);
@@ -140,6 +225,87 @@ impl Meta {
     }
 }
 
+/// A [W3C trace context](https://www.w3.org/TR/trace-context/) carried in the `_meta` of a
+/// request, so the spans on both sides of the connection can be linked.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TraceContext {
+    pub traceparent: String,
+    pub tracestate: Option<String>,
+}
+
+impl TraceContext {
+    /// Return `None` if `traceparent` is not `version-traceid-parentid-flags` in hex.
+    pub fn new(traceparent: impl Into<String>) -> Option<Self> {
+        let traceparent = traceparent.into();
+        is_valid_traceparent(&traceparent).then_some(Self {
+            traceparent,
+            tracestate: None,
+        })
+    }
+
+    pub fn with_tracestate(mut self, tracestate: impl Into<String>) -> Self {
+        self.tracestate = Some(tracestate.into());
+        self
+    }
+
+    /// The 32 hex digits trace id
+    pub fn trace_id(&self) -> &str {
+        &self.traceparent[3..35]
+    }
+
+    /// The 16 hex digits id of the parent span
+    pub fn parent_id(&self) -> &str {
+        &self.traceparent[36..52]
+    }
+}
+
+fn is_valid_traceparent(traceparent: &str) -> bool {
+    let parts = traceparent.split('-').collect::<Vec<_>>();
+    let [version, trace_id, parent_id, flags, ..] = parts.as_slice() else {
+        return false;
+    };
+    let is_hex = |part: &str, len: usize| {
+        part.len() == len && part.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    };
+    is_hex(version, 2)
+        && *version != "ff"
+        // only version 00 is fixed to 4 fields, later versions may append more
+        && (parts.len() == 4 || *version != "00")
+        && is_hex(trace_id, 32)
+        && trace_id.bytes().any(|b| b != b'0')
+        && is_hex(parent_id, 16)
+        && parent_id.bytes().any(|b| b != b'0')
+        && is_hex(flags, 2)
+}
+
+/// Put `context` into `meta`, replacing any trace context already there.
+pub fn inject_trace_context(meta: &mut Meta, context: &TraceContext) {
+    meta.0.insert(
+        TRACEPARENT_FIELD.to_string(),
+        Value::String(context.traceparent.clone()),
+    );
+    match &context.tracestate {
+        Some(tracestate) => {
+            meta.0.insert(
+                TRACESTATE_FIELD.to_string(),
+                Value::String(tracestate.clone()),
+            );
+        }
+        None => {
+            meta.0.remove(TRACESTATE_FIELD);
+        }
+    }
+}
+
+/// Read the trace context from `meta`, an invalid `traceparent` is ignored.
+pub fn extract_trace_context(meta: &Meta) -> Option<TraceContext> {
+    let context = TraceContext::new(meta.0.get(TRACEPARENT_FIELD)?.as_str()?)?;
+    match meta.0.get(TRACESTATE_FIELD).and_then(Value::as_str) {
+        Some(tracestate) => Some(context.with_tracestate(tracestate)),
+        None => Some(context),
+    }
+}
+
 impl Deref for Meta {
     type Target = JsonObject;
 
@@ -192,3 +358,59 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_trace_context_round_trip() {
+        let context = TraceContext::new("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+            .expect("valid traceparent")
+            .with_tracestate("rojo=00f067aa0ba902b7");
+        assert_eq!(context.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(context.parent_id(), "00f067aa0ba902b7");
+        let mut meta = Meta::new();
+        meta.set_progress_token(ProgressToken(NumberOrString::Number(1)));
+        inject_trace_context(&mut meta, &context);
+        let value = serde_json::to_value(&meta).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "progressToken": 1,
+                "traceparent": "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+                "tracestate": "rojo=00f067aa0ba902b7",
+            })
+        );
+        let meta: Meta = serde_json::from_value(value).unwrap();
+        assert_eq!(extract_trace_context(&meta), Some(context));
+    }
+
+    #[test]
+    fn test_invalid_traceparent() {
+        for traceparent in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        ] {
+            assert_eq!(TraceContext::new(traceparent), None, "{traceparent}");
+        }
+        assert!(
+            TraceContext::new("01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra")
+                .is_some()
+        );
+        let meta = Meta(
+            json!({ "traceparent": "not a traceparent" })
+                .as_object()
+                .unwrap()
+                .clone(),
+        );
+        assert_eq!(extract_trace_context(&meta), None);
+    }
+}
@@ -2,7 +2,7 @@ use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
 use serde::{Deserialize, Serialize};
 
 use super::{
-    AnnotateAble, Annotations, RawEmbeddedResource, RawImageContent,
+    AnnotateAble, Annotations, RawEmbeddedResource, RawImageContent, RawResource, Resource,
     content::{EmbeddedResource, ImageContent},
     resource::ResourceContents,
 };
@@ -74,6 +74,12 @@ pub enum PromptMessageContent {
     },
     /// Embedded server-side resource
     Resource { resource: EmbeddedResource },
+    /// A reference to a server-side resource, the client reads it to get the contents
+    #[serde(rename = "resource_link")]
+    ResourceLink {
+        #[serde(flatten)]
+        link: Resource,
+    },
 }
 
 impl PromptMessageContent {
@@ -133,6 +139,7 @@ impl PromptMessage {
         let resource_contents = ResourceContents::TextResourceContents {
             uri,
             mime_type: Some(mime_type),
+            charset: None,
             text: text.unwrap_or_default(),
         };
 
@@ -146,6 +153,20 @@ impl PromptMessage {
             },
         }
     }
+
+    /// Create a new message referencing a resource by uri, instead of embedding its contents
+    pub fn new_resource_link(
+        role: PromptMessageRole,
+        uri: impl Into<String>,
+        name: impl Into<String>,
+    ) -> Self {
+        Self {
+            role,
+            content: PromptMessageContent::ResourceLink {
+                link: RawResource::new(uri, name).no_annotation(),
+            },
+        }
+    }
 }
 
 /// A template for a prompt
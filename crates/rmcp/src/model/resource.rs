@@ -46,6 +46,10 @@ pub enum ResourceContents {
         uri: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         mime_type: Option<String>,
+        /// The character encoding the text was read in, like `iso-8859-1`, the text itself is
+        /// always unicode
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        charset: Option<String>,
         text: String,
     },
     BlobResourceContents {
@@ -61,9 +65,272 @@ impl ResourceContents {
         Self::TextResourceContents {
             uri: uri.into(),
             mime_type: Some("text".into()),
+            charset: None,
             text: text.into(),
         }
     }
+
+    /// Text which was read in `charset`, like `iso-8859-1`
+    pub fn text_with_charset(
+        text: impl Into<String>,
+        charset: impl Into<String>,
+        uri: impl Into<String>,
+    ) -> Self {
+        Self::TextResourceContents {
+            uri: uri.into(),
+            mime_type: Some("text/plain".into()),
+            charset: Some(charset.into()),
+            text: text.into(),
+        }
+    }
+
+    /// Text read as `bytes`, in `charset` when known, utf-8 otherwise.
+    ///
+    /// Without a charset, a byte order mark tells utf-16 apart. The text is decoded and sent as
+    /// `text/plain` text keeping its charset, bytes which can't be decoded are sent as a blob
+    /// with the charset in the mime type, like `text/plain; charset=ebcdic`.
+    #[cfg(feature = "base64")]
+    pub fn encoded_text(bytes: &[u8], charset: Option<&str>, uri: impl Into<String>) -> Self {
+        use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+        let charset = charset.or_else(|| detect_charset(bytes));
+        let text = match charset {
+            Some(charset) => Charset::parse(charset).and_then(|charset| charset.decode(bytes)),
+            None => Charset::Utf8.decode(bytes),
+        };
+        match text {
+            Some(text) => Self::TextResourceContents {
+                uri: uri.into(),
+                mime_type: Some("text/plain".into()),
+                charset: charset.map(str::to_owned),
+                text,
+            },
+            None => Self::BlobResourceContents {
+                uri: uri.into(),
+                mime_type: Some(match charset {
+                    Some(charset) => format!("text/plain; charset={charset}"),
+                    None => "text/plain".into(),
+                }),
+                blob: BASE64_STANDARD.encode(bytes),
+            },
+        }
+    }
+
+    /// The text of the contents, a blob is decoded in the charset of its mime type.
+    ///
+    /// Returns `None` when the blob can't be decoded, isn't valid in its charset, or the
+    /// charset isn't one of utf-8, us-ascii, iso-8859-1 and utf-16.
+    pub fn decode_text(&self) -> Option<String> {
+        match self {
+            Self::TextResourceContents { text, .. } => Some(text.clone()),
+            #[cfg(feature = "base64")]
+            Self::BlobResourceContents {
+                mime_type, blob, ..
+            } => {
+                use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+                let bytes = BASE64_STANDARD.decode(blob).ok()?;
+                let charset = match mime_type.as_deref().and_then(mime_charset) {
+                    Some(charset) => Charset::parse(charset)?,
+                    None => Charset::Utf8,
+                };
+                charset.decode(&bytes)
+            }
+            #[cfg(not(feature = "base64"))]
+            Self::BlobResourceContents { .. } => None,
+        }
+    }
+
+    /// Cut the contents down to the bytes covered by `range`.
+    ///
+    /// For text the range is over the utf-8 bytes, for blob it's over the decoded bytes.
+    /// Returns `None` when a text range doesn't fall on char boundaries,
+    /// or a blob can't be decoded.
+    pub fn slice(&self, range: ResourceRange) -> Option<Self> {
+        match self {
+            Self::TextResourceContents {
+                uri,
+                mime_type,
+                charset,
+                text,
+            } => {
+                let (start, end) = range.bounds(text.len());
+                Some(Self::TextResourceContents {
+                    uri: uri.clone(),
+                    mime_type: mime_type.clone(),
+                    charset: charset.clone(),
+                    text: text.get(start..end)?.to_owned(),
+                })
+            }
+            #[cfg(feature = "base64")]
+            Self::BlobResourceContents {
+                uri,
+                mime_type,
+                blob,
+            } => {
+                use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+                let bytes = BASE64_STANDARD.decode(blob).ok()?;
+                let (start, end) = range.bounds(bytes.len());
+                Some(Self::BlobResourceContents {
+                    uri: uri.clone(),
+                    mime_type: mime_type.clone(),
+                    blob: BASE64_STANDARD.encode(&bytes[start..end]),
+                })
+            }
+            #[cfg(not(feature = "base64"))]
+            Self::BlobResourceContents { .. } => None,
+        }
+    }
+
+    /// The same contents encoded as `encoding`.
+    ///
+    /// Text is always representable as a blob, a blob is only as text when its bytes are utf-8.
+    /// Returns `None` when the blob isn't utf-8 or can't be decoded.
+    pub fn encode(&self, encoding: ResourceEncoding) -> Option<Self> {
+        match (self, encoding) {
+            (Self::TextResourceContents { .. }, ResourceEncoding::Text)
+            | (Self::BlobResourceContents { .. }, ResourceEncoding::Blob) => Some(self.clone()),
+            #[cfg(feature = "base64")]
+            (
+                Self::TextResourceContents {
+                    uri,
+                    mime_type,
+                    text,
+                    ..
+                },
+                ResourceEncoding::Blob,
+            ) => {
+                use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+                Some(Self::BlobResourceContents {
+                    uri: uri.clone(),
+                    mime_type: mime_type.clone(),
+                    blob: BASE64_STANDARD.encode(text),
+                })
+            }
+            #[cfg(feature = "base64")]
+            (Self::BlobResourceContents { uri, mime_type, .. }, ResourceEncoding::Text) => {
+                Some(Self::TextResourceContents {
+                    uri: uri.clone(),
+                    mime_type: mime_type.clone(),
+                    charset: mime_type
+                        .as_deref()
+                        .and_then(mime_charset)
+                        .map(str::to_owned),
+                    text: self.decode_text()?,
+                })
+            }
+            #[cfg(not(feature = "base64"))]
+            _ => None,
+        }
+    }
+}
+
+/// The character encodings [`ResourceContents::decode_text`] knows
+#[cfg(feature = "base64")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Charset {
+    Utf8,
+    Ascii,
+    Latin1,
+    Utf16Le,
+    Utf16Be,
+    // byte order mark first, big endian without it
+    Utf16,
+}
+
+#[cfg(feature = "base64")]
+impl Charset {
+    fn parse(charset: &str) -> Option<Self> {
+        let charset = charset.trim().to_ascii_lowercase();
+        Some(match charset.as_str() {
+            "utf-8" | "utf8" => Self::Utf8,
+            "us-ascii" | "ascii" => Self::Ascii,
+            "iso-8859-1" | "iso8859-1" | "latin1" | "latin-1" | "l1" => Self::Latin1,
+            "utf-16le" => Self::Utf16Le,
+            "utf-16be" => Self::Utf16Be,
+            "utf-16" => Self::Utf16,
+            _ => return None,
+        })
+    }
+
+    fn decode(self, bytes: &[u8]) -> Option<String> {
+        match self {
+            Self::Utf8 => String::from_utf8(bytes.to_vec()).ok(),
+            Self::Ascii => bytes
+                .is_ascii()
+                .then(|| bytes.iter().map(|&byte| char::from(byte)).collect()),
+            Self::Latin1 => Some(bytes.iter().map(|&byte| char::from(byte)).collect()),
+            Self::Utf16Le => decode_utf16(bytes, u16::from_le_bytes),
+            Self::Utf16Be => decode_utf16(bytes, u16::from_be_bytes),
+            Self::Utf16 => match bytes {
+                [0xff, 0xfe, rest @ ..] => decode_utf16(rest, u16::from_le_bytes),
+                [0xfe, 0xff, rest @ ..] => decode_utf16(rest, u16::from_be_bytes),
+                _ => decode_utf16(bytes, u16::from_be_bytes),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "base64")]
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> Option<String> {
+    if bytes.len() % 2 != 0 {
+        return None;
+    }
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    char::decode_utf16(units).collect::<Result<_, _>>().ok()
+}
+
+/// The `charset` parameter of a mime type, like `text/plain; charset=iso-8859-1`
+#[cfg(feature = "base64")]
+fn mime_charset(mime_type: &str) -> Option<&str> {
+    mime_type.split(';').skip(1).find_map(|parameter| {
+        let (name, value) = parameter.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+/// The charset told by a byte order mark
+#[cfg(feature = "base64")]
+fn detect_charset(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0xff, 0xfe, ..] | [0xfe, 0xff, ..] => Some("utf-16"),
+        _ => None,
+    }
+}
+
+/// How a client prefers the contents of a resource, `text` or base64 `blob`.
+///
+/// Clients ask for it in the `_meta` of a read request, see
+/// [`Meta::get_encoding`](crate::model::Meta::get_encoding).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ResourceEncoding {
+    Text,
+    Blob,
+}
+
+/// A byte range of a resource.
+///
+/// Clients ask for it in the `_meta` of a read request, and servers that honored it echo it in
+/// the `_meta` of the result, see [`Meta::get_range`](crate::model::Meta::get_range).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceRange {
+    pub offset: u64,
+    pub length: u64,
+}
+
+impl ResourceRange {
+    pub fn new(offset: u64, length: u64) -> Self {
+        Self { offset, length }
+    }
+
+    /// The `start..end` indices of this range, clamped to `len`
+    fn bounds(&self, len: usize) -> (usize, usize) {
+        let start = usize::try_from(self.offset).unwrap_or(usize::MAX).min(len);
+        let length = usize::try_from(self.length).unwrap_or(usize::MAX);
+        (start, start.saturating_add(length).min(len))
+    }
 }
 
 impl RawResource {
@@ -0,0 +1,302 @@
+use thiserror::Error;
+
+use super::{AnnotateAble, Annotations, RawResourceTemplate, ResourceTemplate};
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum UriTemplateError {
+    #[error("unclosed expression starting at {position}")]
+    UnclosedExpression { position: usize },
+    #[error("unexpected '}}' at {position}")]
+    UnexpectedClosingBrace { position: usize },
+    #[error("empty expression at {position}")]
+    EmptyExpression { position: usize },
+    #[error("operator '{operator}' at {position} is reserved")]
+    ReservedOperator { operator: char, position: usize },
+    #[error("invalid variable name {name:?} at {position}")]
+    InvalidVariable { name: String, position: usize },
+    #[error("invalid modifier {modifier:?} at {position}")]
+    InvalidModifier { modifier: String, position: usize },
+    #[error("character {character:?} at {position} is not allowed in a uri template")]
+    InvalidCharacter { character: char, position: usize },
+}
+
+/// Check that `template` is a well-formed [RFC 6570](https://www.rfc-editor.org/rfc/rfc6570)
+/// uri template, up to level 4.
+///
+/// ```rust
+/// # use rmcp::model::*;
+/// assert!(validate_uri_template("file:///{path}").is_ok());
+/// assert!(validate_uri_template("file:///{+path}{?query*}").is_ok());
+/// assert!(validate_uri_template("file:///{path").is_err());
+/// ```
+pub fn validate_uri_template(template: &str) -> Result<(), UriTemplateError> {
+    let mut chars = template.char_indices();
+    while let Some((position, character)) = chars.next() {
+        match character {
+            '{' => {
+                let mut expression = None;
+                for (end, character) in chars.by_ref() {
+                    if character == '}' {
+                        expression = Some(&template[position + 1..end]);
+                        break;
+                    }
+                }
+                let expression =
+                    expression.ok_or(UriTemplateError::UnclosedExpression { position })?;
+                validate_expression(expression, position)?;
+            }
+            '}' => return Err(UriTemplateError::UnexpectedClosingBrace { position }),
+            // the characters RFC 6570 excludes from literals
+            ' ' | '"' | '\'' | '<' | '>' | '\\' | '^' | '`' | '|' => {
+                return Err(UriTemplateError::InvalidCharacter {
+                    character,
+                    position,
+                });
+            }
+            character if character.is_control() => {
+                return Err(UriTemplateError::InvalidCharacter {
+                    character,
+                    position,
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Check the body of an expression, `position` is the one of its opening brace.
+fn validate_expression(expression: &str, position: usize) -> Result<(), UriTemplateError> {
+    let variables = match expression.chars().next() {
+        None => return Err(UriTemplateError::EmptyExpression { position }),
+        Some('+' | '#' | '.' | '/' | ';' | '?' | '&') => &expression[1..],
+        Some(operator @ ('=' | ',' | '!' | '@' | '|')) => {
+            return Err(UriTemplateError::ReservedOperator {
+                operator,
+                position: position + 1,
+            });
+        }
+        Some(_) => expression,
+    };
+    let mut offset = position + 1 + expression.len() - variables.len();
+    for variable in variables.split(',') {
+        let (name, modifier) = match variable.find([':', '*']) {
+            Some(index) => variable.split_at(index),
+            None => (variable, ""),
+        };
+        if !is_variable_name(name) {
+            return Err(UriTemplateError::InvalidVariable {
+                name: name.to_owned(),
+                position: offset,
+            });
+        }
+        if !is_modifier(modifier) {
+            return Err(UriTemplateError::InvalidModifier {
+                modifier: modifier.to_owned(),
+                position: offset + name.len(),
+            });
+        }
+        offset += variable.len() + 1;
+    }
+    Ok(())
+}
+
+/// `varname = varchar *( ["."] varchar )`, where a varchar is alphanumeric, `_` or a
+/// percent-encoded byte
+fn is_variable_name(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    if bytes.first() == Some(&b'.') || bytes.last() == Some(&b'.') || name.contains("..") {
+        return false;
+    }
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' => {
+                let is_encoded = bytes
+                    .get(index + 1..index + 3)
+                    .is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit));
+                if !is_encoded {
+                    return false;
+                }
+                index += 3;
+            }
+            byte if byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'.' => index += 1,
+            _ => return false,
+        }
+    }
+    !bytes.is_empty()
+}
+
+/// No modifier, `*`, or a prefix `:` followed by a length from 1 to 9999
+fn is_modifier(modifier: &str) -> bool {
+    match modifier.strip_prefix(':') {
+        Some(length) => {
+            (1..=4).contains(&length.len())
+                && !length.starts_with('0')
+                && length.bytes().all(|byte| byte.is_ascii_digit())
+        }
+        None => modifier.is_empty() || modifier == "*",
+    }
+}
+
+/// Build a [`ResourceTemplate`], its uri template is checked when it's built.
+///
+/// ```rust
+/// # use rmcp::model::*;
+/// let template = ResourceTemplate::builder("file:///{path}", "file")
+///     .description("A file of the project")
+///     .mime_type("text/plain")
+///     .build()
+///     .unwrap();
+/// assert_eq!(template.raw.uri_template, "file:///{path}");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceTemplateBuilder {
+    raw: RawResourceTemplate,
+    annotations: Option<Annotations>,
+}
+
+impl ResourceTemplateBuilder {
+    pub fn new(uri_template: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            raw: RawResourceTemplate {
+                uri_template: uri_template.into(),
+                name: name.into(),
+                description: None,
+                mime_type: None,
+            },
+            annotations: None,
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.raw.description = Some(description.into());
+        self
+    }
+
+    pub fn mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.raw.mime_type = Some(mime_type.into());
+        self
+    }
+
+    pub fn annotations(mut self, annotations: Annotations) -> Self {
+        self.annotations = Some(annotations);
+        self
+    }
+
+    pub fn build(self) -> Result<ResourceTemplate, UriTemplateError> {
+        validate_uri_template(&self.raw.uri_template)?;
+        Ok(self.raw.optional_annotate(self.annotations))
+    }
+}
+
+impl ResourceTemplate {
+    pub fn builder(
+        uri_template: impl Into<String>,
+        name: impl Into<String>,
+    ) -> ResourceTemplateBuilder {
+        ResourceTemplateBuilder::new(uri_template, name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let template = ResourceTemplate::builder("file:///{path}", "file")
+            .description("A file of the project")
+            .mime_type("text/plain")
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&template).unwrap(),
+            json!({
+                "uriTemplate": "file:///{path}",
+                "name": "file",
+                "description": "A file of the project",
+                "mimeType": "text/plain",
+            })
+        );
+    }
+
+    #[test]
+    fn test_builder_with_malformed_template() {
+        let result = ResourceTemplate::builder("file:///{path", "file").build();
+        assert_eq!(
+            result,
+            Err(UriTemplateError::UnclosedExpression { position: 8 })
+        );
+    }
+
+    #[test]
+    fn test_validate_uri_template() {
+        for template in [
+            "str:///static",
+            "file:///{path}",
+            "http://example.com/{+path}/{file.name}{?query,page:3}{&list*}",
+            "{#fragment}{.ext}{/segments*}{;params}",
+            "db://{table%20name}/{id}",
+        ] {
+            assert_eq!(validate_uri_template(template), Ok(()), "{template}");
+        }
+        let errors = [
+            (
+                "file:///path}",
+                UriTemplateError::UnexpectedClosingBrace { position: 12 },
+            ),
+            (
+                "file:///{}",
+                UriTemplateError::EmptyExpression { position: 8 },
+            ),
+            (
+                "file:///{=path}",
+                UriTemplateError::ReservedOperator {
+                    operator: '=',
+                    position: 9,
+                },
+            ),
+            (
+                "file:///{pa-th}",
+                UriTemplateError::InvalidVariable {
+                    name: "pa-th".into(),
+                    position: 9,
+                },
+            ),
+            (
+                "file:///{?a,.b}",
+                UriTemplateError::InvalidVariable {
+                    name: ".b".into(),
+                    position: 12,
+                },
+            ),
+            (
+                "file:///{path:0}",
+                UriTemplateError::InvalidModifier {
+                    modifier: ":0".into(),
+                    position: 13,
+                },
+            ),
+            (
+                "file:///{path:10000}",
+                UriTemplateError::InvalidModifier {
+                    modifier: ":10000".into(),
+                    position: 13,
+                },
+            ),
+            (
+                "file:///my file",
+                UriTemplateError::InvalidCharacter {
+                    character: ' ',
+                    position: 10,
+                },
+            ),
+        ];
+        for (template, error) in errors {
+            assert_eq!(validate_uri_template(template), Err(error), "{template}");
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use serde_json::Value;
+use thiserror::Error;
+
+use super::{
+    Content, ContextInclusion, CreateMessageRequestParam, CreateMessageResult, ModelHint,
+    ModelPreferences, RawContent, Role, SamplingMessage,
+};
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum SamplingRequestError {
+    #[error("a sampling request needs at least one message")]
+    NoMessages,
+    #[error("the {name} must be between 0 and 1, got {priority}")]
+    PriorityOutOfRange { name: &'static str, priority: f32 },
+    #[error("the model hint at {index} has an empty name")]
+    EmptyHintName { index: usize },
+}
+
+/// Why the result of a sampling request is not a message sampled from the model
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum SamplingResponseError {
+    #[error("expect a message with the assistant role, got {0:?}")]
+    UnexpectedRole(Role),
+    #[error("expect text, image or audio content, got {0}")]
+    UnsupportedContent(&'static str),
+    #[error("expect a sampling result, got {0}")]
+    NotAMessage(String),
+}
+
+/// Build a [`CreateMessageRequestParam`] step by step.
+///
+/// The optional fields which are never set are left out of the request.
+///
+/// ```rust
+/// # use rmcp::model::*;
+/// let request = CreateMessageRequestParam::builder(100)
+///     .system_prompt("You are a helpful assistant")
+///     .user_message("What is the capital of France?")
+///     .build()
+///     .unwrap();
+/// assert_eq!(request.messages.len(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingRequestBuilder {
+    param: CreateMessageRequestParam,
+}
+
+impl SamplingRequestBuilder {
+    pub fn new(max_tokens: u32) -> Self {
+        Self {
+            param: CreateMessageRequestParam {
+                messages: Vec::new(),
+                model_preferences: None,
+                system_prompt: None,
+                include_context: None,
+                temperature: None,
+                max_tokens,
+                stop_sequences: None,
+                metadata: None,
+            },
+        }
+    }
+
+    pub fn message(mut self, message: SamplingMessage) -> Self {
+        self.param.messages.push(message);
+        self
+    }
+
+    pub fn messages(mut self, messages: impl IntoIterator<Item = SamplingMessage>) -> Self {
+        self.param.messages.extend(messages);
+        self
+    }
+
+    /// Append a text message from the user
+    pub fn user_message(self, text: impl Into<String>) -> Self {
+        self.message(SamplingMessage {
+            role: Role::User,
+            content: Content::text(text),
+        })
+    }
+
+    /// Append a text message from the assistant
+    pub fn assistant_message(self, text: impl Into<String>) -> Self {
+        self.message(SamplingMessage {
+            role: Role::Assistant,
+            content: Content::text(text),
+        })
+    }
+
+    pub fn model_preferences(mut self, model_preferences: ModelPreferences) -> Self {
+        self.param.model_preferences = Some(model_preferences);
+        self
+    }
+
+    pub fn system_prompt(mut self, system_prompt: impl Into<String>) -> Self {
+        self.param.system_prompt = Some(system_prompt.into());
+        self
+    }
+
+    pub fn include_context(mut self, include_context: ContextInclusion) -> Self {
+        self.param.include_context = Some(include_context);
+        self
+    }
+
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.param.temperature = Some(temperature);
+        self
+    }
+
+    pub fn max_tokens(mut self, max_tokens: u32) -> Self {
+        self.param.max_tokens = max_tokens;
+        self
+    }
+
+    pub fn stop_sequence(mut self, stop_sequence: impl Into<String>) -> Self {
+        self.param
+            .stop_sequences
+            .get_or_insert_with(Vec::new)
+            .push(stop_sequence.into());
+        self
+    }
+
+    pub fn metadata(mut self, metadata: Value) -> Self {
+        self.param.metadata = Some(metadata);
+        self
+    }
+
+    pub fn build(self) -> Result<CreateMessageRequestParam, SamplingRequestError> {
+        if self.param.messages.is_empty() {
+            return Err(SamplingRequestError::NoMessages);
+        }
+        if let Some(model_preferences) = &self.param.model_preferences {
+            model_preferences.validate()?;
+        }
+        Ok(self.param)
+    }
+}
+
+impl CreateMessageResult {
+    /// Check the result is an assistant message with content the sampling supports.
+    pub fn validate(&self) -> Result<(), SamplingResponseError> {
+        if self.message.role != Role::Assistant {
+            return Err(SamplingResponseError::UnexpectedRole(
+                self.message.role.clone(),
+            ));
+        }
+        match &self.message.content.raw {
+            RawContent::Text(_) | RawContent::Image(_) | RawContent::Audio(_) => Ok(()),
+            RawContent::Resource(_) => Err(SamplingResponseError::UnsupportedContent("resource")),
+        }
+    }
+}
+
+impl CreateMessageRequestParam {
+    pub fn builder(max_tokens: u32) -> SamplingRequestBuilder {
+        SamplingRequestBuilder::new(max_tokens)
+    }
+}
+
+/// Priorities are between 0 and 1, anything else is clamped into that range.
+fn clamp_priority(priority: f32) -> f32 {
+    if priority.is_nan() {
+        0.0
+    } else {
+        priority.clamp(0.0, 1.0)
+    }
+}
+
+impl ModelPreferences {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prefer the cheapest model.
+    pub fn prefer_cost() -> Self {
+        Self::new().cost_priority(1.0)
+    }
+
+    /// Prefer the fastest model.
+    pub fn prefer_speed() -> Self {
+        Self::new().speed_priority(1.0)
+    }
+
+    /// Prefer the most capable model.
+    pub fn prefer_intelligence() -> Self {
+        Self::new().intelligence_priority(1.0)
+    }
+
+    pub fn cost_priority(mut self, priority: f32) -> Self {
+        self.cost_priority = Some(clamp_priority(priority));
+        self
+    }
+
+    pub fn speed_priority(mut self, priority: f32) -> Self {
+        self.speed_priority = Some(clamp_priority(priority));
+        self
+    }
+
+    pub fn intelligence_priority(mut self, priority: f32) -> Self {
+        self.intelligence_priority = Some(clamp_priority(priority));
+        self
+    }
+
+    /// Check the priorities are between 0 and 1 and the hints name a model.
+    ///
+    /// The setters already clamp the priorities, this catches the preferences built by hand
+    /// or received from a peer.
+    pub fn validate(&self) -> Result<(), SamplingRequestError> {
+        let priorities = [
+            ("costPriority", self.cost_priority),
+            ("speedPriority", self.speed_priority),
+            ("intelligencePriority", self.intelligence_priority),
+        ];
+        for (name, priority) in priorities {
+            if let Some(priority) = priority.filter(|priority| !(0.0..=1.0).contains(priority)) {
+                return Err(SamplingRequestError::PriorityOutOfRange { name, priority });
+            }
+        }
+        let empty_hint = self.hints.iter().flatten().position(|hint| {
+            hint.name
+                .as_deref()
+                .is_some_and(|name| name.trim().is_empty())
+        });
+        if let Some(index) = empty_hint {
+            return Err(SamplingRequestError::EmptyHintName { index });
+        }
+        Ok(())
+    }
+
+    /// Append a model name hint, hints are evaluated in order by the client.
+    pub fn with_hint(mut self, name: impl Into<String>) -> Self {
+        self.hints.get_or_insert_with(Vec::new).push(ModelHint {
+            name: Some(name.into()),
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let request = CreateMessageRequestParam::builder(256)
+            .system_prompt("You are a poet")
+            .user_message("Write a haiku about rust")
+            .assistant_message("Iron turns to red")
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "messages": [
+                    {
+                        "role": "user",
+                        "content": { "type": "text", "text": "Write a haiku about rust" },
+                    },
+                    {
+                        "role": "assistant",
+                        "content": { "type": "text", "text": "Iron turns to red" },
+                    },
+                ],
+                "systemPrompt": "You are a poet",
+                "maxTokens": 256,
+            })
+        );
+    }
+
+    #[test]
+    fn test_builder_without_messages() {
+        let result = SamplingRequestBuilder::new(256)
+            .system_prompt("You are a poet")
+            .build();
+        assert_eq!(result, Err(SamplingRequestError::NoMessages));
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_model_preferences() {
+        let build = |model_preferences| {
+            CreateMessageRequestParam::builder(256)
+                .user_message("Write a haiku about rust")
+                .model_preferences(model_preferences)
+                .build()
+        };
+        let out_of_range = ModelPreferences {
+            speed_priority: Some(1.5),
+            ..ModelPreferences::prefer_cost()
+        };
+        assert_eq!(
+            build(out_of_range),
+            Err(SamplingRequestError::PriorityOutOfRange {
+                name: "speedPriority",
+                priority: 1.5
+            })
+        );
+        let nan = ModelPreferences {
+            cost_priority: Some(f32::NAN),
+            ..Default::default()
+        };
+        assert!(matches!(
+            build(nan),
+            Err(SamplingRequestError::PriorityOutOfRange {
+                name: "costPriority",
+                ..
+            })
+        ));
+        let empty_hint = ModelPreferences::new().with_hint("claude").with_hint("");
+        assert_eq!(
+            build(empty_hint),
+            Err(SamplingRequestError::EmptyHintName { index: 1 })
+        );
+        assert!(build(ModelPreferences::prefer_speed().with_hint("claude")).is_ok());
+    }
+
+    #[test]
+    fn test_model_preferences() {
+        let preferences = ModelPreferences::prefer_speed()
+            .cost_priority(-0.5)
+            .intelligence_priority(1.5)
+            .with_hint("claude-3-sonnet")
+            .with_hint("claude");
+        assert_eq!(preferences.speed_priority, Some(1.0));
+        assert_eq!(preferences.cost_priority, Some(0.0));
+        assert_eq!(preferences.intelligence_priority, Some(1.0));
+        assert_eq!(
+            ModelPreferences::new()
+                .speed_priority(f32::NAN)
+                .speed_priority,
+            Some(0.0)
+        );
+        assert_eq!(
+            serde_json::to_value(&preferences).unwrap()["hints"],
+            json!([{ "name": "claude-3-sonnet" }, { "name": "claude" }])
+        );
+        assert_eq!(
+            serde_json::to_value(ModelPreferences::prefer_intelligence()).unwrap(),
+            json!({ "intelligencePriority": 1.0 })
+        );
+    }
+}
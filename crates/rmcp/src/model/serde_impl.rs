@@ -1,10 +1,11 @@
 use std::borrow::Cow;
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use super::{
-    Extensions, Meta, Notification, NotificationNoParam, Request, RequestNoParam,
-    RequestOptionalParam,
+    Extensions, JsonRpcNotification, JsonRpcVersion2_0, Meta, Notification, NotificationNoParam,
+    Request, RequestNoParam, RequestOptionalParam,
 };
 #[derive(Serialize, Deserialize)]
 struct WithMeta<'a, P> {
@@ -27,8 +28,16 @@ struct ProxyOptionalParam<'a, M, P> {
 }
 
 #[derive(Serialize, Deserialize)]
-struct ProxyNoParam<M> {
+struct OnlyMeta<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    _meta: Option<Cow<'a, Meta>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProxyNoParam<'a, M> {
     method: M,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    params: Option<OnlyMeta<'a>>,
 }
 
 impl<M, R> Serialize for Request<M, R>
@@ -143,6 +152,7 @@ where
         ProxyNoParam::serialize(
             &ProxyNoParam {
                 method: &self.method,
+                params: _meta.map(|_meta| OnlyMeta { _meta: Some(_meta) }),
             },
             serializer,
         )
@@ -158,7 +168,10 @@ where
         D: serde::Deserializer<'de>,
     {
         let body = ProxyNoParam::<_>::deserialize(deserializer)?;
-        let extensions = Extensions::new();
+        let mut extensions = Extensions::new();
+        if let Some(meta) = body.params.and_then(|params| params._meta) {
+            extensions.insert(meta.into_owned());
+        }
         Ok(RequestNoParam {
             extensions,
             method: body.method,
@@ -226,6 +239,7 @@ where
         ProxyNoParam::serialize(
             &ProxyNoParam {
                 method: &self.method,
+                params: _meta.map(|_meta| OnlyMeta { _meta: Some(_meta) }),
             },
             serializer,
         )
@@ -241,7 +255,10 @@ where
         D: serde::Deserializer<'de>,
     {
         let body = ProxyNoParam::<_>::deserialize(deserializer)?;
-        let extensions = Extensions::new();
+        let mut extensions = Extensions::new();
+        if let Some(meta) = body.params.and_then(|params| params._meta) {
+            extensions.insert(meta.into_owned());
+        }
         Ok(NotificationNoParam {
             extensions,
             method: body.method,
@@ -253,7 +270,7 @@ where
 mod test {
     use serde_json::json;
 
-    use crate::model::ListToolsRequest;
+    use crate::model::{ListToolsRequest, Meta, PingRequest};
 
     #[test]
     fn test_deserialize_lost_tools_request() {
@@ -264,4 +281,52 @@ mod test {
         ))
         .unwrap();
     }
+
+    #[test]
+    fn test_no_param_request_keeps_meta() {
+        let ping = json!({
+            "method": "ping",
+            "params": { "_meta": { "pingPayload": "aGk=" } },
+        });
+        let req: PingRequest = serde_json::from_value(ping.clone()).unwrap();
+        let meta = req.extensions.get::<Meta>().unwrap();
+        assert_eq!(meta.get_ping_payload(), Some(&json!("aGk=")));
+        assert_eq!(serde_json::to_value(&req).unwrap(), ping);
+
+        let req: PingRequest = serde_json::from_value(json!({ "method": "ping" })).unwrap();
+        assert_eq!(
+            serde_json::to_value(&req).unwrap(),
+            json!({ "method": "ping" })
+        );
+    }
+}
+
+impl<'de, N> Deserialize<'de> for JsonRpcNotification<N>
+where
+    N: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Body<N> {
+            jsonrpc: JsonRpcVersion2_0,
+            // a null id is the same as none, any other id makes it a request
+            #[serde(default)]
+            id: Option<Value>,
+            #[serde(flatten)]
+            notification: N,
+        }
+        let body = Body::<N>::deserialize(deserializer)?;
+        if let Some(id) = body.id {
+            return Err(serde::de::Error::custom(format_args!(
+                "a notification can't have the id {id}"
+            )));
+        }
+        Ok(JsonRpcNotification {
+            jsonrpc: body.jsonrpc,
+            notification: body.notification,
+        })
+    }
 }
@@ -66,6 +66,10 @@ pub struct ToolAnnotations {
     ///
     /// Default: true
     pub open_world_hint: Option<bool>,
+
+    /// Set if the tool is deprecated, telling what to use instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>,
 }
 
 impl ToolAnnotations {
@@ -106,6 +110,13 @@ impl ToolAnnotations {
         }
     }
 
+    pub fn deprecated(self, message: impl Into<String>) -> Self {
+        ToolAnnotations {
+            deprecated: Some(message.into()),
+            ..self
+        }
+    }
+
     /// If not set, defaults to true.
     pub fn is_destructive(&self) -> bool {
         self.destructive_hint.unwrap_or(true)
@@ -140,8 +151,158 @@ impl Tool {
         }
     }
 
+    /// The deprecation message, if the tool is deprecated
+    pub fn deprecation(&self) -> Option<&str> {
+        self.annotations.as_ref()?.deprecated.as_deref()
+    }
+
     /// Get the schema as json value
     pub fn schema_as_json_value(&self) -> Value {
         Value::Object(self.input_schema.as_ref().clone())
     }
+
+    /// Validate the arguments of a call to this tool against its input schema.
+    #[cfg(feature = "schema-validation")]
+    pub fn validate_arguments(&self, arguments: Option<&JsonObject>) -> Result<(), crate::Error> {
+        validate_against_input_schema(&self.input_schema, arguments)
+    }
+}
+
+#[cfg(feature = "schema-validation")]
+pub(crate) fn validate_against_input_schema(
+    schema: &Arc<JsonObject>,
+    arguments: Option<&JsonObject>,
+) -> Result<(), crate::Error> {
+    let validator = cached_validator_for(schema)?;
+    let instance = Value::Object(arguments.cloned().unwrap_or_default());
+    let errors = validator
+        .iter_errors(&instance)
+        .map(|e| format!("{}: {e}", e.instance_path))
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(crate::Error::invalid_params(
+            format!(
+                "arguments don't match the input schema: {}",
+                errors.join("; ")
+            ),
+            Some(serde_json::json!({ "errors": errors })),
+        ))
+    }
+}
+
+/// Compile a schema once, the validators are cached by the address of the schema.
+///
+/// A cached validator keeps a weak reference to its schema, so the address isn't reused by
+/// another schema, and it's dropped with the next validator cached after the schema is gone.
+#[cfg(feature = "schema-validation")]
+fn cached_validator_for(
+    schema: &Arc<JsonObject>,
+) -> Result<Arc<jsonschema::Validator>, crate::Error> {
+    use std::{
+        collections::HashMap,
+        sync::{OnceLock, RwLock, Weak},
+    };
+
+    type ValidatorCache = HashMap<usize, (Weak<JsonObject>, Arc<jsonschema::Validator>)>;
+    // shared by the threads of the runtime, so a schema is compiled once per process
+    static CACHE_FOR_SCHEMA: OnceLock<RwLock<ValidatorCache>> = OnceLock::new();
+    let cache = CACHE_FOR_SCHEMA.get_or_init(Default::default);
+    let key = Arc::as_ptr(schema) as usize;
+    if let Some((_, validator)) = cache
+        .read()
+        .expect("validator cache lock poisoned")
+        .get(&key)
+    {
+        return Ok(validator.clone());
+    }
+    let validator =
+        jsonschema::validator_for(&Value::Object(schema.as_ref().clone())).map_err(|e| {
+            crate::Error::internal_error(format!("invalid tool input schema: {e}"), None)
+        })?;
+    let mut cache = cache.write().expect("validator cache lock poisoned");
+    // another thread may have compiled it meanwhile
+    let (_, validator) = cache
+        .entry(key)
+        .or_insert_with(|| (Arc::downgrade(schema), Arc::new(validator)));
+    let validator = validator.clone();
+    cache.retain(|_, (schema, _)| schema.strong_count() > 0);
+    Ok(validator)
+}
+
+/// The arguments of a tool call.
+///
+/// Tools take their arguments by name, some clients send them as a JSON array instead, which
+/// only the tools marked with `#[tool(positional)]` accept.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ToolArguments {
+    Named(JsonObject),
+    Positional(Vec<Value>),
+}
+
+impl ToolArguments {
+    /// The arguments, if they were given by name
+    pub fn as_named(&self) -> Option<&JsonObject> {
+        match self {
+            ToolArguments::Named(object) => Some(object),
+            ToolArguments::Positional(_) => None,
+        }
+    }
+
+    /// The arguments, if they were given by position
+    pub fn as_positional(&self) -> Option<&Vec<Value>> {
+        match self {
+            ToolArguments::Named(_) => None,
+            ToolArguments::Positional(values) => Some(values),
+        }
+    }
+}
+
+impl Default for ToolArguments {
+    fn default() -> Self {
+        ToolArguments::Named(JsonObject::new())
+    }
+}
+
+impl From<JsonObject> for ToolArguments {
+    fn from(object: JsonObject) -> Self {
+        ToolArguments::Named(object)
+    }
+}
+
+impl From<Vec<Value>> for ToolArguments {
+    fn from(values: Vec<Value>) -> Self {
+        ToolArguments::Positional(values)
+    }
+}
+
+#[cfg(all(test, feature = "schema-validation"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validator_compiled_once_per_schema() {
+        let schema = Arc::new(
+            serde_json::json!({ "type": "object" })
+                .as_object()
+                .cloned()
+                .unwrap(),
+        );
+        let first = cached_validator_for(&schema).unwrap();
+        let second = cached_validator_for(&schema.clone()).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        // the other threads reuse it too
+        let shared = schema.clone();
+        let third = std::thread::spawn(move || cached_validator_for(&shared).unwrap())
+            .join()
+            .unwrap();
+        assert!(Arc::ptr_eq(&first, &third));
+
+        // an equal schema elsewhere is compiled on its own
+        let other = Arc::new(schema.as_ref().clone());
+        let fourth = cached_validator_for(&other).unwrap();
+        assert!(!Arc::ptr_eq(&first, &fourth));
+    }
 }
@@ -0,0 +1,213 @@
+#[cfg(feature = "base64")]
+use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+use serde_json::Value;
+use thiserror::Error;
+
+use super::{CallToolResult, Content, ResourceContents};
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ToolResultError {
+    #[error("invalid mime type {0:?}")]
+    InvalidMimeType(String),
+    #[error("mime type {mime_type:?} is not an {expected} type")]
+    UnexpectedMimeType {
+        mime_type: String,
+        expected: &'static str,
+    },
+}
+
+/// Build a [`CallToolResult`] out of several contents, in the order they're added.
+///
+/// ```rust
+/// # use rmcp::model::*;
+/// let result = CallToolResult::builder()
+///     .text("the chart of the month")
+///     .image(b"\x89PNG", "image/png")
+///     .build()
+///     .unwrap();
+/// assert_eq!(result.content.len(), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ToolResultBuilder {
+    content: Vec<Content>,
+    structured_content: Option<Value>,
+    is_error: bool,
+    // the first invalid content, reported by build
+    error: Option<ToolResultError>,
+}
+
+impl ToolResultBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.content.push(Content::text(text));
+        self
+    }
+
+    /// Add an image, `data` is encoded in base64 and `mime_type` must be an `image/*` type
+    #[cfg(feature = "base64")]
+    pub fn image(mut self, data: impl AsRef<[u8]>, mime_type: impl Into<String>) -> Self {
+        let mime_type = mime_type.into();
+        match check_mime_type(&mime_type, "image") {
+            Ok(()) => self
+                .content
+                .push(Content::image(BASE64_STANDARD.encode(data), mime_type)),
+            Err(error) => {
+                self.error.get_or_insert(error);
+            }
+        }
+        self
+    }
+
+    /// Add an embedded resource, its mime type is checked if it has one
+    pub fn resource(mut self, resource: ResourceContents) -> Self {
+        let mime_type = match &resource {
+            ResourceContents::TextResourceContents { mime_type, .. }
+            | ResourceContents::BlobResourceContents { mime_type, .. } => mime_type.as_deref(),
+        };
+        if let Some(mime_type) = mime_type.filter(|mime_type| !is_mime_type(mime_type)) {
+            self.error
+                .get_or_insert(ToolResultError::InvalidMimeType(mime_type.to_owned()));
+        } else {
+            self.content.push(Content::resource(resource));
+        }
+        self
+    }
+
+    /// Set the structured content of the result
+    pub fn structured(mut self, value: Value) -> Self {
+        self.structured_content = Some(value);
+        self
+    }
+
+    /// Mark the result as an error of the tool
+    pub fn error(mut self) -> Self {
+        self.is_error = true;
+        self
+    }
+
+    /// Finish the result, failing with the first invalid content given
+    pub fn build(self) -> Result<CallToolResult, ToolResultError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        Ok(CallToolResult {
+            content: self.content,
+            structured_content: self.structured_content,
+            is_error: Some(self.is_error),
+        })
+    }
+}
+
+impl CallToolResult {
+    pub fn builder() -> ToolResultBuilder {
+        ToolResultBuilder::new()
+    }
+}
+
+#[cfg(feature = "base64")]
+fn check_mime_type(mime_type: &str, expected: &'static str) -> Result<(), ToolResultError> {
+    if !is_mime_type(mime_type) {
+        return Err(ToolResultError::InvalidMimeType(mime_type.to_owned()));
+    }
+    let (kind, _) = mime_type.split_once('/').unwrap_or_default();
+    if !kind.eq_ignore_ascii_case(expected) {
+        return Err(ToolResultError::UnexpectedMimeType {
+            mime_type: mime_type.to_owned(),
+            expected,
+        });
+    }
+    Ok(())
+}
+
+/// `type/subtype`, optionally followed by parameters, with the names of
+/// [RFC 6838](https://www.rfc-editor.org/rfc/rfc6838#section-4.2)
+fn is_mime_type(mime_type: &str) -> bool {
+    let essence = mime_type.split(';').next().unwrap_or_default().trim();
+    let is_name = |name: &str| {
+        name.bytes()
+            .next()
+            .is_some_and(|byte| byte.is_ascii_alphanumeric())
+            && name.len() <= 127
+            && name
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || b"!#$&-^_.+".contains(&byte))
+    };
+    essence
+        .split_once('/')
+        .is_some_and(|(kind, subtype)| is_name(kind) && is_name(subtype))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_text_and_image() {
+        let result = CallToolResult::builder()
+            .text("the chart of the month")
+            .image([0x89, b'P', b'N', b'G'], "image/png")
+            .structured(json!({ "month": "may" }))
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            json!({
+                "content": [
+                    { "type": "text", "text": "the chart of the month" },
+                    { "type": "image", "data": "iVBORw==", "mimeType": "image/png" },
+                ],
+                "structuredContent": { "month": "may" },
+                "isError": false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_error_result() {
+        let result = CallToolResult::builder()
+            .text("no such file")
+            .error()
+            .build()
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_invalid_image_mime_types() {
+        let result = CallToolResult::builder().image([0], "png").build();
+        assert_eq!(result, Err(ToolResultError::InvalidMimeType("png".into())));
+
+        let result = CallToolResult::builder().image([0], "text/plain").build();
+        assert_eq!(
+            result,
+            Err(ToolResultError::UnexpectedMimeType {
+                mime_type: "text/plain".into(),
+                expected: "image",
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_resource_mime_type() {
+        let result = CallToolResult::builder()
+            .resource(ResourceContents::TextResourceContents {
+                uri: "file:///notes".into(),
+                mime_type: Some("text plain".into()),
+                charset: None,
+                text: "notes".into(),
+            })
+            .build();
+        assert_eq!(
+            result,
+            Err(ToolResultError::InvalidMimeType("text plain".into()))
+        );
+        assert!(is_mime_type("text/plain; charset=utf-8"));
+    }
+}
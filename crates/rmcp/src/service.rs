@@ -1,30 +1,45 @@
-use futures::future::BoxFuture;
+use futures::{FutureExt, Sink, Stream, future::BoxFuture};
 use thiserror::Error;
 
 use crate::{
     error::Error as McpError,
     model::{
-        CancelledNotification, CancelledNotificationParam, Extensions, GetExtensions, GetMeta,
-        JsonRpcBatchRequestItem, JsonRpcBatchResponseItem, JsonRpcError, JsonRpcMessage,
-        JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, Meta, NumberOrString, ProgressToken,
-        RequestId, ServerJsonRpcMessage,
+        CancelledNotification, CancelledNotificationParam, EmptyResult, Extensions, GetExtensions,
+        GetMeta, JsonRpcBatchRequestItem, JsonRpcBatchResponseItem, JsonRpcError, JsonRpcMessage,
+        JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, Meta, NumberOrString,
+        ProgressNotificationParam, ProgressToken, RequestId, ServerJsonRpcMessage, TraceContext,
+        extract_trace_context,
     },
     transport::IntoTransport,
 };
 #[cfg(feature = "client")]
 mod client;
+mod clock;
+mod metrics;
+mod progress;
+mod request_id;
 #[cfg(feature = "client")]
 pub use client::*;
+pub use clock::*;
+pub use metrics::*;
+pub use progress::*;
+pub use request_id::*;
 #[cfg(feature = "server")]
 mod server;
+mod state;
 #[cfg(feature = "server")]
 pub use server::*;
+pub use state::*;
 #[cfg(feature = "tower")]
 mod tower;
+#[cfg(feature = "trace-context")]
+mod trace;
 use tokio_util::sync::{CancellationToken, DropGuard};
 #[cfg(feature = "tower")]
 pub use tower::*;
-use tracing::instrument;
+#[cfg(feature = "trace-context")]
+pub use trace::*;
+use tracing::{Instrument, instrument};
 #[derive(Error, Debug)]
 #[non_exhaustive]
 pub enum ServiceError {
@@ -38,9 +53,40 @@ pub enum ServiceError {
     Cancelled { reason: Option<String> },
     #[error("request timeout after {}", chrono::Duration::from_std(*timeout).unwrap_or_default())]
     Timeout { timeout: Duration },
+    /// The service stopped before the request or notification could complete
+    #[error("connection closed")]
+    ConnectionClosed,
+    #[error("too many pending requests, at most {max} are allowed")]
+    TooManyPendingRequests { max: usize },
+    /// A request with the same id is still waiting for its response
+    #[error("request id {id} is already pending")]
+    DuplicateRequestId { id: RequestId },
+    #[error("invalid sampling response: {0}")]
+    InvalidSamplingResponse(#[from] crate::model::SamplingResponseError),
+    /// A progress beyond its total, refused by [`ProgressValidation::Strict`]
+    #[error("progress {progress} exceeds its total {total}")]
+    InvalidProgress { progress: u32, total: u32 },
+    /// The peer didn't echo the payload of [`Peer::ping_with_payload`]
+    #[error("the ping payload wasn't echoed back")]
+    PingPayloadMismatch,
 }
 
-impl ServiceError {}
+impl ServiceError {
+    /// The error object the peer responded with, if that's why the request failed
+    pub fn as_mcp_error(&self) -> Option<&McpError> {
+        match self {
+            ServiceError::McpError(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    pub fn into_mcp_error(self) -> Option<McpError> {
+        match self {
+            ServiceError::McpError(error) => Some(error),
+            _ => None,
+        }
+    }
+}
 trait TransferObject:
     std::fmt::Debug + Clone + serde::Serialize + serde::de::DeserializeOwned + Send + Sync + 'static
 {
@@ -57,8 +103,16 @@ impl<T> TransferObject for T where
 {
 }
 
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The side of a connection, `RoleClient` or `RoleServer`. It's sealed, so that items can
+/// be added to it without breaking other crates.
 #[allow(private_bounds, reason = "there's no the third implementation")]
-pub trait ServiceRole: std::fmt::Debug + Send + Sync + 'static + Copy + Clone {
+pub trait ServiceRole:
+    sealed::Sealed + std::fmt::Debug + Send + Sync + 'static + Copy + Clone
+{
     type Req: TransferObject + GetMeta + GetExtensions;
     type Resp: TransferObject;
     type Not: TryInto<CancelledNotification, Error = Self::Not>
@@ -72,6 +126,33 @@ pub trait ServiceRole: std::fmt::Debug + Send + Sync + 'static + Copy + Clone {
     const IS_CLIENT: bool;
     type Info: TransferObject;
     type PeerInfo: TransferObject;
+    /// Whether a request from the peer is a handshake request, which is rejected once the
+    /// service is running.
+    fn is_handshake_request(_request: &Self::PeerReq) -> bool {
+        false
+    }
+    /// Whether a notification from the peer is a handshake notification, which is dropped once
+    /// the service is running.
+    fn is_handshake_notification(_notification: &Self::PeerNot) -> bool {
+        false
+    }
+    /// The parameters of a progress notification from the peer
+    fn progress_notification(_notification: &Self::PeerNot) -> Option<&ProgressNotificationParam> {
+        None
+    }
+    /// A progress notification to send to the peer
+    fn new_progress_notification(params: ProgressNotificationParam) -> Self::Not;
+    /// A ping request to send to the peer
+    fn new_ping_request() -> Self::Req;
+    /// The result of a ping, if the peer responded with one
+    fn into_ping_result(result: Self::PeerResp) -> Option<EmptyResult>;
+    /// The request methods answered by a service advertising `info`
+    fn supported_methods(_info: &Self::Info) -> Vec<&'static str> {
+        Vec::new()
+    }
+    /// The notification sent by [`RunningService::shutdown`] before closing the transport
+    #[cfg(feature = "shutdown-notification")]
+    fn shutdown_notification() -> Self::Not;
 }
 
 pub type TxJsonRpcMessage<R> =
@@ -92,9 +173,82 @@ pub trait Service<R: ServiceRole>: Send + Sync + 'static {
         &self,
         notification: R::PeerNot,
     ) -> impl Future<Output = Result<(), McpError>> + Send + '_;
+    /// Handle a request, and return the notifications to send right before its response.
+    ///
+    /// Override it instead of [`Service::handle_request`] when a response must not be seen
+    /// before the notifications produced along with it, the ones sent through
+    /// [`Peer::send_notification`] may be overtaken by the response.
+    fn handle_request_with_notifications(
+        &self,
+        request: R::PeerReq,
+        context: RequestContext<R>,
+    ) -> impl Future<Output = Result<WithNotifications<R::Resp, R::Not>, McpError>> + Send + '_
+    {
+        self.handle_request(request, context)
+            .map(|result| result.map(WithNotifications::new))
+    }
     fn get_peer(&self) -> Option<Peer<R>>;
     fn set_peer(&mut self, peer: Peer<R>);
     fn get_info(&self) -> R::Info;
+    /// Called once the handshake succeeded, before any other message is processed.
+    fn on_peer_initialized(&self, peer_info: &R::PeerInfo) {
+        let _ = peer_info;
+    }
+    /// The request methods this service handles, by default derived from the capabilities of
+    /// [`Service::get_info`].
+    fn supported_methods(&self) -> Vec<&'static str> {
+        R::supported_methods(&self.get_info())
+    }
+    /// Called when the peer responds to a request which isn't pending, for example one which
+    /// already timed out. The response is dropped and the connection stays open.
+    fn on_orphan_response(&self, id: &RequestId) {
+        let _ = id;
+    }
+    /// Called when the response to a request from the peer is ready to be sent, with how long
+    /// its handler took. A metrics middleware can record it here.
+    fn on_request_completed(&self, result: &Result<R::Resp, McpError>, timing: &RequestTiming) {
+        let _ = (result, timing);
+    }
+    /// The most requests of the peer handled at once, the others are queued until a handler
+    /// finishes, see [`RunningService::request_metrics`]. A request cancelled while queued is
+    /// answered with [`McpError::request_cancelled`] and its handler never runs. Not limited by
+    /// default.
+    fn max_concurrent_requests(&self) -> Option<NonZeroUsize> {
+        None
+    }
+}
+
+/// When a request was received and how long its handler took, see
+/// [`Service::on_request_completed`].
+#[derive(Debug, Clone)]
+pub struct RequestTiming {
+    pub id: RequestId,
+    /// When the service loop received the request
+    pub started_at: Instant,
+    /// The time from receiving the request to its response being produced
+    pub elapsed: Duration,
+}
+
+/// A response bundled with the notifications sent right before it, see
+/// [`Service::handle_request_with_notifications`].
+#[derive(Debug, Clone)]
+pub struct WithNotifications<T, N> {
+    pub response: T,
+    pub notifications: Vec<N>,
+}
+
+impl<T, N> WithNotifications<T, N> {
+    pub fn new(response: T) -> Self {
+        Self {
+            response,
+            notifications: Vec::new(),
+        }
+    }
+
+    pub fn with_notification(mut self, notification: impl Into<N>) -> Self {
+        self.notifications.push(notification.into());
+        self
+    }
 }
 
 pub trait ServiceExt<R: ServiceRole>: Service<R> + Sized {
@@ -142,6 +296,15 @@ impl<R: ServiceRole> Service<R> for Box<dyn DynService<R>> {
         DynService::handle_notification(self.as_ref(), notification)
     }
 
+    fn handle_request_with_notifications(
+        &self,
+        request: R::PeerReq,
+        context: RequestContext<R>,
+    ) -> impl Future<Output = Result<WithNotifications<R::Resp, R::Not>, McpError>> + Send + '_
+    {
+        DynService::handle_request_with_notifications(self.as_ref(), request, context)
+    }
+
     fn get_peer(&self) -> Option<Peer<R>> {
         DynService::get_peer(self.as_ref())
     }
@@ -153,6 +316,26 @@ impl<R: ServiceRole> Service<R> for Box<dyn DynService<R>> {
     fn get_info(&self) -> R::Info {
         DynService::get_info(self.as_ref())
     }
+
+    fn on_peer_initialized(&self, peer_info: &R::PeerInfo) {
+        DynService::on_peer_initialized(self.as_ref(), peer_info)
+    }
+
+    fn supported_methods(&self) -> Vec<&'static str> {
+        DynService::supported_methods(self.as_ref())
+    }
+
+    fn on_orphan_response(&self, id: &RequestId) {
+        DynService::on_orphan_response(self.as_ref(), id)
+    }
+
+    fn on_request_completed(&self, result: &Result<R::Resp, McpError>, timing: &RequestTiming) {
+        DynService::on_request_completed(self.as_ref(), result, timing)
+    }
+
+    fn max_concurrent_requests(&self) -> Option<NonZeroUsize> {
+        DynService::max_concurrent_requests(self.as_ref())
+    }
 }
 
 pub trait DynService<R: ServiceRole>: Send + Sync {
@@ -162,9 +345,19 @@ pub trait DynService<R: ServiceRole>: Send + Sync {
         context: RequestContext<R>,
     ) -> BoxFuture<Result<R::Resp, McpError>>;
     fn handle_notification(&self, notification: R::PeerNot) -> BoxFuture<Result<(), McpError>>;
+    fn handle_request_with_notifications(
+        &self,
+        request: R::PeerReq,
+        context: RequestContext<R>,
+    ) -> BoxFuture<Result<WithNotifications<R::Resp, R::Not>, McpError>>;
     fn get_peer(&self) -> Option<Peer<R>>;
     fn set_peer(&mut self, peer: Peer<R>);
     fn get_info(&self) -> R::Info;
+    fn on_peer_initialized(&self, peer_info: &R::PeerInfo);
+    fn supported_methods(&self) -> Vec<&'static str>;
+    fn on_orphan_response(&self, id: &RequestId);
+    fn on_request_completed(&self, result: &Result<R::Resp, McpError>, timing: &RequestTiming);
+    fn max_concurrent_requests(&self) -> Option<NonZeroUsize>;
 }
 
 impl<R: ServiceRole, S: Service<R>> DynService<R> for S {
@@ -178,6 +371,13 @@ impl<R: ServiceRole, S: Service<R>> DynService<R> for S {
     fn handle_notification(&self, notification: R::PeerNot) -> BoxFuture<Result<(), McpError>> {
         Box::pin(self.handle_notification(notification))
     }
+    fn handle_request_with_notifications(
+        &self,
+        request: R::PeerReq,
+        context: RequestContext<R>,
+    ) -> BoxFuture<Result<WithNotifications<R::Resp, R::Not>, McpError>> {
+        Box::pin(self.handle_request_with_notifications(request, context))
+    }
     fn get_peer(&self) -> Option<Peer<R>> {
         self.get_peer()
     }
@@ -187,16 +387,35 @@ impl<R: ServiceRole, S: Service<R>> DynService<R> for S {
     fn get_info(&self) -> R::Info {
         self.get_info()
     }
+    fn on_peer_initialized(&self, peer_info: &R::PeerInfo) {
+        self.on_peer_initialized(peer_info)
+    }
+    fn supported_methods(&self) -> Vec<&'static str> {
+        self.supported_methods()
+    }
+    fn on_orphan_response(&self, id: &RequestId) {
+        self.on_orphan_response(id)
+    }
+    fn on_request_completed(&self, result: &Result<R::Resp, McpError>, timing: &RequestTiming) {
+        self.on_request_completed(result, timing)
+    }
+    fn max_concurrent_requests(&self) -> Option<NonZeroUsize> {
+        self.max_concurrent_requests()
+    }
 }
 
 use std::{
+    any::Any,
+    borrow::Cow,
     collections::{HashMap, VecDeque},
+    num::NonZeroUsize,
     ops::Deref,
-    sync::{Arc, atomic::AtomicU32},
-    time::Duration,
+    panic::AssertUnwindSafe,
+    sync::{Arc, OnceLock, atomic::AtomicU32},
+    time::{Duration, Instant},
 };
 
-use tokio::sync::mpsc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, broadcast, mpsc};
 
 pub trait RequestIdProvider: Send + Sync + 'static {
     fn next_request_id(&self) -> RequestId;
@@ -242,21 +461,24 @@ pub struct RequestHandle<R: ServiceRole> {
     pub peer: Peer<R>,
     pub id: RequestId,
     pub progress_token: ProgressToken,
+    /// the slot taken in the pending requests limit, released with the handle
+    _permit: Option<OwnedSemaphorePermit>,
 }
 
 impl<R: ServiceRole> RequestHandle<R> {
     pub const REQUEST_TIMEOUT_REASON: &str = "request timeout";
+    /// The reason of the cancellation sent by [`Peer::send_request_cancel_on_drop`]
+    pub const REQUEST_DROPPED_REASON: &str = "request dropped";
     pub async fn await_response(self) -> Result<R::PeerResp, ServiceError> {
         if let Some(timeout) = self.options.timeout {
-            let timeout_result = tokio::time::timeout(timeout, async move {
-                self.rx
-                    .await
-                    .map_err(|_e| ServiceError::Transport(std::io::Error::other("disconnected")))?
-            })
-            .await;
+            let expired = self.peer.clock.sleep(timeout);
+            let timeout_result = tokio::select! {
+                response = self.rx => Some(response),
+                _ = expired => None,
+            };
             match timeout_result {
-                Ok(response) => response,
-                Err(_) => {
+                Some(response) => response.map_err(|_e| ServiceError::ConnectionClosed)?,
+                None => {
                     let error = Err(ServiceError::Timeout { timeout });
                     // cancel this request
                     let notification = CancelledNotification {
@@ -272,24 +494,44 @@ impl<R: ServiceRole> RequestHandle<R> {
                 }
             }
         } else {
-            self.rx
-                .await
-                .map_err(|_e| ServiceError::Transport(std::io::Error::other("disconnected")))?
+            self.rx.await.map_err(|_e| ServiceError::ConnectionClosed)?
         }
     }
 
     /// Cancel this request
     pub async fn cancel(self, reason: Option<String>) -> Result<(), ServiceError> {
+        self.peer.cancel(self.id, reason).await
+    }
+}
+
+/// Cancel the request `id` of `peer` when dropped, unless `id` was taken
+struct CancelOnDrop<R: ServiceRole> {
+    peer: Peer<R>,
+    id: Option<RequestId>,
+}
+
+impl<R: ServiceRole> Drop for CancelOnDrop<R> {
+    fn drop(&mut self) {
+        let Some(request_id) = self.id.take() else {
+            return;
+        };
         let notification = CancelledNotification {
             params: CancelledNotificationParam {
-                request_id: self.id,
-                reason,
+                request_id,
+                reason: Some(RequestHandle::<R>::REQUEST_DROPPED_REASON.to_owned()),
             },
             method: crate::model::CancelledNotificationMethod,
             extensions: Default::default(),
         };
-        self.peer.send_notification(notification.into()).await?;
-        Ok(())
+        // a drop can't wait for room in the queue
+        let (responder, _) = tokio::sync::oneshot::channel();
+        let message = PeerSinkMessage::Notification {
+            notification: notification.into(),
+            responder,
+        };
+        if self.peer.tx.try_send(message).is_err() {
+            tracing::debug!("fail to queue the cancellation of a dropped request");
+        }
     }
 }
 
@@ -317,6 +559,98 @@ pub struct Peer<R: ServiceRole> {
     request_id_provider: Arc<dyn RequestIdProvider>,
     progress_token_provider: Arc<dyn ProgressTokenProvider>,
     info: Arc<R::PeerInfo>,
+    pending_requests_limit: Option<PendingRequestsLimit>,
+    progress_handlers: ProgressHandlers,
+    notifications: broadcast::Sender<R::PeerNot>,
+    state: ConnectionState,
+    clock: Arc<dyn Clock>,
+    progress_validation: ProgressValidation,
+    transport_info: Arc<Extensions>,
+}
+
+type ProgressHandler = Arc<dyn Fn(ProgressNotificationParam) + Send + Sync + 'static>;
+type ProgressHandlers = Arc<std::sync::Mutex<HashMap<ProgressToken, ProgressHandler>>>;
+
+/// Remove the progress handler of a request once it's done
+struct ProgressHandlerGuard {
+    handlers: ProgressHandlers,
+    progress_token: ProgressToken,
+}
+
+impl Drop for ProgressHandlerGuard {
+    fn drop(&mut self) {
+        self.handlers
+            .lock()
+            .expect("progress handlers poisoned")
+            .remove(&self.progress_token);
+    }
+}
+
+/// What a new request does when the pending requests limit is reached
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PendingRequestsMode {
+    /// Wait until a pending request completes
+    #[default]
+    Wait,
+    /// Fail with [`ServiceError::TooManyPendingRequests`]
+    Reject,
+}
+
+/// What the progress notifications of a peer do with a progress beyond their total, a
+/// progress without total is never limited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProgressValidation {
+    /// Send the total as the progress
+    #[default]
+    Lenient,
+    /// Fail with [`ServiceError::InvalidProgress`]
+    Strict,
+}
+
+impl ProgressValidation {
+    pub fn check(
+        self,
+        mut params: ProgressNotificationParam,
+    ) -> Result<ProgressNotificationParam, ServiceError> {
+        match params.total {
+            Some(total) if params.progress > total => match self {
+                ProgressValidation::Lenient => {
+                    params.progress = total;
+                    Ok(params)
+                }
+                ProgressValidation::Strict => Err(ServiceError::InvalidProgress {
+                    progress: params.progress,
+                    total,
+                }),
+            },
+            _ => Ok(params),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PendingRequestsLimit {
+    semaphore: Arc<Semaphore>,
+    max: usize,
+    mode: PendingRequestsMode,
+}
+
+impl PendingRequestsLimit {
+    async fn acquire(&self) -> Result<OwnedSemaphorePermit, ServiceError> {
+        match self.mode {
+            PendingRequestsMode::Wait => self
+                .semaphore
+                .clone()
+                .acquire_owned()
+                .await
+                .map_err(|_| ServiceError::ConnectionClosed),
+            PendingRequestsMode::Reject => self
+                .semaphore
+                .clone()
+                .try_acquire_owned()
+                .map_err(|_| ServiceError::TooManyPendingRequests { max: self.max }),
+        }
+    }
 }
 
 impl<R: ServiceRole> std::fmt::Debug for Peer<R> {
@@ -344,6 +678,8 @@ impl PeerRequestOptions {
 
 impl<R: ServiceRole> Peer<R> {
     const CLIENT_CHANNEL_BUFFER_SIZE: usize = 1024;
+    /// The notifications kept for a [`Peer::notifications`] stream which lags behind
+    pub const NOTIFICATION_STREAM_BUFFER_SIZE: usize = 256;
     pub(crate) fn new(
         request_id_provider: Arc<dyn RequestIdProvider>,
         peer_info: R::PeerInfo,
@@ -355,10 +691,69 @@ impl<R: ServiceRole> Peer<R> {
                 request_id_provider,
                 progress_token_provider: Arc::new(AtomicU32ProgressTokenProvider::default()),
                 info: peer_info.into(),
+                pending_requests_limit: None,
+                progress_handlers: Default::default(),
+                notifications: broadcast::channel(Self::NOTIFICATION_STREAM_BUFFER_SIZE).0,
+                state: ConnectionState::default(),
+                clock: Arc::new(TokioClock),
+                progress_validation: ProgressValidation::default(),
+                transport_info: Default::default(),
             },
             rx,
         )
     }
+
+    pub(crate) fn with_transport_info(mut self, transport_info: Extensions) -> Self {
+        self.transport_info = Arc::new(transport_info);
+        self
+    }
+
+    /// Limit the number of requests of this peer waiting for a response.
+    ///
+    /// The limit is shared by the returned peer and its clones, a request holds its slot until
+    /// its [`RequestHandle`] is dropped, and `mode` decides what a request does once all the
+    /// slots are taken.
+    pub fn with_max_pending_requests(
+        mut self,
+        max_pending_requests: NonZeroUsize,
+        mode: PendingRequestsMode,
+    ) -> Self {
+        self.pending_requests_limit = Some(PendingRequestsLimit {
+            semaphore: Arc::new(Semaphore::new(max_pending_requests.get())),
+            max: max_pending_requests.get(),
+            mode,
+        });
+        self
+    }
+    /// Measure the request timeouts of the returned peer and its clones with `clock`
+    pub fn with_clock(mut self, clock: impl Clock) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+    /// Check the progress notified by the returned peer and its clones with `validation`
+    pub fn with_progress_validation(mut self, validation: ProgressValidation) -> Self {
+        self.progress_validation = validation;
+        self
+    }
+    /// Notify the progress of a request of the peer, checked with the
+    /// [`ProgressValidation`] of this peer
+    pub async fn notify_progress(
+        &self,
+        params: ProgressNotificationParam,
+    ) -> Result<(), ServiceError> {
+        let params = self.progress_validation.check(params)?;
+        self.send_notification(R::new_progress_notification(params))
+            .await
+    }
+    /// Take the ids of the requests sent through the returned peer and its clones from
+    /// `provider`.
+    ///
+    /// The ids must stay unique on the connection, so use it before sending any request and for
+    /// every handle of the peer.
+    pub fn with_request_id_provider(mut self, provider: impl RequestIdProvider) -> Self {
+        self.request_id_provider = Arc::new(provider);
+        self
+    }
     pub async fn send_notification(&self, notification: R::Not) -> Result<(), ServiceError> {
         let (responder, receiver) = tokio::sync::oneshot::channel();
         self.tx
@@ -367,12 +762,10 @@ impl<R: ServiceRole> Peer<R> {
                 responder,
             })
             .await
-            .map_err(|_m| {
-                ServiceError::Transport(std::io::Error::other("disconnected: receiver dropped"))
-            })?;
-        receiver.await.map_err(|_e| {
-            ServiceError::Transport(std::io::Error::other("disconnected: responder dropped"))
-        })?
+            .map_err(|_m| ServiceError::ConnectionClosed)?;
+        receiver
+            .await
+            .map_err(|_e| ServiceError::ConnectionClosed)?
     }
     pub async fn send_request(&self, request: R::Req) -> Result<R::PeerResp, ServiceError> {
         self.send_request_with_option(request, PeerRequestOptions::no_options())
@@ -381,6 +774,36 @@ impl<R: ServiceRole> Peer<R> {
             .await
     }
 
+    /// Send a request like [`Peer::send_request`], but the request is cancelled when the
+    /// future is dropped before the response, for example by a `select!` or an aborted task.
+    pub async fn send_request_cancel_on_drop(
+        &self,
+        request: R::Req,
+    ) -> Result<R::PeerResp, ServiceError> {
+        let handle = self
+            .send_request_with_option(request, PeerRequestOptions::no_options())
+            .await?;
+        let mut guard = CancelOnDrop {
+            peer: self.clone(),
+            id: Some(handle.id.clone()),
+        };
+        let response = handle.await_response().await;
+        guard.id = None;
+        response
+    }
+
+    /// Send a request along with a value of the caller, which comes back with the response or
+    /// the error, to route many responses without a map of the request ids.
+    ///
+    /// The context is never sent, it stays in the returned future.
+    pub async fn send_request_with_context<C>(
+        &self,
+        request: R::Req,
+        context: C,
+    ) -> (Result<R::PeerResp, ServiceError>, C) {
+        (self.send_request(request).await, context)
+    }
+
     pub async fn send_cancellable_request(
         &self,
         request: R::Req,
@@ -391,17 +814,175 @@ impl<R: ServiceRole> Peer<R> {
 
     pub async fn send_request_with_option(
         &self,
-        mut request: R::Req,
+        request: R::Req,
         options: PeerRequestOptions,
     ) -> Result<RequestHandle<R>, ServiceError> {
         let id = self.request_id_provider.next_request_id();
         let progress_token = self.progress_token_provider.next_progress_token();
+        self.send_request_with_id_and_progress_token(request, options, id, progress_token)
+            .await
+    }
+
+    /// Send a request with the given id instead of one from the id provider, a proxy uses it
+    /// to keep the id of the request it forwards.
+    ///
+    /// Fails with [`ServiceError::DuplicateRequestId`] if a request with this id is still
+    /// pending. The ids from the provider are numbers, so pick strings to stay clear of them.
+    pub async fn send_request_with_id(
+        &self,
+        id: RequestId,
+        request: R::Req,
+    ) -> Result<R::PeerResp, ServiceError> {
+        let progress_token = self.progress_token_provider.next_progress_token();
+        self.send_request_with_id_and_progress_token(
+            request,
+            PeerRequestOptions::no_options(),
+            id,
+            progress_token,
+        )
+        .await?
+        .await_response()
+        .await
+    }
+
+    /// Send a request and call `handler` with every progress the peer reports for it.
+    ///
+    /// The progress token is generated and put into the `_meta` of the request, and the
+    /// handler is dropped once the request completes. It's called from the service loop, so
+    /// keep it short.
+    pub async fn send_request_with_progress(
+        &self,
+        request: R::Req,
+        handler: impl Fn(ProgressNotificationParam) + Send + Sync + 'static,
+    ) -> Result<R::PeerResp, ServiceError> {
+        let progress_token = self.progress_token_provider.next_progress_token();
+        self.progress_handlers
+            .lock()
+            .expect("progress handlers poisoned")
+            .insert(progress_token.clone(), Arc::new(handler));
+        let _guard = ProgressHandlerGuard {
+            handlers: self.progress_handlers.clone(),
+            progress_token: progress_token.clone(),
+        };
+        self.send_request_with_id_and_progress_token(
+            request,
+            PeerRequestOptions::no_options(),
+            self.request_id_provider.next_request_id(),
+            progress_token,
+        )
+        .await?
+        .await_response()
+        .await
+    }
+
+    /// Call the progress handler of the request, returns whether there is one.
+    pub(crate) fn dispatch_progress(&self, progress: &ProgressNotificationParam) -> bool {
+        let handler = self
+            .progress_handlers
+            .lock()
+            .expect("progress handlers poisoned")
+            .get(&progress.progress_token)
+            .cloned();
+        if let Some(handler) = handler {
+            handler(progress.clone());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The notifications received from the peer from now on, in order.
+    ///
+    /// The service handler still gets every notification, the stream gets them too, except the
+    /// progress notifications routed to the progress handler of a request. The stream ends
+    /// when the service stops.
+    ///
+    /// A stream keeps up to [`Peer::NOTIFICATION_STREAM_BUFFER_SIZE`] notifications, when the
+    /// consumer lags further behind the oldest ones are dropped, with a warning.
+    pub fn notifications(&self) -> impl Stream<Item = R::PeerNot> + Send + 'static {
+        let rx = self.notifications.subscribe();
+        let tx = self.tx.clone();
+        futures::stream::unfold((rx, tx), |(mut rx, tx)| async move {
+            loop {
+                tokio::select! {
+                    biased;
+                    received = rx.recv() => match received {
+                        Ok(notification) => return Some((notification, (rx, tx))),
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            tracing::warn!(
+                                skipped,
+                                "notification stream lagging, drop the oldest notifications"
+                            );
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    },
+                    _ = tx.closed() => return None,
+                }
+            }
+        })
+    }
+
+    /// The state of this connection, see [`ConnectionState`]
+    pub fn state(&self) -> &ConnectionState {
+        &self.state
+    }
+
+    /// Whether the service of this peer stopped, then sending anything fails with
+    /// [`ServiceError::ConnectionClosed`]
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    /// Ping the peer with `payload`, which it must echo back unchanged.
+    ///
+    /// The payload is sent in base64 as the `pingPayload` of the request `_meta`, the ping
+    /// handlers of this SDK return it in the `_meta` of their result. Anything else fails with
+    /// [`ServiceError::PingPayloadMismatch`].
+    #[cfg(feature = "base64")]
+    pub async fn ping_with_payload(&self, payload: impl AsRef<[u8]>) -> Result<(), ServiceError> {
+        use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+        let payload = serde_json::Value::String(BASE64_STANDARD.encode(payload));
+        let mut request = R::new_ping_request();
+        request.get_meta_mut().set_ping_payload(payload.clone());
+        let result = self.send_request(request).await?;
+        let result = R::into_ping_result(result).ok_or(ServiceError::UnexpectedResponse)?;
+        let echoed = result.meta.as_ref().and_then(Meta::get_ping_payload);
+        if echoed != Some(&payload) {
+            return Err(ServiceError::PingPayloadMismatch);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn publish_notification(&self, notification: &R::PeerNot) {
+        if self.notifications.receiver_count() > 0 {
+            let _ = self.notifications.send(notification.clone());
+        }
+    }
+
+    async fn send_request_with_id_and_progress_token(
+        &self,
+        mut request: R::Req,
+        options: PeerRequestOptions,
+        id: RequestId,
+        progress_token: ProgressToken,
+    ) -> Result<RequestHandle<R>, ServiceError> {
+        let permit = match &self.pending_requests_limit {
+            Some(limit) => Some(limit.acquire().await?),
+            None => None,
+        };
         request
             .get_meta_mut()
             .set_progress_token(progress_token.clone());
         if let Some(meta) = options.meta.clone() {
             request.get_meta_mut().extend(meta);
         }
+        #[cfg(feature = "trace-context")]
+        if let Some(context) = current_trace_context() {
+            let meta = request.get_meta_mut();
+            if extract_trace_context(meta).is_none() {
+                crate::model::inject_trace_context(meta, &context);
+            }
+        }
         let (responder, receiver) = tokio::sync::oneshot::channel();
         self.tx
             .send(PeerSinkMessage::Request {
@@ -410,25 +991,60 @@ impl<R: ServiceRole> Peer<R> {
                 responder,
             })
             .await
-            .map_err(|_m| ServiceError::Transport(std::io::Error::other("disconnected")))?;
+            .map_err(|_m| ServiceError::ConnectionClosed)?;
         Ok(RequestHandle {
             id,
             rx: receiver,
             progress_token,
             options,
             peer: self.clone(),
+            _permit: permit,
         })
     }
+    /// Cancel a request sent through this peer.
+    ///
+    /// The peer is notified with a `notifications/cancelled` and the pending response
+    /// resolves with [`ServiceError::Cancelled`]. Nothing is sent if the request is unknown or
+    /// has already completed.
+    ///
+    /// [`RunningService::cancel`] stops the whole service, call this one through
+    /// [`RunningService::peer`].
+    pub async fn cancel(
+        &self,
+        request_id: RequestId,
+        reason: Option<String>,
+    ) -> Result<(), ServiceError> {
+        let notification = CancelledNotification {
+            params: CancelledNotificationParam { request_id, reason },
+            method: crate::model::CancelledNotificationMethod,
+            extensions: Default::default(),
+        };
+        self.send_notification(notification.into()).await
+    }
     pub fn peer_info(&self) -> &R::PeerInfo {
         &self.info
     }
+    /// What the transport knows about the connection, by type, for what's beyond the
+    /// protocol, like the [`SocketAddrs`](crate::transport::SocketAddrs) of a TCP connection:
+    ///
+    /// ```rust,ignore
+    /// let addrs = peer.transport_info().get::<SocketAddrs>();
+    /// ```
+    ///
+    /// It depends on the transport, see
+    /// [`IntoTransport::transport_info`](crate::transport::IntoTransport::transport_info).
+    pub fn transport_info(&self) -> &Extensions {
+        &self.transport_info
+    }
 }
 
 #[derive(Debug)]
 pub struct RunningService<R: ServiceRole, S: Service<R>> {
     service: Arc<S>,
     peer: Peer<R>,
-    handle: tokio::task::JoinHandle<QuitReason>,
+    handle: tokio::task::JoinHandle<CloseReason>,
+    shutdown_tx: mpsc::Sender<ShutdownOptions>,
+    request_metrics: RequestMetrics,
     /// cancellation token with drop guard
     dg: DropGuard,
 }
@@ -449,26 +1065,176 @@ impl<R: ServiceRole, S: Service<R>> RunningService<R, S> {
     pub fn service(&self) -> &S {
         self.service.as_ref()
     }
-    pub async fn waiting(self) -> Result<QuitReason, tokio::task::JoinError> {
+    /// The requests of the peer queued and in flight, kept up to date while the service runs
+    pub fn request_metrics(&self) -> &RequestMetrics {
+        &self.request_metrics
+    }
+    pub async fn waiting(self) -> Result<CloseReason, tokio::task::JoinError> {
         self.handle.await
     }
-    pub async fn cancel(self) -> Result<QuitReason, tokio::task::JoinError> {
+    pub async fn cancel(self) -> Result<CloseReason, tokio::task::JoinError> {
         let RunningService { dg, handle, .. } = self;
         dg.disarm().cancel();
         handle.await
     }
+    /// Stop the service gracefully.
+    ///
+    /// Unlike [`RunningService::cancel`], the outbound messages are flushed and the transport
+    /// is closed, then the messages the peer still sends are read until it closes its side or
+    /// [`ShutdownOptions::timeout`] elapses. Late responses still complete their requests, and
+    /// late notifications are delivered or dropped according to
+    /// [`ShutdownOptions::drain_notifications`].
+    ///
+    /// With the `shutdown-notification` feature, a `notifications/shutdown` is sent right before
+    /// the transport is closed.
+    pub async fn shutdown(
+        self,
+        options: ShutdownOptions,
+    ) -> Result<CloseReason, tokio::task::JoinError> {
+        if self.shutdown_tx.send(options).await.is_err() {
+            tracing::debug!("service already stopped");
+        }
+        self.waiting().await
+    }
+}
+
+/// How [`RunningService::shutdown`] stops the service
+#[derive(Debug, Clone)]
+pub struct ShutdownOptions {
+    /// Deliver the notifications received during shutdown to the service, or drop them
+    pub drain_notifications: bool,
+    /// How long to wait for the peer to close its side
+    pub timeout: Duration,
+}
+
+impl ShutdownOptions {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+}
+
+impl Default for ShutdownOptions {
+    fn default() -> Self {
+        Self {
+            drain_notifications: true,
+            timeout: Self::DEFAULT_TIMEOUT,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-pub enum QuitReason {
+/// Why a running service stopped, it's the output of [`RunningService::waiting`].
+///
+/// Supervisors can use it to decide whether it's worth reconnecting.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum CloseReason {
+    /// The service was cancelled locally, which is a clean shutdown
+    #[error("service cancelled")]
     Cancelled,
+    /// The peer closed the connection
+    #[error("connection closed by peer")]
     Closed,
+    /// Reading from or writing to the transport failed
+    #[error("transport error: {0}")]
+    TransportError(std::io::Error),
+    /// The transport timed out
+    #[error("transport timeout")]
+    Timeout,
+    /// The peer sent something that isn't a valid message
+    #[error("protocol violation: {0}")]
+    ProtocolViolation(ProtocolViolation),
+}
+
+/// The former name of [`CloseReason`]
+pub type QuitReason = CloseReason;
+
+/// The peer sent a message the protocol doesn't allow at this point.
+///
+/// The initialization fails with it as the source of an [`std::io::ErrorKind::InvalidData`]
+/// error, and a running service closes with [`CloseReason::ProtocolViolation`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("expected {expected} while {context}, got {got}")]
+pub struct ProtocolViolation {
+    /// What the protocol allows at this point
+    pub expected: Cow<'static, str>,
+    /// What the peer sent instead
+    pub got: String,
+    /// What the service was doing
+    pub context: &'static str,
+}
+
+impl ProtocolViolation {
+    pub fn new(
+        expected: impl Into<Cow<'static, str>>,
+        got: impl Into<String>,
+        context: &'static str,
+    ) -> Self {
+        Self {
+            expected: expected.into(),
+            got: got.into(),
+            context,
+        }
+    }
+
+    /// A message of the wrong kind, `got` is the message as it's sent
+    pub(crate) fn unexpected_message(
+        expected: impl Into<Cow<'static, str>>,
+        message: &impl serde::Serialize,
+        context: &'static str,
+    ) -> Self {
+        let got = serde_json::to_string(message).unwrap_or_else(|error| error.to_string());
+        Self::new(expected, got, context)
+    }
+}
+
+impl From<ProtocolViolation> for std::io::Error {
+    fn from(violation: ProtocolViolation) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, violation)
+    }
+}
+
+impl From<std::io::Error> for CloseReason {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::InvalidData => {
+                let violation = error
+                    .get_ref()
+                    .and_then(|error| error.downcast_ref::<ProtocolViolation>())
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        ProtocolViolation::new(
+                            "a valid JSON-RPC message",
+                            error.to_string(),
+                            "receiving a message",
+                        )
+                    });
+                CloseReason::ProtocolViolation(violation)
+            }
+            std::io::ErrorKind::TimedOut => CloseReason::Timeout,
+            _ => CloseReason::TransportError(error),
+        }
+    }
+}
+
+/// Recover the [`std::io::Error`] behind a transport error, or wrap it in one.
+pub(crate) fn transport_error_into_io<E>(error: E) -> std::io::Error
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let error: Box<dyn std::error::Error + Send + Sync> = Box::new(error);
+    match error.downcast::<std::io::Error>() {
+        Ok(error) => *error,
+        Err(error) => std::io::Error::other(error),
+    }
 }
 
+/// The reason of the `notifications/cancelled` of a request, set before its token is cancelled
+#[derive(Debug, Clone, Default)]
+struct CancellationReason(Arc<OnceLock<String>>);
+
 /// Request execution context
 #[derive(Debug, Clone)]
 pub struct RequestContext<R: ServiceRole> {
-    /// this token will be cancelled when the [`CancelledNotification`] is received.
+    /// this token will be cancelled when the [`CancelledNotification`] is received, see
+    /// [`RequestContext::cancellation_reason`] for why.
     pub ct: CancellationToken,
     pub id: RequestId,
     pub meta: Meta,
@@ -477,6 +1243,83 @@ pub struct RequestContext<R: ServiceRole> {
     pub peer: Peer<R>,
 }
 
+impl<R: ServiceRole> RequestContext<R> {
+    /// Get the value of type `T` stored in the state of this connection
+    pub fn state<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        self.peer.state().get()
+    }
+
+    /// The state of this connection, to insert or update its values
+    pub fn connection_state(&self) -> &ConnectionState {
+        self.peer.state()
+    }
+
+    /// Why the peer cancelled this request, once [`ct`](Self::ct) is cancelled by a
+    /// `notifications/cancelled` which gives a reason
+    pub fn cancellation_reason(&self) -> Option<&str> {
+        self.extensions
+            .get::<CancellationReason>()?
+            .0
+            .get()
+            .map(String::as_str)
+    }
+
+    /// The reporter of the progress of this request, `None` when the peer didn't ask for
+    /// progress with a progress token
+    pub fn progress_reporter(&self) -> Option<ProgressReporter<R>> {
+        if let Some(reporter) = self.extensions.get::<ProgressReporter<R>>() {
+            return Some(reporter.clone());
+        }
+        let progress_token = self.meta.get_progress_token()?;
+        Some(ProgressReporter::new(self.peer.clone(), progress_token))
+    }
+
+    /// Report the progress of this request, if the peer asked for it.
+    ///
+    /// The intermediate updates are coalesced rather than awaited when the transport can't keep
+    /// up, see [`ProgressReporter`].
+    pub async fn report_progress(
+        &self,
+        progress: u32,
+        total: Option<u32>,
+        message: Option<String>,
+    ) -> Result<(), ServiceError> {
+        match self.progress_reporter() {
+            Some(reporter) => reporter.report(progress, total, message).await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// Extract a readable message from a panic payload
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Handle a notification, a panicking handler is only logged
+async fn handle_notification_isolated<R: ServiceRole, S: Service<R>>(
+    service: &S,
+    notification: R::PeerNot,
+) {
+    let result = AssertUnwindSafe(service.handle_notification(notification))
+        .catch_unwind()
+        .await
+        .unwrap_or_else(|panic| {
+            let message = panic_message(panic.as_ref());
+            tracing::error!(%message, "notification handler panicked");
+            Ok(())
+        });
+    if let Err(error) = result {
+        tracing::warn!(%error, "Error sending notification");
+    }
+}
+
 /// Use this function to skip initialization process
 pub async fn serve_directly<R, S, T, E, A>(
     service: S,
@@ -506,13 +1349,14 @@ where
     E: std::error::Error + Send + Sync + 'static,
 {
     let (peer, peer_rx) = Peer::new(Arc::new(AtomicU32RequestIdProvider::default()), peer_info);
-    serve_inner(service, transport, peer, peer_rx, ct).await
+    let peer = peer.with_transport_info(transport.transport_info());
+    serve_inner(service, transport.into_transport(), peer, peer_rx, ct).await
 }
 
 #[instrument(skip_all)]
-async fn serve_inner<R, S, T, E, A>(
+async fn serve_inner<R, S, Tx, Rx, E>(
     mut service: S,
-    transport: T,
+    (sink, stream): (Tx, Rx),
     peer: Peer<R>,
     mut peer_rx: tokio::sync::mpsc::Receiver<PeerSinkMessage<R>>,
     ct: CancellationToken,
@@ -520,7 +1364,8 @@ async fn serve_inner<R, S, T, E, A>(
 where
     R: ServiceRole,
     S: Service<R>,
-    T: IntoTransport<R, E, A>,
+    Tx: Sink<TxJsonRpcMessage<R>, Error = E> + Send + 'static,
+    Rx: Stream<Item = Result<RxJsonRpcMessage<R>, E>> + Send + 'static,
     E: std::error::Error + Send + Sync + 'static,
 {
     use futures::{SinkExt, StreamExt};
@@ -536,7 +1381,12 @@ where
 
     service.set_peer(peer.clone());
     let mut local_responder_pool = HashMap::new();
-    let mut local_ct_pool = HashMap::<RequestId, CancellationToken>::new();
+    let mut local_ct_pool = HashMap::<RequestId, (CancellationToken, CancellationReason)>::new();
+    let concurrency_limit = service
+        .max_concurrent_requests()
+        .map(|max| Arc::new(Semaphore::new(max.get())));
+    let request_metrics = RequestMetrics::default();
+    let request_metrics_return = request_metrics.clone();
     let shared_service = Arc::new(service);
     // for return
     let service = shared_service.clone();
@@ -545,11 +1395,12 @@ where
     // let mut stream = std::pin::pin!(stream);
     let serve_loop_ct = ct.child_token();
     let peer_return: Peer<R> = peer.clone();
+    let (shutdown_tx, mut shutdown_rx) = mpsc::channel::<ShutdownOptions>(1);
     let handle = tokio::spawn(async move {
-        let (mut sink, mut stream) = transport.into_transport();
         let mut sink = std::pin::pin!(sink);
         let mut stream = std::pin::pin!(stream);
         let mut batch_messages = VecDeque::<RxJsonRpcMessage<R>>::new();
+        let mut shutdown = None;
         #[derive(Debug)]
         enum Event<P, R, T> {
             ProxyMessage(P),
@@ -569,12 +1420,17 @@ where
                         }
                     }
                     m = stream.next() => {
-                        if let Some(m) = m {
-                            Event::PeerMessage(m)
-                        } else {
-                            // input stream closed
-                            tracing::info!("input stream terminated");
-                            break QuitReason::Closed
+                        match m {
+                            Some(Ok(m)) => Event::PeerMessage(m),
+                            Some(Err(error)) => {
+                                tracing::error!(%error, "fail to receive message");
+                                break CloseReason::from(transport_error_into_io(error))
+                            }
+                            None => {
+                                // input stream closed
+                                tracing::info!("input stream terminated");
+                                break CloseReason::Closed
+                            }
                         }
                     }
                     m = peer_rx.recv() => {
@@ -586,7 +1442,12 @@ where
                     }
                     _ = serve_loop_ct.cancelled() => {
                         tracing::info!("task cancelled");
-                        break QuitReason::Cancelled
+                        break CloseReason::Cancelled
+                    }
+                    Some(options) = shutdown_rx.recv() => {
+                        tracing::info!(?options, "shutting down");
+                        shutdown = Some(options);
+                        break CloseReason::Cancelled
                     }
                 }
             };
@@ -600,12 +1461,13 @@ where
                         JsonRpcMessage::Error(error) => Some(&error.id),
                         _ => None,
                     } {
-                        if let Some(ct) = local_ct_pool.remove(id) {
+                        if let Some((ct, _)) = local_ct_pool.remove(id) {
                             ct.cancel();
                         }
                         let send_result = sink.send(m).await;
                         if let Err(error) = send_result {
                             tracing::error!(%error, "fail to response message");
+                            break CloseReason::from(transport_error_into_io(error));
                         }
                     }
                 }
@@ -614,15 +1476,23 @@ where
                     id,
                     responder,
                 }) => {
+                    if local_responder_pool.contains_key(&id) {
+                        tracing::warn!(%id, "reject request with a pending id");
+                        let _ = responder.send(Err(ServiceError::DuplicateRequestId { id }));
+                        continue;
+                    }
                     local_responder_pool.insert(id.clone(), responder);
                     let send_result = sink
                         .send(JsonRpcMessage::request(request, id.clone()))
                         .await;
                     if let Err(e) = send_result {
+                        let error = transport_error_into_io(e);
                         if let Some(responder) = local_responder_pool.remove(&id) {
-                            let _ = responder
-                                .send(Err(ServiceError::Transport(std::io::Error::other(e))));
+                            let _ = responder.send(Err(ServiceError::Transport(
+                                std::io::Error::new(error.kind(), error.to_string()),
+                            )));
                         }
+                        break CloseReason::from(error);
                     }
                 }
                 Event::ProxyMessage(PeerSinkMessage::Notification {
@@ -633,18 +1503,27 @@ where
                     let mut cancellation_param = None;
                     let notification = match notification.try_into() {
                         Ok::<CancelledNotification, _>(cancelled) => {
+                            let id = &cancelled.params.request_id;
+                            if !local_responder_pool.contains_key(id) {
+                                tracing::debug!(%id, "skip cancellation of an unknown request");
+                                let _ = responder.send(Ok(()));
+                                continue;
+                            }
                             cancellation_param.replace(cancelled.params.clone());
                             cancelled.into()
                         }
                         Err(notification) => notification,
                     };
                     let send_result = sink.send(JsonRpcMessage::notification(notification)).await;
-                    let response = if let Err(e) = send_result {
-                        Err(ServiceError::Transport(std::io::Error::other(e)))
-                    } else {
-                        Ok(())
-                    };
-                    let _ = responder.send(response);
+                    if let Err(e) = send_result {
+                        let error = transport_error_into_io(e);
+                        let _ = responder.send(Err(ServiceError::Transport(std::io::Error::new(
+                            error.kind(),
+                            error.to_string(),
+                        ))));
+                        break CloseReason::from(error);
+                    }
+                    let _ = responder.send(Ok(()));
                     if let Some(param) = cancellation_param {
                         if let Some(responder) = local_responder_pool.remove(&param.request_id) {
                             tracing::info!(id = %param.request_id, reason = param.reason, "cancelled");
@@ -658,23 +1537,120 @@ where
                     id, request, ..
                 })) => {
                     tracing::debug!(%id, ?request, "received request");
+                    if R::is_handshake_request(&request) {
+                        tracing::warn!(%id, "reject handshake request of an initialized service");
+                        let sink = sink_proxy_tx.clone();
+                        tokio::spawn(async move {
+                            let error = JsonRpcMessage::error(McpError::already_initialized(), id);
+                            let _send_result = sink.send(error).await;
+                        });
+                        continue;
+                    }
                     {
+                        let started_at = Instant::now();
+                        let queued = request_metrics.enqueue();
+                        let concurrency_limit = concurrency_limit.clone();
                         let service = shared_service.clone();
                         let sink = sink_proxy_tx.clone();
                         let request_ct = serve_loop_ct.child_token();
                         let context_ct = request_ct.child_token();
-                        local_ct_pool.insert(id.clone(), request_ct);
-                        let context = RequestContext {
+                        let cancellation_reason = CancellationReason::default();
+                        local_ct_pool.insert(id.clone(), (request_ct, cancellation_reason.clone()));
+                        let mut context = RequestContext {
                             ct: context_ct,
                             id: id.clone(),
                             peer: peer.clone(),
                             meta: request.get_meta().clone(),
                             extensions: request.extensions().clone(),
                         };
+                        context.extensions.insert(cancellation_reason);
+                        // shared by every report of the request, to coalesce them
+                        if let Some(progress_token) = context.meta.get_progress_token() {
+                            context
+                                .extensions
+                                .insert(ProgressReporter::new(peer.clone(), progress_token));
+                        }
+                        let trace_context = extract_trace_context(&context.meta);
+                        let span = tracing::info_span!(
+                            "request",
+                            %id,
+                            trace_id = trace_context.as_ref().map(TraceContext::trace_id),
+                            parent_id = trace_context.as_ref().map(TraceContext::parent_id),
+                        );
                         tokio::spawn(async move {
-                            let result = service.handle_request(request, context).await;
+                            let _permit = match concurrency_limit {
+                                Some(limit) => tokio::select! {
+                                    permit = limit.acquire_owned() => {
+                                        Some(permit.expect("the limit is never closed"))
+                                    }
+                                    _ = context.ct.cancelled() => {
+                                        // a request cancelled while queued is answered without
+                                        // running its handler
+                                        drop(queued);
+                                        let error = McpError::request_cancelled(
+                                            context.cancellation_reason(),
+                                        );
+                                        let timing = RequestTiming {
+                                            id: id.clone(),
+                                            started_at,
+                                            elapsed: started_at.elapsed(),
+                                        };
+                                        service.on_request_completed(&Err(error.clone()), &timing);
+                                        tracing::debug!(%id, "request cancelled while queued");
+                                        let _send_result =
+                                            sink.send(JsonRpcMessage::error(error, id)).await;
+                                        return;
+                                    }
+                                },
+                                None => None,
+                            };
+                            let _in_flight = queued.start();
+                            let handler =
+                                service.handle_request_with_notifications(request, context);
+                            // nested requests of the handler keep the trace of this one
+                            #[cfg(feature = "trace-context")]
+                            let handler = async move {
+                                match trace_context {
+                                    Some(trace_context) => {
+                                        with_trace_context(trace_context, handler).await
+                                    }
+                                    None => handler.await,
+                                }
+                            };
+                            // a panicking handler only fails its own request
+                            let result = AssertUnwindSafe(handler.instrument(span))
+                                .catch_unwind()
+                                .await
+                                .unwrap_or_else(|panic| {
+                                    let message = panic_message(panic.as_ref());
+                                    tracing::error!(%id, %message, "request handler panicked");
+                                    Err(McpError::internal_error(
+                                        format!("request handler panicked: {message}"),
+                                        None,
+                                    ))
+                                });
+                            let (result, notifications) = match result {
+                                Ok(WithNotifications {
+                                    response,
+                                    notifications,
+                                }) => (Ok(response), notifications),
+                                Err(error) => (Err(error), Vec::new()),
+                            };
+                            let timing = RequestTiming {
+                                id: id.clone(),
+                                started_at,
+                                elapsed: started_at.elapsed(),
+                            };
+                            service.on_request_completed(&result, &timing);
                             let response = match result {
                                 Ok(result) => {
+                                    // both go through the same sink, so the notifications are
+                                    // written before the response
+                                    for notification in notifications {
+                                        let _send_result = sink
+                                            .send(JsonRpcMessage::notification(notification))
+                                            .await;
+                                    }
                                     tracing::debug!(%id, ?result, "response message");
                                     JsonRpcMessage::response(result, id)
                                 }
@@ -692,11 +1668,25 @@ where
                     ..
                 })) => {
                     tracing::info!(?notification, "received notification");
+                    if R::is_handshake_notification(&notification) {
+                        tracing::warn!("drop handshake notification of an initialized service");
+                        continue;
+                    }
+                    let consumed = R::progress_notification(&notification)
+                        .is_some_and(|progress| peer.dispatch_progress(progress));
+                    if !consumed {
+                        peer.publish_notification(&notification);
+                    }
                     // catch cancelled notification
                     let notification = match notification.try_into() {
                         Ok::<CancelledNotification, _>(cancelled) => {
-                            if let Some(ct) = local_ct_pool.remove(&cancelled.params.request_id) {
+                            if let Some((ct, cancellation_reason)) =
+                                local_ct_pool.remove(&cancelled.params.request_id)
+                            {
                                 tracing::info!(id = %cancelled.params.request_id, reason = cancelled.params.reason, "cancelled");
+                                if let Some(reason) = &cancelled.params.reason {
+                                    let _ = cancellation_reason.0.set(reason.clone());
+                                }
                                 ct.cancel();
                             }
                             cancelled.into()
@@ -706,10 +1696,7 @@ where
                     {
                         let service = shared_service.clone();
                         tokio::spawn(async move {
-                            let result = service.handle_notification(notification).await;
-                            if let Err(error) = result {
-                                tracing::warn!(%error, "Error sending notification");
-                            }
+                            handle_notification_isolated(service.as_ref(), notification).await;
                         });
                     }
                 }
@@ -723,6 +1710,9 @@ where
                         if let Err(_error) = response_result {
                             tracing::warn!(%id, "Error sending response");
                         }
+                    } else {
+                        tracing::warn!(%id, "ignore response to an unknown request");
+                        shared_service.on_orphan_response(&id);
                     }
                 }
                 Event::PeerMessage(JsonRpcMessage::Error(JsonRpcError { error, id, .. })) => {
@@ -731,6 +1721,9 @@ where
                         if let Err(_error) = _response_result {
                             tracing::warn!(%id, "Error sending response");
                         }
+                    } else {
+                        tracing::warn!(%id, ?error, "ignore error response to an unknown request");
+                        shared_service.on_orphan_response(&id);
                     }
                 }
                 Event::PeerMessage(JsonRpcMessage::BatchRequest(batch)) => {
@@ -749,10 +1742,82 @@ where
                 }
             }
         };
-        let sink_close_result = sink.close().await;
-        if let Err(e) = sink_close_result {
-            tracing::error!(%e, "fail to close sink");
+        let mut sink_closed = false;
+        if let Some(options) = shutdown {
+            // flush the responses already produced, then let the peer know we're done
+            while let Ok(message) = sink_proxy_rx.try_recv() {
+                if let Err(e) = sink.send(message).await {
+                    tracing::error!(%e, "fail to flush message");
+                    break;
+                }
+            }
+            #[cfg(feature = "shutdown-notification")]
+            if let Err(e) = sink
+                .send(JsonRpcMessage::notification(R::shutdown_notification()))
+                .await
+            {
+                tracing::error!(%e, "fail to send shutdown notification");
+            }
+            if let Err(e) = sink.close().await {
+                tracing::error!(%e, "fail to close sink");
+            }
+            sink_closed = true;
+            let drain = async {
+                while let Some(Ok(message)) = stream.next().await {
+                    match message {
+                        JsonRpcMessage::Notification(JsonRpcNotification {
+                            notification, ..
+                        }) => {
+                            if options.drain_notifications {
+                                handle_notification_isolated(shared_service.as_ref(), notification)
+                                    .await;
+                            } else {
+                                tracing::debug!(?notification, "drop notification on shutdown");
+                            }
+                        }
+                        JsonRpcMessage::Response(JsonRpcResponse { result, id, .. }) => {
+                            if let Some(responder) = local_responder_pool.remove(&id) {
+                                let _ = responder.send(Ok(result));
+                            }
+                        }
+                        JsonRpcMessage::Error(JsonRpcError { error, id, .. }) => {
+                            if let Some(responder) = local_responder_pool.remove(&id) {
+                                let _ = responder.send(Err(ServiceError::McpError(error)));
+                            }
+                        }
+                        message => {
+                            tracing::debug!(?message, "ignore message on shutdown");
+                        }
+                    }
+                }
+            };
+            if tokio::time::timeout(options.timeout, drain).await.is_err() {
+                tracing::warn!(timeout = ?options.timeout, "peer didn't close on shutdown");
+            }
+        }
+        // nobody is going to answer now, stop the handlers and fail everything still waiting
+        serve_loop_ct.cancel();
+        peer_rx.close();
+        for (_, responder) in local_responder_pool.drain() {
+            let _ = responder.send(Err(ServiceError::ConnectionClosed));
+        }
+        while let Ok(message) = peer_rx.try_recv() {
+            match message {
+                PeerSinkMessage::Request { responder, .. } => {
+                    let _ = responder.send(Err(ServiceError::ConnectionClosed));
+                }
+                PeerSinkMessage::Notification { responder, .. } => {
+                    let _ = responder.send(Err(ServiceError::ConnectionClosed));
+                }
+            }
+        }
+        if !sink_closed {
+            let sink_close_result = sink.close().await;
+            if let Err(e) = sink_close_result {
+                tracing::error!(%e, "fail to close sink");
+            }
         }
+        peer.state().clear();
         tracing::info!(?quit_reason, "serve finished");
         quit_reason
     });
@@ -760,6 +1825,8 @@ where
         service,
         peer: peer_return,
         handle,
+        shutdown_tx,
+        request_metrics: request_metrics_return,
         dg: ct.drop_guard(),
     })
 }
@@ -1,35 +1,52 @@
+use std::borrow::Cow;
+
 use futures::{SinkExt, Stream, StreamExt};
 use thiserror::Error;
 
 use super::*;
 use crate::model::{
-    CallToolRequest, CallToolRequestParam, CallToolResult, CancelledNotification,
+    AnnotateAble, CallToolRequest, CallToolRequestParam, CallToolResult, CancelledNotification,
     CancelledNotificationParam, ClientInfo, ClientJsonRpcMessage, ClientNotification,
     ClientRequest, ClientResult, CompleteRequest, CompleteRequestParam, CompleteResult,
-    GetPromptRequest, GetPromptRequestParam, GetPromptResult, InitializeRequest,
-    InitializedNotification, JsonRpcResponse, ListPromptsRequest, ListPromptsResult,
-    ListResourceTemplatesRequest, ListResourceTemplatesResult, ListResourcesRequest,
-    ListResourcesResult, ListToolsRequest, ListToolsResult, PaginatedRequestParam,
-    ProgressNotification, ProgressNotificationParam, ReadResourceRequest, ReadResourceRequestParam,
-    ReadResourceResult, RequestId, RootsListChangedNotification, ServerInfo, ServerJsonRpcMessage,
+    EmptyResult, ErrorData, GetPromptRequest, GetPromptRequestParam, GetPromptResult,
+    InitializeRequest, InitializedNotification, JsonRpcError, JsonRpcResponse, ListPromptsRequest,
+    ListPromptsResult, ListResourceTemplatesRequest, ListResourceTemplatesResult,
+    ListResourcesRequest, ListResourcesResult, ListToolsRequest, ListToolsResult, Meta,
+    PaginatedRequestParam, PingRequest, ProgressNotification, ProgressNotificationParam,
+    PromptMessage, PromptMessageContent, ProtocolVersion, RawEmbeddedResource, ReadResourceEntry,
+    ReadResourceRequest, ReadResourceRequestParam, ReadResourceResult, RequestId, ResourceEncoding,
+    ResourceRange, RootsListChangedNotification, ServerInfo, ServerJsonRpcMessage,
     ServerNotification, ServerRequest, ServerResult, SetLevelRequest, SetLevelRequestParam,
-    SubscribeRequest, SubscribeRequestParam, UnsubscribeRequest, UnsubscribeRequestParam,
+    SubscribeRequest, SubscribeRequestParam, Tool, UnsubscribeRequest, UnsubscribeRequestParam,
 };
 
+mod pool;
+pub use pool::*;
+
 /// It represents the error that may occur when serving the client.
 ///
 /// if you want to handle the error, you can use `serve_client_with_ct` or `serve_client` with `Result<RunningService<RoleClient, S>, ClientError>`
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum ClientError {
+    #[deprecated(note = "reported as `ClientError::ProtocolViolation`")]
     #[error("expect initialized response, but received: {0:?}")]
     ExpectedInitResponse(Option<ServerJsonRpcMessage>),
 
+    #[deprecated(note = "reported as `ClientError::ProtocolViolation`")]
     #[error("expect initialized result, but received: {0:?}")]
     ExpectedInitResult(Option<ServerResult>),
 
+    #[deprecated(note = "reported as `ClientError::ProtocolViolation`")]
     #[error("conflict initialized response id: expected {0}, got {1}")]
     ConflictInitResponseId(RequestId, RequestId),
 
+    #[error("protocol violation: {0}")]
+    ProtocolViolation(#[from] ProtocolViolation),
+
+    #[error("initialize failed: {0}")]
+    InitializeFailed(ErrorData),
+
     #[error("connection closed: {0}")]
     ConnectionClosed(String),
 
@@ -38,39 +55,52 @@ pub enum ClientError {
 }
 
 /// Helper function to get the next message from the stream
-async fn expect_next_message<S>(
+async fn expect_next_message<S, E>(
     stream: &mut S,
     context: &str,
 ) -> Result<ServerJsonRpcMessage, ClientError>
 where
-    S: Stream<Item = ServerJsonRpcMessage> + Unpin,
+    S: Stream<Item = Result<ServerJsonRpcMessage, E>> + Unpin,
+    E: std::error::Error + Send + Sync + 'static,
 {
     stream
         .next()
         .await
         .ok_or_else(|| ClientError::ConnectionClosed(context.to_string()))
-        .map_err(|e| ClientError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))
+        .map_err(|e| ClientError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))?
+        .map_err(|e| ClientError::Io(transport_error_into_io(e)))
 }
 
 /// Helper function to expect a response from the stream
-async fn expect_response<S>(
+async fn expect_response<S, E>(
     stream: &mut S,
     context: &str,
 ) -> Result<(ServerResult, RequestId), ClientError>
 where
-    S: Stream<Item = ServerJsonRpcMessage> + Unpin,
+    S: Stream<Item = Result<ServerJsonRpcMessage, E>> + Unpin,
+    E: std::error::Error + Send + Sync + 'static,
 {
     let msg = expect_next_message(stream, context).await?;
 
     match msg {
         ServerJsonRpcMessage::Response(JsonRpcResponse { id, result, .. }) => Ok((result, id)),
-        _ => Err(ClientError::ExpectedInitResponse(Some(msg))),
+        ServerJsonRpcMessage::Error(JsonRpcError { error, .. }) => {
+            Err(ClientError::InitializeFailed(error))
+        }
+        _ => Err(ProtocolViolation::unexpected_message(
+            "the initialize response",
+            &msg,
+            "initializing",
+        )
+        .into()),
     }
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct RoleClient;
 
+impl super::sealed::Sealed for RoleClient {}
+
 impl ServiceRole for RoleClient {
     type Req = ClientRequest;
     type Resp = ClientResult;
@@ -82,6 +112,49 @@ impl ServiceRole for RoleClient {
     type PeerInfo = ServerInfo;
 
     const IS_CLIENT: bool = true;
+
+    fn progress_notification(
+        notification: &ServerNotification,
+    ) -> Option<&ProgressNotificationParam> {
+        match notification {
+            ServerNotification::ProgressNotification(notification) => Some(&notification.params),
+            _ => None,
+        }
+    }
+
+    fn new_progress_notification(params: ProgressNotificationParam) -> ClientNotification {
+        ClientNotification::ProgressNotification(ProgressNotification {
+            method: Default::default(),
+            params,
+            extensions: Default::default(),
+        })
+    }
+
+    fn new_ping_request() -> ClientRequest {
+        ClientRequest::PingRequest(PingRequest {
+            method: Default::default(),
+            extensions: Default::default(),
+        })
+    }
+
+    fn into_ping_result(result: ServerResult) -> Option<EmptyResult> {
+        match result {
+            ServerResult::EmptyResult(result) => Some(result),
+            _ => None,
+        }
+    }
+
+    fn supported_methods(info: &ClientInfo) -> Vec<&'static str> {
+        info.capabilities.supported_methods()
+    }
+
+    #[cfg(feature = "shutdown-notification")]
+    fn shutdown_notification() -> ClientNotification {
+        ClientNotification::ShutdownNotification(crate::model::ShutdownNotification {
+            method: Default::default(),
+            extensions: Default::default(),
+        })
+    }
 }
 
 pub type ServerSink = Peer<RoleClient>;
@@ -123,6 +196,7 @@ where
     T: IntoTransport<RoleClient, E, A>,
     E: std::error::Error + From<std::io::Error> + Send + Sync + 'static,
 {
+    let transport_info = transport.transport_info();
     let (sink, stream) = transport.into_transport();
     let mut sink = Box::pin(sink);
     let mut stream = Box::pin(stream);
@@ -132,6 +206,7 @@ where
     let handle_client_error = |e: ClientError| -> E {
         match e {
             ClientError::Io(io_err) => io_err.into(),
+            ClientError::ProtocolViolation(violation) => std::io::Error::from(violation).into(),
             other => std::io::Error::new(std::io::ErrorKind::Other, format!("{}", other)).into(),
         }
     };
@@ -154,16 +229,25 @@ where
         .map_err(handle_client_error)?;
 
     if id != response_id {
-        return Err(handle_client_error(ClientError::ConflictInitResponseId(
-            id,
-            response_id,
-        )));
+        return Err(handle_client_error(
+            ProtocolViolation::new(
+                format!("the response to request {id}"),
+                format!("a response to request {response_id}"),
+                "initializing",
+            )
+            .into(),
+        ));
     }
 
     let ServerResult::InitializeResult(initialize_result) = response else {
-        return Err(handle_client_error(ClientError::ExpectedInitResult(Some(
-            response,
-        ))));
+        return Err(handle_client_error(
+            ProtocolViolation::unexpected_message(
+                "an initialize result",
+                &response,
+                "initializing",
+            )
+            .into(),
+        ));
     };
 
     // send notification
@@ -174,7 +258,9 @@ where
         }),
     );
     sink.send(notification).await?;
+    service.on_peer_initialized(&initialize_result);
     let (peer, peer_rx) = Peer::new(id_provider, initialize_result);
+    let peer = peer.with_transport_info(transport_info);
     serve_inner(service, (sink, stream), peer, peer_rx, ct).await
 }
 
@@ -192,7 +278,8 @@ macro_rules! method {
             }
         }
     };
-    (peer_req $method:ident $Req:ident($Param: ident) => $Resp: ident ) => {
+    ($(#[$attr:meta])* peer_req $method:ident $Req:ident($Param: ident) => $Resp: ident ) => {
+        $(#[$attr])*
         pub async fn $method(&self, params: $Param) -> Result<$Resp, ServiceError> {
             let result = self
                 .send_request(ClientRequest::$Req($Req {
@@ -271,71 +358,114 @@ impl Peer<RoleClient> {
     method!(peer_req read_resource ReadResourceRequest(ReadResourceRequestParam) => ReadResourceResult);
     method!(peer_req subscribe SubscribeRequest(SubscribeRequestParam) );
     method!(peer_req unsubscribe UnsubscribeRequest(UnsubscribeRequestParam));
-    method!(peer_req call_tool CallToolRequest(CallToolRequestParam) => CallToolResult);
+    method!(
+        /// Call a tool of the server.
+        ///
+        /// A tool which ran and failed is still `Ok`, check [`CallToolResult::is_tool_error`].
+        /// `Err` means the call itself failed: the server answered with an error, like an unknown
+        /// method or tool, or the request couldn't be sent or answered.
+        peer_req call_tool CallToolRequest(CallToolRequestParam) => CallToolResult
+    );
     method!(peer_req list_tools ListToolsRequest(PaginatedRequestParam)? => ListToolsResult);
 
     method!(peer_not notify_cancelled CancelledNotification(CancelledNotificationParam));
-    method!(peer_not notify_progress ProgressNotification(ProgressNotificationParam));
     method!(peer_not notify_initialized InitializedNotification);
     method!(peer_not notify_roots_list_changed RootsListChangedNotification);
 }
 
 impl Peer<RoleClient> {
-    /// A wrapper method for [`Peer<RoleClient>::list_tools`].
+    /// The protocol version negotiated with the server, it may be older than the requested one.
+    pub fn protocol_version(&self) -> &ProtocolVersion {
+        &self.peer_info().protocol_version
+    }
+
+    /// Request the pages with `request` until `page` returns no cursor.
     ///
-    /// This function will call [`Peer<RoleClient>::list_tools`] multiple times until all tools are listed.
-    pub async fn list_all_tools(&self) -> Result<Vec<crate::model::Tool>, ServiceError> {
-        let mut tools = Vec::new();
+    /// If the future is dropped, the request of the current page is cancelled rather than
+    /// left running on the server.
+    async fn list_all<T>(
+        &self,
+        request: fn(Option<PaginatedRequestParam>) -> ClientRequest,
+        page: fn(ServerResult) -> Option<(Vec<T>, Option<String>)>,
+    ) -> Result<Vec<T>, ServiceError> {
+        let mut items = Vec::new();
         let mut cursor = None;
         loop {
             let result = self
-                .list_tools(Some(PaginatedRequestParam { cursor }))
+                .send_request_cancel_on_drop(request(Some(PaginatedRequestParam { cursor })))
                 .await?;
-            tools.extend(result.tools);
-            cursor = result.next_cursor;
+            let (page_items, next_cursor) = page(result).ok_or(ServiceError::UnexpectedResponse)?;
+            items.extend(page_items);
+            cursor = next_cursor;
             if cursor.is_none() {
                 break;
             }
         }
-        Ok(tools)
+        Ok(items)
     }
 
-    /// A wrapper method for [`Peer<RoleClient>::list_resources`].
+    /// A wrapper method for [`Peer<RoleClient>::list_tools`].
     ///
-    /// This function will call [`Peer<RoleClient>::list_resources`] multiple times until all resources are listed.
+    /// This function will call [`Peer<RoleClient>::list_tools`] multiple times until all tools are listed.
+    pub async fn list_all_tools(&self) -> Result<Vec<crate::model::Tool>, ServiceError> {
+        self.list_all(
+            |params| {
+                ClientRequest::ListToolsRequest(ListToolsRequest {
+                    method: Default::default(),
+                    params,
+                    extensions: Default::default(),
+                })
+            },
+            |result| match result {
+                ServerResult::ListToolsResult(result) => Some((result.tools, result.next_cursor)),
+                _ => None,
+            },
+        )
+        .await
+    }
+
+    /// A wrapper method for [`Peer<RoleClient>::list_prompts`].
+    ///
+    /// This function will call [`Peer<RoleClient>::list_prompts`] multiple times until all prompts are listed.
     pub async fn list_all_prompts(&self) -> Result<Vec<crate::model::Prompt>, ServiceError> {
-        let mut prompts = Vec::new();
-        let mut cursor = None;
-        loop {
-            let result = self
-                .list_prompts(Some(PaginatedRequestParam { cursor }))
-                .await?;
-            prompts.extend(result.prompts);
-            cursor = result.next_cursor;
-            if cursor.is_none() {
-                break;
-            }
-        }
-        Ok(prompts)
+        self.list_all(
+            |params| {
+                ClientRequest::ListPromptsRequest(ListPromptsRequest {
+                    method: Default::default(),
+                    params,
+                    extensions: Default::default(),
+                })
+            },
+            |result| match result {
+                ServerResult::ListPromptsResult(result) => {
+                    Some((result.prompts, result.next_cursor))
+                }
+                _ => None,
+            },
+        )
+        .await
     }
 
     /// A wrapper method for [`Peer<RoleClient>::list_resources`].
     ///
     /// This function will call [`Peer<RoleClient>::list_resources`] multiple times until all resources are listed.
     pub async fn list_all_resources(&self) -> Result<Vec<crate::model::Resource>, ServiceError> {
-        let mut resources = Vec::new();
-        let mut cursor = None;
-        loop {
-            let result = self
-                .list_resources(Some(PaginatedRequestParam { cursor }))
-                .await?;
-            resources.extend(result.resources);
-            cursor = result.next_cursor;
-            if cursor.is_none() {
-                break;
-            }
-        }
-        Ok(resources)
+        self.list_all(
+            |params| {
+                ClientRequest::ListResourcesRequest(ListResourcesRequest {
+                    method: Default::default(),
+                    params,
+                    extensions: Default::default(),
+                })
+            },
+            |result| match result {
+                ServerResult::ListResourcesResult(result) => {
+                    Some((result.resources, result.next_cursor))
+                }
+                _ => None,
+            },
+        )
+        .await
     }
 
     /// A wrapper method for [`Peer<RoleClient>::list_resource_templates`].
@@ -344,18 +474,274 @@ impl Peer<RoleClient> {
     pub async fn list_all_resource_templates(
         &self,
     ) -> Result<Vec<crate::model::ResourceTemplate>, ServiceError> {
-        let mut resource_templates = Vec::new();
-        let mut cursor = None;
-        loop {
+        self.list_all(
+            |params| {
+                ClientRequest::ListResourceTemplatesRequest(ListResourceTemplatesRequest {
+                    method: Default::default(),
+                    params,
+                    extensions: Default::default(),
+                })
+            },
+            |result| match result {
+                ServerResult::ListResourceTemplatesResult(result) => {
+                    Some((result.resource_templates, result.next_cursor))
+                }
+                _ => None,
+            },
+        )
+        .await
+    }
+
+    /// A wrapper method for [`Peer<RoleClient>::read_resource`] asking for a byte range only.
+    ///
+    /// Servers may not support ranges, check [`ReadResourceResult::range`] to tell whether the
+    /// contents were cut down.
+    pub async fn read_resource_range(
+        &self,
+        params: ReadResourceRequestParam,
+        range: ResourceRange,
+    ) -> Result<ReadResourceResult, ServiceError> {
+        let mut meta = Meta::new();
+        meta.set_range(range);
+        self.read_resource_with_meta(params, meta).await
+    }
+
+    /// A wrapper method for [`Peer<RoleClient>::read_resource`] preferring the contents as
+    /// `text` or as `blob`.
+    ///
+    /// It's a hint, servers may not support it or keep contents which can't be represented
+    /// that way, so match on both kinds of [`ResourceContents`](crate::model::ResourceContents).
+    pub async fn read_resource_as(
+        &self,
+        params: ReadResourceRequestParam,
+        encoding: ResourceEncoding,
+    ) -> Result<ReadResourceResult, ServiceError> {
+        let mut meta = Meta::new();
+        meta.set_encoding(encoding);
+        self.read_resource_with_meta(params, meta).await
+    }
+
+    /// Read several resources in one request, the failure of one is reported in its entry
+    /// rather than failing the whole call.
+    ///
+    /// The entries come in the order of `uris`. When the server doesn't read batches, it only
+    /// reads the first uri and the others are read one request each.
+    pub async fn read_resources(
+        &self,
+        uris: Vec<String>,
+    ) -> Result<Vec<ReadResourceEntry>, ServiceError> {
+        let Some(first) = uris.first().cloned() else {
+            return Ok(Vec::new());
+        };
+        let mut meta = Meta::new();
+        meta.set_uris(uris.clone());
+        let result = self
+            .read_resource_with_meta(ReadResourceRequestParam { uri: first.clone() }, meta)
+            .await;
+        let first_result = match result {
+            Ok(result) => match result.entries() {
+                Some(entries) => return Ok(entries),
+                None => Ok(result),
+            },
+            Err(ServiceError::McpError(error)) => Err(error),
+            Err(error) => return Err(error),
+        };
+        let mut entries = vec![ReadResourceEntry::new(first, first_result)];
+        for uri in uris.into_iter().skip(1) {
+            let result = match self
+                .read_resource(ReadResourceRequestParam { uri: uri.clone() })
+                .await
+            {
+                Ok(result) => Ok(result),
+                Err(ServiceError::McpError(error)) => Err(error),
+                Err(error) => return Err(error),
+            };
+            entries.push(ReadResourceEntry::new(uri, result));
+        }
+        Ok(entries)
+    }
+
+    async fn read_resource_with_meta(
+        &self,
+        params: ReadResourceRequestParam,
+        meta: Meta,
+    ) -> Result<ReadResourceResult, ServiceError> {
+        let result = self
+            .send_request_with_option(
+                ClientRequest::ReadResourceRequest(ReadResourceRequest {
+                    method: Default::default(),
+                    params,
+                    extensions: Default::default(),
+                }),
+                PeerRequestOptions {
+                    meta: Some(meta),
+                    ..Default::default()
+                },
+            )
+            .await?
+            .await_response()
+            .await?;
+        match result {
+            ServerResult::ReadResourceResult(result) => Ok(result),
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+
+    /// Replace the resource links in the messages of a prompt with the embedded contents of
+    /// the resources, read with [`Peer<RoleClient>::read_resource`].
+    ///
+    /// A resource with several contents is expanded into one message per content, in order.
+    pub async fn resolve_embedded_resources(
+        &self,
+        prompt: GetPromptResult,
+    ) -> Result<GetPromptResult, ServiceError> {
+        let mut messages = Vec::with_capacity(prompt.messages.len());
+        for message in prompt.messages {
+            let PromptMessageContent::ResourceLink { link } = message.content else {
+                messages.push(message);
+                continue;
+            };
             let result = self
-                .list_resource_templates(Some(PaginatedRequestParam { cursor }))
+                .read_resource(ReadResourceRequestParam {
+                    uri: link.raw.uri.clone(),
+                })
                 .await?;
-            resource_templates.extend(result.resource_templates);
-            cursor = result.next_cursor;
-            if cursor.is_none() {
-                break;
-            }
+            messages.extend(result.contents.into_iter().map(|contents| {
+                PromptMessage {
+                    role: message.role.clone(),
+                    content: PromptMessageContent::Resource {
+                        resource: RawEmbeddedResource { resource: contents }
+                            .optional_annotate(link.annotations.clone()),
+                    },
+                }
+            }));
         }
-        Ok(resource_templates)
+        Ok(GetPromptResult {
+            description: prompt.description,
+            messages,
+        })
+    }
+}
+
+/// The tools of a server, fetched once, to check the arguments of a call before sending it.
+///
+/// ```rust,ignore
+/// let client = ().serve(transport).await?;
+/// let tools = ClientToolCache::fetch(&client).await?;
+/// // fails locally if the arguments don't match the input schema
+/// let result = tools.call_tool(&client, param).await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClientToolCache {
+    tools: HashMap<Cow<'static, str>, Tool>,
+}
+
+impl ClientToolCache {
+    pub fn new(tools: impl IntoIterator<Item = Tool>) -> Self {
+        Self {
+            tools: tools
+                .into_iter()
+                .map(|tool| (tool.name.clone(), tool))
+                .collect(),
+        }
+    }
+
+    /// List all the tools of the server.
+    pub async fn fetch(peer: &Peer<RoleClient>) -> Result<Self, ServiceError> {
+        Ok(Self::new(peer.list_all_tools().await?))
+    }
+
+    pub fn get(&self, tool_name: &str) -> Option<&Tool> {
+        self.tools.get(tool_name)
+    }
+
+    pub fn tools(&self) -> impl Iterator<Item = &Tool> {
+        self.tools.values()
+    }
+
+    /// Validate the arguments of a call against the cached input schema of the tool.
+    #[cfg(feature = "schema-validation")]
+    pub fn validate_args(
+        &self,
+        tool_name: &str,
+        arguments: &crate::model::JsonObject,
+    ) -> Result<(), crate::Error> {
+        let tool = self.get(tool_name).ok_or_else(|| {
+            crate::Error::invalid_params(format!("unknown tool {tool_name}"), None)
+        })?;
+        tool.validate_arguments(Some(arguments))
+    }
+
+    /// Validate the arguments, and call the tool only if they are valid.
+    #[cfg(feature = "schema-validation")]
+    pub async fn call_tool(
+        &self,
+        peer: &Peer<RoleClient>,
+        params: CallToolRequestParam,
+    ) -> Result<CallToolResult, ServiceError> {
+        // positional arguments are only named, and checked, by the server
+        if params.positional_arguments().is_none() {
+            let arguments = params.named_arguments().cloned().unwrap_or_default();
+            self.validate_args(&params.name, &arguments)
+                .map_err(ServiceError::McpError)?;
+        }
+        peer.call_tool(params).await
+    }
+}
+
+/// The URIs of the resources of a server, kept up to date with the
+/// `notifications/resources/list_delta` it sends instead of listing them again.
+///
+/// ```rust,ignore
+/// let resources = Arc::new(Mutex::new(LiveResourceSet::fetch(&client).await?));
+/// // in ClientHandler::on_resource_list_delta
+/// resources.lock().unwrap().apply(&params);
+/// ```
+#[cfg(feature = "resource-delta")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LiveResourceSet {
+    uris: std::collections::BTreeSet<String>,
+}
+
+#[cfg(feature = "resource-delta")]
+impl LiveResourceSet {
+    pub fn new(uris: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            uris: uris.into_iter().collect(),
+        }
+    }
+
+    /// List all the resources of the server.
+    pub async fn fetch(peer: &Peer<RoleClient>) -> Result<Self, ServiceError> {
+        let resources = peer.list_all_resources().await?;
+        Ok(Self::new(
+            resources.into_iter().map(|resource| resource.raw.uri),
+        ))
+    }
+
+    /// Apply a delta, its additions then its removals, so a resource both added and removed
+    /// by the same delta is gone.
+    pub fn apply(&mut self, delta: &crate::model::ResourceListDeltaNotificationParam) {
+        self.uris.extend(delta.added.iter().cloned());
+        for uri in &delta.removed {
+            self.uris.remove(uri);
+        }
+    }
+
+    pub fn contains(&self, uri: &str) -> bool {
+        self.uris.contains(uri)
+    }
+
+    /// The URIs, in order
+    pub fn uris(&self) -> impl Iterator<Item = &str> {
+        self.uris.iter().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.uris.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.uris.is_empty()
     }
 }
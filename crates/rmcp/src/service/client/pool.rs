@@ -0,0 +1,209 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    ops::Deref,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use futures::{FutureExt, future::BoxFuture};
+use thiserror::Error;
+
+use crate::service::{Peer, RoleClient, RunningService, Service};
+
+#[derive(Debug, Error)]
+pub enum ClientPoolError<E: std::error::Error + 'static> {
+    /// Every connection is borrowed, none can be opened or evicted
+    #[error("the pool is exhausted, at most {max} connections can be open")]
+    Exhausted { max: usize },
+    #[error("fail to connect: {0}")]
+    Connect(#[source] E),
+}
+
+type Connector<K, S, E> =
+    Arc<dyn Fn(&K) -> BoxFuture<'static, Result<RunningService<RoleClient, S>, E>> + Send + Sync>;
+
+/// A bounded pool of initialized client connections, keyed by endpoint.
+///
+/// [`ClientPool::get`] hands out an idle connection of the endpoint, or opens one with the
+/// connector. At most `max_connections` are open, idle or borrowed: when it's reached, an idle
+/// connection of another endpoint is closed to make room. A connection returns to the pool
+/// when its [`PooledClient`] is dropped, and one whose transport closed is evicted rather than
+/// handed out again.
+///
+/// ```rust,ignore
+/// let pool = ClientPool::new(8, |server: &String| {
+///     let transport = TokioChildProcess::new(&mut Command::new(server));
+///     async move { ().serve(transport?).await }
+/// });
+/// let client = pool.get("everything-server".to_string()).await?;
+/// let tools = client.list_all_tools().await?;
+/// ```
+pub struct ClientPool<K, S: Service<RoleClient> = (), E = std::io::Error> {
+    connect: Connector<K, S, E>,
+    max_connections: usize,
+    state: Arc<Mutex<PoolState<K, S>>>,
+}
+
+struct PoolState<K, S: Service<RoleClient>> {
+    idle: HashMap<K, Vec<RunningService<RoleClient, S>>>,
+    // the idle and the borrowed ones
+    open: usize,
+}
+
+impl<K, S: Service<RoleClient>> PoolState<K, S> {
+    /// Drop the idle connections whose service stopped, returning how many were
+    fn evict_closed(&mut self) -> usize {
+        let mut evicted = 0;
+        self.idle.retain(|_, services| {
+            services.retain(|service| {
+                let closed = service.is_closed();
+                evicted += usize::from(closed);
+                !closed
+            });
+            !services.is_empty()
+        });
+        self.open -= evicted;
+        evicted
+    }
+}
+
+fn lock<K, S: Service<RoleClient>>(
+    state: &Mutex<PoolState<K, S>>,
+) -> MutexGuard<'_, PoolState<K, S>> {
+    state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl<K, S: Service<RoleClient>, E> Clone for ClientPool<K, S, E> {
+    fn clone(&self) -> Self {
+        Self {
+            connect: self.connect.clone(),
+            max_connections: self.max_connections,
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<K, S, E> ClientPool<K, S, E>
+where
+    K: Eq + Hash,
+    S: Service<RoleClient>,
+    E: std::error::Error + 'static,
+{
+    pub fn new<F, Fut>(max_connections: usize, connect: F) -> Self
+    where
+        F: Fn(&K) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<RunningService<RoleClient, S>, E>> + Send + 'static,
+    {
+        Self {
+            connect: Arc::new(move |key| connect(key).boxed()),
+            max_connections,
+            state: Arc::new(Mutex::new(PoolState {
+                idle: HashMap::new(),
+                open: 0,
+            })),
+        }
+    }
+
+    /// Borrow a connection to `key`, reusing an idle one if there's any
+    pub async fn get(&self, key: K) -> Result<PooledClient<K, S>, ClientPoolError<E>> {
+        let evicted = {
+            let mut state = lock(&self.state);
+            state.evict_closed();
+            if let Some(service) = state.idle.get_mut(&key).and_then(Vec::pop) {
+                return Ok(self.pooled(key, service));
+            }
+            let mut evicted = None;
+            if state.open >= self.max_connections {
+                // make room by closing an idle connection of another endpoint
+                evicted = state.idle.values_mut().find_map(Vec::pop);
+                if evicted.is_none() {
+                    return Err(ClientPoolError::Exhausted {
+                        max: self.max_connections,
+                    });
+                }
+                state.idle.retain(|_, services| !services.is_empty());
+            } else {
+                state.open += 1;
+            }
+            evicted
+        };
+        if let Some(service) = evicted {
+            // the slot is handed over to the new connection
+            drop(service);
+        }
+        match (self.connect)(&key).await {
+            Ok(service) => Ok(self.pooled(key, service)),
+            Err(error) => {
+                lock(&self.state).open -= 1;
+                Err(ClientPoolError::Connect(error))
+            }
+        }
+    }
+
+    fn pooled(&self, key: K, service: RunningService<RoleClient, S>) -> PooledClient<K, S> {
+        PooledClient {
+            connection: Some((key, service)),
+            state: self.state.clone(),
+        }
+    }
+
+    /// Close the idle connections whose transport closed, returning how many were
+    pub fn evict_closed(&self) -> usize {
+        lock(&self.state).evict_closed()
+    }
+
+    /// The connections waiting in the pool
+    pub fn idle_connections(&self) -> usize {
+        lock(&self.state).idle.values().map(Vec::len).sum()
+    }
+
+    /// The connections opened by the pool and not closed yet, idle or borrowed
+    pub fn open_connections(&self) -> usize {
+        lock(&self.state).open
+    }
+}
+
+/// A connection borrowed from a [`ClientPool`], it returns to the pool when dropped
+pub struct PooledClient<K: Eq + Hash, S: Service<RoleClient>> {
+    // only taken on drop
+    connection: Option<(K, RunningService<RoleClient, S>)>,
+    state: Arc<Mutex<PoolState<K, S>>>,
+}
+
+impl<K: Eq + Hash, S: Service<RoleClient>> PooledClient<K, S> {
+    fn connection(&self) -> &(K, RunningService<RoleClient, S>) {
+        self.connection.as_ref().expect("only taken on drop")
+    }
+
+    pub fn key(&self) -> &K {
+        &self.connection().0
+    }
+
+    pub fn service(&self) -> &RunningService<RoleClient, S> {
+        &self.connection().1
+    }
+}
+
+impl<K: Eq + Hash, S: Service<RoleClient>> Deref for PooledClient<K, S> {
+    type Target = Peer<RoleClient>;
+
+    fn deref(&self) -> &Self::Target {
+        self.service().peer()
+    }
+}
+
+impl<K: Eq + Hash, S: Service<RoleClient>> Drop for PooledClient<K, S> {
+    fn drop(&mut self) {
+        let Some((key, service)) = self.connection.take() else {
+            return;
+        };
+        let mut state = lock(&self.state);
+        if service.is_closed() {
+            state.open -= 1;
+        } else {
+            state.idle.entry(key).or_default().push(service);
+        }
+    }
+}
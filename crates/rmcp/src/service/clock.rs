@@ -0,0 +1,27 @@
+use std::time::{Duration, Instant};
+
+use futures::{FutureExt, future::BoxFuture};
+
+/// The time source of the timeouts and retry delays.
+///
+/// [`TokioClock`] is used by default, a test can use
+/// [`ManualClock`](crate::testing::ManualClock) to run them without waiting.
+pub trait Clock: Send + Sync + 'static {
+    fn now(&self) -> Instant;
+    /// Complete once `duration` elapsed on this clock
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// The clock of the tokio runtime, which follows [`tokio::time::pause`] too
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioClock;
+
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        tokio::time::sleep(duration).boxed()
+    }
+}
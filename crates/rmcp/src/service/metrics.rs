@@ -0,0 +1,71 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+/// The requests of the peer a running service is handling, see
+/// [`RunningService::request_metrics`](super::RunningService::request_metrics).
+///
+/// The counters are updated by the service loop and shared by the clones, an autoscaler can
+/// poll them to see how backed up the service is.
+#[derive(Debug, Clone, Default)]
+pub struct RequestMetrics {
+    counters: Arc<Counters>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    queued: AtomicUsize,
+    in_flight: AtomicUsize,
+}
+
+impl RequestMetrics {
+    /// The requests received but waiting for a slot of
+    /// [`Service::max_concurrent_requests`](super::Service::max_concurrent_requests)
+    pub fn queued(&self) -> usize {
+        self.counters.queued.load(Ordering::Relaxed)
+    }
+
+    /// The requests whose handler is running
+    pub fn in_flight(&self) -> usize {
+        self.counters.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Count a request as queued until the returned guard is started or dropped
+    pub(crate) fn enqueue(&self) -> QueuedRequest {
+        self.counters.queued.fetch_add(1, Ordering::Relaxed);
+        QueuedRequest {
+            counters: self.counters.clone(),
+        }
+    }
+}
+
+pub(crate) struct QueuedRequest {
+    counters: Arc<Counters>,
+}
+
+impl QueuedRequest {
+    /// Move the request from the queue to the requests in flight
+    pub(crate) fn start(self) -> InFlightRequest {
+        self.counters.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlightRequest {
+            counters: self.counters.clone(),
+        }
+    }
+}
+
+impl Drop for QueuedRequest {
+    fn drop(&mut self) {
+        self.counters.queued.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+pub(crate) struct InFlightRequest {
+    counters: Arc<Counters>,
+}
+
+impl Drop for InFlightRequest {
+    fn drop(&mut self) {
+        self.counters.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
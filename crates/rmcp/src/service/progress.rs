@@ -0,0 +1,153 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tokio::sync::mpsc::error::TrySendError;
+
+use super::{Peer, PeerSinkMessage, ServiceError, ServiceRole};
+use crate::model::{ProgressNotificationParam, ProgressToken};
+
+/// The updates are coalesced once less than `1 / NEAR_FULL_DIVISOR` of the outbound buffer is
+/// free
+const NEAR_FULL_DIVISOR: usize = 4;
+
+/// Report the progress of a request without outpacing the peer.
+///
+/// An update is queued right away while the outbound buffer of the connection has room. Once
+/// it's near full, because the transport writes slower than the handler reports, the updates
+/// are coalesced: only the latest one is kept and sent in the background as soon as there's
+/// room again, so reporting never blocks the handler. The terminal update, the one reaching
+/// `total`, always waits to be delivered after everything reported before it.
+#[derive(Clone)]
+pub struct ProgressReporter<R: ServiceRole> {
+    peer: Peer<R>,
+    progress_token: ProgressToken,
+    inner: Arc<ReporterInner>,
+}
+
+#[derive(Default)]
+struct ReporterInner {
+    state: Mutex<ReporterState>,
+    // held while a coalesced or terminal update is sent, to keep them in order
+    send_lock: tokio::sync::Mutex<()>,
+}
+
+#[derive(Default)]
+struct ReporterState {
+    latest: Option<ProgressNotificationParam>,
+    flushing: bool,
+}
+
+impl<R: ServiceRole> std::fmt::Debug for ProgressReporter<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("progress_token", &self.progress_token)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: ServiceRole> ProgressReporter<R> {
+    pub fn new(peer: Peer<R>, progress_token: ProgressToken) -> Self {
+        Self {
+            peer,
+            progress_token,
+            inner: Default::default(),
+        }
+    }
+
+    pub fn progress_token(&self) -> &ProgressToken {
+        &self.progress_token
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, ReporterState> {
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn is_near_full(&self) -> bool {
+        self.peer.tx.capacity() < self.peer.tx.max_capacity() / NEAR_FULL_DIVISOR
+    }
+
+    pub async fn report(
+        &self,
+        progress: u32,
+        total: Option<u32>,
+        message: Option<String>,
+    ) -> Result<(), ServiceError> {
+        let params = self
+            .peer
+            .progress_validation
+            .check(ProgressNotificationParam {
+                progress_token: self.progress_token.clone(),
+                progress,
+                total,
+                message,
+            })?;
+        if total.is_some_and(|total| params.progress >= total) {
+            // supersedes whatever is still waiting
+            self.lock_state().latest = None;
+            let _send_lock = self.inner.send_lock.lock().await;
+            return self
+                .peer
+                .send_notification(R::new_progress_notification(params))
+                .await;
+        }
+        let mut state = self.lock_state();
+        let params = if state.flushing || self.is_near_full() {
+            params
+        } else {
+            match self.try_queue(params) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Full(params)) => params,
+                Err(TrySendError::Closed(_)) => return Err(ServiceError::ConnectionClosed),
+            }
+        };
+        state.latest = Some(params);
+        if !state.flushing {
+            state.flushing = true;
+            tokio::spawn(self.clone().flush());
+        }
+        Ok(())
+    }
+
+    /// Queue an update without waiting for it to be written
+    fn try_queue(
+        &self,
+        params: ProgressNotificationParam,
+    ) -> Result<(), TrySendError<ProgressNotificationParam>> {
+        let permit = self.peer.tx.try_reserve().map_err(|error| match error {
+            TrySendError::Full(()) => TrySendError::Full(params.clone()),
+            TrySendError::Closed(()) => TrySendError::Closed(params.clone()),
+        })?;
+        let (responder, _receiver) = tokio::sync::oneshot::channel();
+        permit.send(PeerSinkMessage::Notification {
+            notification: R::new_progress_notification(params),
+            responder,
+        });
+        Ok(())
+    }
+
+    /// Send the latest update, one at a time, until no newer one was reported meanwhile
+    async fn flush(self) {
+        loop {
+            let _send_lock = self.inner.send_lock.lock().await;
+            let params = {
+                let mut state = self.lock_state();
+                match state.latest.take() {
+                    Some(params) => params,
+                    None => {
+                        state.flushing = false;
+                        return;
+                    }
+                }
+            };
+            if let Err(error) = self
+                .peer
+                .send_notification(R::new_progress_notification(params))
+                .await
+            {
+                tracing::debug!(%error, "fail to send progress");
+            }
+        }
+    }
+}
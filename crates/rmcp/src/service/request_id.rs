@@ -0,0 +1,112 @@
+use std::{
+    fmt,
+    str::FromStr,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use thiserror::Error;
+
+use super::RequestIdProvider;
+use crate::model::{NumberOrString, RequestId};
+
+/// An id type of its own for the requests, for a [`RequestIdProvider`] which puts more than a
+/// counter in its ids.
+///
+/// It goes on the wire as the [`RequestId`] it converts to, so the other side only ever sees a
+/// plain number or string, and [`StructuredRequestId::from_request_id`] gets it back from the
+/// id of a response or a cancellation.
+pub trait StructuredRequestId: Into<NumberOrString> + Sized {
+    /// `None` if `id` wasn't generated as this type
+    fn from_request_id(id: &RequestId) -> Option<Self>;
+}
+
+/// A request id made of the shard which sent the request and a sequence number within the
+/// shard, sent as the string `"{shard}:{sequence}"`.
+///
+/// ```rust
+/// # use rmcp::{model::RequestId, service::*};
+/// let provider = ShardedIdProvider::new(3);
+/// let id = provider.next_request_id();
+/// assert_eq!(id, RequestId::String("3:0".into()));
+/// assert_eq!(
+///     ShardedRequestId::from_request_id(&id),
+///     Some(ShardedRequestId { shard: 3, sequence: 0 })
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShardedRequestId {
+    pub shard: u32,
+    pub sequence: u32,
+}
+
+impl fmt::Display for ShardedRequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.shard, self.sequence)
+    }
+}
+
+impl FromStr for ShardedRequestId {
+    type Err = ParseShardedRequestIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (shard, sequence) = s.split_once(':').ok_or(ParseShardedRequestIdError)?;
+        Ok(Self {
+            shard: shard.parse().map_err(|_| ParseShardedRequestIdError)?,
+            sequence: sequence.parse().map_err(|_| ParseShardedRequestIdError)?,
+        })
+    }
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+#[error("not a `shard:sequence` request id")]
+pub struct ParseShardedRequestIdError;
+
+impl From<ShardedRequestId> for NumberOrString {
+    fn from(id: ShardedRequestId) -> Self {
+        NumberOrString::String(id.to_string().into())
+    }
+}
+
+impl StructuredRequestId for ShardedRequestId {
+    fn from_request_id(id: &RequestId) -> Option<Self> {
+        match id {
+            NumberOrString::String(id) => id.parse().ok(),
+            NumberOrString::Number(_) => None,
+        }
+    }
+}
+
+/// Generate the [`ShardedRequestId`]s of one shard, to tell apart the requests of several
+/// processes sharing a connection, or route a response back to the shard which sent the
+/// request.
+#[derive(Debug)]
+pub struct ShardedIdProvider {
+    shard: u32,
+    sequence: AtomicU32,
+}
+
+impl ShardedIdProvider {
+    pub fn new(shard: u32) -> Self {
+        Self {
+            shard,
+            sequence: AtomicU32::new(0),
+        }
+    }
+
+    pub fn shard(&self) -> u32 {
+        self.shard
+    }
+
+    pub fn next_id(&self) -> ShardedRequestId {
+        ShardedRequestId {
+            shard: self.shard,
+            sequence: self.sequence.fetch_add(1, Ordering::SeqCst),
+        }
+    }
+}
+
+impl RequestIdProvider for ShardedIdProvider {
+    fn next_request_id(&self) -> RequestId {
+        self.next_id().into()
+    }
+}
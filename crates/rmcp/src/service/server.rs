@@ -5,16 +5,19 @@ use super::*;
 use crate::model::{
     CancelledNotification, CancelledNotificationParam, ClientInfo, ClientJsonRpcMessage,
     ClientNotification, ClientRequest, ClientResult, CreateMessageRequest,
-    CreateMessageRequestParam, CreateMessageResult, ErrorData, ListRootsRequest, ListRootsResult,
-    LoggingMessageNotification, LoggingMessageNotificationParam, ProgressNotification,
-    ProgressNotificationParam, PromptListChangedNotification, ResourceListChangedNotification,
-    ResourceUpdatedNotification, ResourceUpdatedNotificationParam, ServerInfo, ServerNotification,
-    ServerRequest, ServerResult, ToolListChangedNotification,
+    CreateMessageRequestParam, CreateMessageResult, EmptyResult, ErrorData, ListRootsRequest,
+    ListRootsResult, LoggingMessageNotification, LoggingMessageNotificationParam, PingRequest,
+    ProgressNotification, ProgressNotificationParam, PromptListChangedNotification,
+    ResourceListChangedNotification, ResourceUpdatedNotification, ResourceUpdatedNotificationParam,
+    SamplingResponseError, ServerInfo, ServerNotification, ServerRequest, ServerResult,
+    ToolListChangedNotification,
 };
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct RoleServer;
 
+impl super::sealed::Sealed for RoleServer {}
+
 impl ServiceRole for RoleServer {
     type Req = ServerRequest;
     type Resp = ServerResult;
@@ -25,19 +28,76 @@ impl ServiceRole for RoleServer {
     type Info = ServerInfo;
     type PeerInfo = ClientInfo;
     const IS_CLIENT: bool = false;
+
+    fn is_handshake_request(request: &ClientRequest) -> bool {
+        matches!(request, ClientRequest::InitializeRequest(_))
+    }
+
+    fn is_handshake_notification(notification: &ClientNotification) -> bool {
+        matches!(notification, ClientNotification::InitializedNotification(_))
+    }
+
+    fn progress_notification(
+        notification: &ClientNotification,
+    ) -> Option<&ProgressNotificationParam> {
+        match notification {
+            ClientNotification::ProgressNotification(notification) => Some(&notification.params),
+            _ => None,
+        }
+    }
+
+    fn new_progress_notification(params: ProgressNotificationParam) -> ServerNotification {
+        ServerNotification::ProgressNotification(ProgressNotification {
+            method: Default::default(),
+            params,
+            extensions: Default::default(),
+        })
+    }
+
+    fn new_ping_request() -> ServerRequest {
+        ServerRequest::PingRequest(PingRequest {
+            method: Default::default(),
+            extensions: Default::default(),
+        })
+    }
+
+    fn into_ping_result(result: ClientResult) -> Option<EmptyResult> {
+        match result {
+            ClientResult::EmptyResult(result) => Some(result),
+            _ => None,
+        }
+    }
+
+    fn supported_methods(info: &ServerInfo) -> Vec<&'static str> {
+        info.capabilities.supported_methods()
+    }
+
+    #[cfg(feature = "shutdown-notification")]
+    fn shutdown_notification() -> ServerNotification {
+        ServerNotification::ShutdownNotification(crate::model::ShutdownNotification {
+            method: Default::default(),
+            extensions: Default::default(),
+        })
+    }
 }
 
 /// It represents the error that may occur when serving the server.
 ///
 /// if you want to handle the error, you can use `serve_server_with_ct` or `serve_server` with `Result<RunningService<RoleServer, S>, ServerError>`
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum ServerError {
+    #[deprecated(note = "reported as `ServerError::ProtocolViolation`")]
     #[error("expect initialized request, but received: {0:?}")]
     ExpectedInitRequest(Option<ClientJsonRpcMessage>),
 
+    #[deprecated(note = "reported as `ServerError::ProtocolViolation`")]
     #[error("expect initialized notification, but received: {0:?}")]
     ExpectedInitNotification(Option<ClientJsonRpcMessage>),
 
+    #[error("protocol violation: {0}")]
+    ProtocolViolation(#[from] ProtocolViolation),
+
     #[error("connection closed: {0}")]
     ConnectionClosed(String),
 
@@ -81,46 +141,44 @@ where
 }
 
 /// Helper function to get the next message from the stream
-async fn expect_next_message<S>(
+async fn expect_next_message<S, E>(
     stream: &mut S,
     context: &str,
 ) -> Result<ClientJsonRpcMessage, ServerError>
 where
-    S: StreamExt<Item = ClientJsonRpcMessage> + Unpin,
+    S: StreamExt<Item = Result<ClientJsonRpcMessage, E>> + Unpin,
+    E: std::error::Error + Send + Sync + 'static,
 {
     stream
         .next()
         .await
-        .ok_or_else(|| ServerError::ConnectionClosed(context.to_string()))
+        .ok_or_else(|| ServerError::ConnectionClosed(context.to_string()))?
+        .map_err(|e| ServerError::Io(transport_error_into_io(e)))
 }
 
 /// Helper function to expect a request from the stream
-async fn expect_request<S>(
+async fn expect_request<S, E>(
     stream: &mut S,
     context: &str,
 ) -> Result<(ClientRequest, RequestId), ServerError>
 where
-    S: StreamExt<Item = ClientJsonRpcMessage> + Unpin,
+    S: StreamExt<Item = Result<ClientJsonRpcMessage, E>> + Unpin,
+    E: std::error::Error + Send + Sync + 'static,
 {
-    let msg = expect_next_message(stream, context).await?;
-    let msg_clone = msg.clone();
-    msg.into_request()
-        .ok_or(ServerError::ExpectedInitRequest(Some(msg_clone)))
+    match expect_next_message(stream, context).await? {
+        JsonRpcMessage::Request(JsonRpcRequest { request, id, .. }) => Ok((request, id)),
+        message => Err(ProtocolViolation::unexpected_message(
+            "the initialize request",
+            &message,
+            "initializing",
+        )
+        .into()),
+    }
 }
 
-/// Helper function to expect a notification from the stream
-async fn expect_notification<S>(
-    stream: &mut S,
-    context: &str,
-) -> Result<ClientNotification, ServerError>
-where
-    S: StreamExt<Item = ClientJsonRpcMessage> + Unpin,
-{
-    let msg = expect_next_message(stream, context).await?;
-    let msg_clone = msg.clone();
-    msg.into_notification()
-        .ok_or(ServerError::ExpectedInitNotification(Some(msg_clone)))
-}
+/// The messages a client can pipeline between the initialize response and the initialized
+/// notification, the next requests are rejected and the next notifications dropped
+const MAX_PIPELINED_BEFORE_INITIALIZED: usize = 64;
 
 pub async fn serve_server_with_ct<S, T, E, A>(
     service: S,
@@ -132,6 +190,7 @@ where
     T: IntoTransport<RoleServer, E, A>,
     E: std::error::Error + From<std::io::Error> + Send + Sync + 'static,
 {
+    let transport_info = transport.transport_info();
     let (sink, stream) = transport.into_transport();
     let mut sink = Box::pin(sink);
     let mut stream = Box::pin(stream);
@@ -141,21 +200,46 @@ where
     let handle_server_error = |e: ServerError| -> E {
         match e {
             ServerError::Io(io_err) => io_err.into(),
+            ServerError::ProtocolViolation(violation) => std::io::Error::from(violation).into(),
             other => std::io::Error::new(std::io::ErrorKind::Other, format!("{}", other)).into(),
         }
     };
 
-    // Get initialize request
-    let (request, id) = expect_request(&mut stream, "initialized request")
-        .await
-        .map_err(handle_server_error)?;
+    // Get initialize request, only pings can come before it
+    let (request, id) = loop {
+        let (request, id) = expect_request(&mut stream, "initialized request")
+            .await
+            .map_err(handle_server_error)?;
+        match request {
+            ClientRequest::InitializeRequest(_) => break (request, id),
+            ClientRequest::PingRequest(_) => {
+                let result = ServerResult::EmptyResult(EmptyResult::ping_echo(request.get_meta()));
+                sink.send(ServerJsonRpcMessage::response(result, id))
+                    .await?;
+            }
+            _ => {
+                tracing::warn!(%id, ?request, "reject request before initialize");
+                sink.send(ServerJsonRpcMessage::error(
+                    ErrorData::not_initialized(),
+                    id,
+                ))
+                .await?;
+            }
+        }
+    };
 
     let ClientRequest::InitializeRequest(peer_info) = &request else {
-        return Err(handle_server_error(ServerError::ExpectedInitRequest(Some(
-            ClientJsonRpcMessage::request(request, id),
-        ))));
+        return Err(handle_server_error(
+            ProtocolViolation::unexpected_message(
+                "the initialize request",
+                &ClientJsonRpcMessage::request(request, id),
+                "initializing",
+            )
+            .into(),
+        ));
     };
     let (peer, peer_rx) = Peer::new(id_provider, peer_info.params.clone());
+    let peer = peer.with_transport_info(transport_info);
     let context = RequestContext {
         ct: ct.child_token(),
         id: id.clone(),
@@ -196,17 +280,52 @@ where
     ))
     .await?;
 
-    // Wait for initialize notification
-    let notification = expect_notification(&mut stream, "initialize notification")
-        .await
-        .map_err(handle_server_error)?;
-    let ClientNotification::InitializedNotification(_) = notification else {
-        return Err(handle_server_error(ServerError::ExpectedInitNotification(
-            Some(ClientJsonRpcMessage::notification(notification)),
-        )));
+    // Wait for initialize notification, a client may pipeline requests before it
+    let mut pipelined = Vec::new();
+    let notification = loop {
+        match expect_next_message(&mut stream, "initialize notification")
+            .await
+            .map_err(handle_server_error)?
+        {
+            JsonRpcMessage::Notification(JsonRpcNotification {
+                notification: notification @ ClientNotification::InitializedNotification(_),
+                ..
+            }) => break notification,
+            message @ (JsonRpcMessage::Request(_) | JsonRpcMessage::Notification(_))
+                if pipelined.len() < MAX_PIPELINED_BEFORE_INITIALIZED =>
+            {
+                pipelined.push(message);
+            }
+            JsonRpcMessage::Request(JsonRpcRequest { id, .. }) => {
+                tracing::warn!(%id, "reject request pipelined before initialized");
+                sink.send(ServerJsonRpcMessage::error(
+                    ErrorData::not_initialized(),
+                    id,
+                ))
+                .await?;
+            }
+            JsonRpcMessage::Notification(JsonRpcNotification { notification, .. }) => {
+                tracing::warn!(
+                    ?notification,
+                    "drop notification pipelined before initialized"
+                );
+            }
+            message => {
+                return Err(handle_server_error(
+                    ProtocolViolation::unexpected_message(
+                        "the initialized notification",
+                        &message,
+                        "initializing",
+                    )
+                    .into(),
+                ));
+            }
+        }
     };
+    service.on_peer_initialized(peer.peer_info());
     let _ = service.handle_notification(notification).await;
-    // Continue processing service
+    // Continue processing service, starting with what was pipelined, in order
+    let stream = futures::stream::iter(pipelined.into_iter().map(Ok)).chain(stream);
     serve_inner(service, (sink, stream), peer, peer_rx, ct).await
 }
 
@@ -284,14 +403,79 @@ macro_rules! method {
 }
 
 impl Peer<RoleServer> {
-    method!(peer_req create_message CreateMessageRequest(CreateMessageRequestParam) => CreateMessageResult);
+    /// Ask the client to sample a message from its model.
+    ///
+    /// The result is checked to be an assistant message with text, image or audio content,
+    /// anything else fails with [`ServiceError::InvalidSamplingResponse`].
+    pub async fn create_message(
+        &self,
+        params: CreateMessageRequestParam,
+    ) -> Result<CreateMessageResult, ServiceError> {
+        let result = self
+            .send_request(ServerRequest::CreateMessageRequest(CreateMessageRequest {
+                method: Default::default(),
+                params,
+                extensions: Default::default(),
+            }))
+            .await?;
+        match result {
+            ClientResult::CreateMessageResult(result) => {
+                result.validate()?;
+                Ok(result)
+            }
+            // a malformed message is parsed as another result
+            result => Err(SamplingResponseError::NotAMessage(
+                serde_json::to_string(&result).unwrap_or_else(|_| format!("{result:?}")),
+            )
+            .into()),
+        }
+    }
     method!(peer_req list_roots ListRootsRequest() => ListRootsResult);
 
     method!(peer_not notify_cancelled CancelledNotification(CancelledNotificationParam));
-    method!(peer_not notify_progress ProgressNotification(ProgressNotificationParam));
     method!(peer_not notify_logging_message LoggingMessageNotification(LoggingMessageNotificationParam));
     method!(peer_not notify_resource_updated ResourceUpdatedNotification(ResourceUpdatedNotificationParam));
     method!(peer_not notify_resource_list_changed ResourceListChangedNotification);
     method!(peer_not notify_tool_list_changed ToolListChangedNotification);
     method!(peer_not notify_prompt_list_changed PromptListChangedNotification);
+
+    /// Tell the client the instructions of the server changed.
+    ///
+    /// This notification is an extension of the protocol, only send it to the clients known to
+    /// support it.
+    #[cfg(feature = "instructions-changed")]
+    pub async fn notify_instructions_changed(
+        &self,
+        instructions: Option<String>,
+    ) -> Result<(), ServiceError> {
+        self.send_notification(ServerNotification::InstructionsChangedNotification(
+            crate::model::InstructionsChangedNotification {
+                method: Default::default(),
+                params: crate::model::InstructionsChangedNotificationParam { instructions },
+                extensions: Default::default(),
+            },
+        ))
+        .await?;
+        Ok(())
+    }
+
+    /// Tell the client which resources were added and removed since the previous delta.
+    ///
+    /// This notification is an extension of the protocol, only send it to the clients known to
+    /// support it, the others need a `notifications/resources/list_changed`.
+    #[cfg(feature = "resource-delta")]
+    pub async fn notify_resource_list_delta(
+        &self,
+        params: crate::model::ResourceListDeltaNotificationParam,
+    ) -> Result<(), ServiceError> {
+        self.send_notification(ServerNotification::ResourceListDeltaNotification(
+            crate::model::ResourceListDeltaNotification {
+                method: Default::default(),
+                params,
+                extensions: Default::default(),
+            },
+        ))
+        .await?;
+        Ok(())
+    }
 }
@@ -0,0 +1,63 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::model::Extensions;
+
+/// A type map shared by every request of one connection.
+///
+/// It's created empty when the connection is accepted and cleared once the connection is
+/// closed, so the handler can keep whatever it learns about a client (a session, a counter, an
+/// authenticated user) without a map keyed by connection.
+///
+/// ```rust
+/// # use rmcp::service::ConnectionState;
+/// let state = ConnectionState::default();
+/// assert_eq!(state.update(|count: &mut u32| { *count += 1; *count }), 1);
+/// assert_eq!(state.update(|count: &mut u32| { *count += 1; *count }), 2);
+/// assert_eq!(state.get::<u32>(), Some(2));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionState {
+    values: Arc<Mutex<Extensions>>,
+}
+
+impl ConnectionState {
+    fn lock(&self) -> MutexGuard<'_, Extensions> {
+        // a panic while holding the lock can't leave the map itself broken
+        self.values
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Insert a value, returning the previous value of this type
+    pub fn insert<T: Clone + Send + Sync + 'static>(&self, value: T) -> Option<T> {
+        self.lock().insert(value)
+    }
+
+    /// Get a clone of the value of this type
+    pub fn get<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        self.lock().get::<T>().cloned()
+    }
+
+    pub fn remove<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        self.lock().remove::<T>()
+    }
+
+    /// Modify the value of this type in place, starting from its default if there's none yet.
+    ///
+    /// The state is locked while `f` runs, so concurrent requests of the connection see each
+    /// update as a whole.
+    pub fn update<T, R>(&self, f: impl FnOnce(&mut T) -> R) -> R
+    where
+        T: Clone + Default + Send + Sync + 'static,
+    {
+        let mut values = self.lock();
+        if values.get::<T>().is_none() {
+            values.insert(T::default());
+        }
+        f(values.get_mut::<T>().expect("value just inserted"))
+    }
+
+    pub(crate) fn clear(&self) {
+        self.lock().clear();
+    }
+}
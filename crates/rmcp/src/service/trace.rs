@@ -0,0 +1,21 @@
+use std::future::Future;
+
+use crate::model::TraceContext;
+
+tokio::task_local! {
+    static CURRENT_TRACE_CONTEXT: TraceContext;
+}
+
+/// Run `future` with `context` as the current trace context.
+///
+/// Every request sent from inside it carries `context` in its `_meta`, unless the request
+/// already has a `traceparent`. The service loop runs each request handler in the trace context
+/// of the request it handles, so the nested requests keep the same trace.
+pub async fn with_trace_context<F: Future>(context: TraceContext, future: F) -> F::Output {
+    CURRENT_TRACE_CONTEXT.scope(context, future).await
+}
+
+/// The trace context set by [`with_trace_context`], if any.
+pub fn current_trace_context() -> Option<TraceContext> {
+    CURRENT_TRACE_CONTEXT.try_with(Clone::clone).ok()
+}
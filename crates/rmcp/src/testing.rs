@@ -0,0 +1,285 @@
+//! # Testing
+//!
+//! Test a [`ServerHandler`] without setting up a transport: [`TestClient`] serves the handler
+//! over an in-memory transport and wraps the calls a test usually makes. [`ManualClock`] runs
+//! the timeouts without waiting.
+//!
+//! To measure a handler alone, [`invoke`] calls it without any transport, and a service
+//! served over a [`NullTransport`] neither receives nor sends anything.
+//!
+//! ```rust,ignore
+//! #[tokio::test]
+//! async fn test_sum() -> anyhow::Result<()> {
+//!     let client = TestClient::new(Calculator).await?;
+//!     let result = client.call_tool("sum", json!({ "a": 1, "b": 2 })).await?;
+//!     assert_eq!(result.content[0].as_text().unwrap().text, "3");
+//!     client.close().await?;
+//!     Ok(())
+//! }
+//! ```
+use std::{
+    borrow::Cow,
+    io,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::future::BoxFuture;
+use serde_json::Value;
+use tokio::{sync::oneshot, task::JoinError};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    Error as McpError, Peer, RoleClient, RoleServer, ServerHandler, ServiceError, ServiceExt,
+    model::{
+        CallToolRequestParam, CallToolResult, GetExtensions, GetMeta, GetPromptRequestParam,
+        GetPromptResult, JsonObject, NumberOrString, ReadResourceRequestParam, ResourceContents,
+        Tool,
+    },
+    service::{
+        AtomicU32RequestIdProvider, Clock, RequestContext, RunningService, RxJsonRpcMessage,
+        Service, ServiceRole, TxJsonRpcMessage,
+    },
+    transport::Transport,
+};
+
+/// The size of the in-memory pipe between the client and the handler
+const BUFFER_SIZE: usize = 4096;
+
+/// A client connected to a [`ServerHandler`] through an in-memory transport.
+///
+/// The client is also a [`Peer<RoleClient>`] for the requests without a helper.
+pub struct TestClient<S: ServerHandler> {
+    client: RunningService<RoleClient, ()>,
+    server: RunningService<RoleServer, S>,
+}
+
+impl<S: ServerHandler> TestClient<S> {
+    /// Serve `handler` and run the initialization.
+    pub async fn new(handler: S) -> io::Result<Self> {
+        let (server_transport, client_transport) = tokio::io::duplex(BUFFER_SIZE);
+        let (server, client) =
+            tokio::try_join!(handler.serve(server_transport), ().serve(client_transport))?;
+        Ok(Self { client, server })
+    }
+
+    pub fn peer(&self) -> &Peer<RoleClient> {
+        self.client.peer()
+    }
+
+    /// The handler being tested
+    pub fn handler(&self) -> &S {
+        self.server.service()
+    }
+
+    /// Call a tool, `arguments` is an object, an array for the positional arguments, or null
+    /// for none.
+    ///
+    /// # Panic
+    /// This will panic when `arguments` is another kind of value.
+    pub async fn call_tool(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        arguments: Value,
+    ) -> Result<CallToolResult, ServiceError> {
+        let param = match arguments {
+            Value::Null => CallToolRequestParam {
+                name: name.into(),
+                arguments: None,
+            },
+            Value::Object(arguments) => CallToolRequestParam {
+                name: name.into(),
+                arguments: Some(arguments.into()),
+            },
+            Value::Array(arguments) => CallToolRequestParam::positional(name, arguments),
+            arguments => {
+                panic!("tool arguments must be an object, an array or null, got {arguments}")
+            }
+        };
+        self.client.call_tool(param).await
+    }
+
+    /// List the tools, following the pagination.
+    pub async fn list_tools(&self) -> Result<Vec<Tool>, ServiceError> {
+        self.client.list_all_tools().await
+    }
+
+    pub async fn read_resource(
+        &self,
+        uri: impl Into<String>,
+    ) -> Result<Vec<ResourceContents>, ServiceError> {
+        let result = self
+            .client
+            .read_resource(ReadResourceRequestParam { uri: uri.into() })
+            .await?;
+        Ok(result.contents)
+    }
+
+    pub async fn get_prompt(
+        &self,
+        name: impl Into<String>,
+        arguments: Option<JsonObject>,
+    ) -> Result<GetPromptResult, ServiceError> {
+        self.client
+            .get_prompt(GetPromptRequestParam {
+                name: name.into(),
+                arguments,
+            })
+            .await
+    }
+
+    /// Close the connection and wait for the handler to stop.
+    pub async fn close(self) -> Result<(), JoinError> {
+        self.client.cancel().await?;
+        self.server.waiting().await?;
+        Ok(())
+    }
+}
+
+impl<S: ServerHandler> std::ops::Deref for TestClient<S> {
+    type Target = Peer<RoleClient>;
+
+    fn deref(&self) -> &Self::Target {
+        self.peer()
+    }
+}
+
+/// A [`Clock`] which only moves forward when told to, so a test can drive the timeouts
+/// without waiting for them.
+///
+/// ```rust,ignore
+/// let clock = ManualClock::new();
+/// let peer = client.peer().clone().with_clock(clock.clone());
+/// // ... send a request with a timeout, then
+/// clock.advance(Duration::from_secs(60));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    state: Arc<Mutex<ManualClockState>>,
+}
+
+#[derive(Debug)]
+struct ManualClockState {
+    now: Instant,
+    sleepers: Vec<(Instant, oneshot::Sender<()>)>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ManualClockState {
+                now: Instant::now(),
+                sleepers: Vec::new(),
+            })),
+        }
+    }
+
+    /// Move the time forward, waking the sleeps which are over
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.state.lock().expect("manual clock poisoned");
+        state.now += duration;
+        let now = state.now;
+        let (done, waiting) = std::mem::take(&mut state.sleepers)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(deadline, _)| *deadline <= now);
+        state.sleepers = waiting;
+        for (_, wake) in done {
+            let _ = wake.send(());
+        }
+    }
+
+    /// The number of sleeps still waiting for the time to move forward
+    pub fn sleepers(&self) -> usize {
+        let mut state = self.state.lock().expect("manual clock poisoned");
+        // the dropped sleeps don't wait anymore
+        state.sleepers.retain(|(_, wake)| !wake.is_closed());
+        state.sleepers.len()
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.state.lock().expect("manual clock poisoned").now
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        let mut state = self.state.lock().expect("manual clock poisoned");
+        let (wake, woken) = oneshot::channel();
+        if duration.is_zero() {
+            let _ = wake.send(());
+        } else {
+            let deadline = state.now + duration;
+            state.sleepers.push((deadline, wake));
+        }
+        Box::pin(async move {
+            let _ = woken.await;
+        })
+    }
+}
+
+/// A transport which discards everything sent and never receives anything.
+///
+/// There is no initialization over it, serve the handler with
+/// [`serve_directly`](crate::service::serve_directly) to run it without a peer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullTransport;
+
+impl<R: ServiceRole> Transport<R> for NullTransport {
+    type Error = io::Error;
+
+    async fn send(&mut self, _item: TxJsonRpcMessage<R>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<R>> {
+        std::future::pending().await
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Call the handler of `request` directly, with no transport nor serialization in between.
+///
+/// The context has a fresh cancellation token and a peer which isn't connected, the requests
+/// and notifications the handler sends to it fail.
+///
+/// ```rust,ignore
+/// let request = ClientRequest::CallToolRequest(CallToolRequest {
+///     method: Default::default(),
+///     params: CallToolRequestParam {
+///         name: "sum".into(),
+///         arguments: Some(object(json!({ "a": 1, "b": 2 })).into()),
+///     },
+///     extensions: Default::default(),
+/// });
+/// let ServerResult::CallToolResult(result) = invoke(&Calculator, request).await? else {
+///     unreachable!()
+/// };
+/// ```
+pub async fn invoke<R, S>(service: &S, request: R::PeerReq) -> Result<R::Resp, McpError>
+where
+    R: ServiceRole,
+    R::PeerInfo: Default,
+    S: Service<R>,
+{
+    let (peer, _outbound) = Peer::new(
+        Arc::new(AtomicU32RequestIdProvider::default()),
+        R::PeerInfo::default(),
+    );
+    let context = RequestContext {
+        ct: CancellationToken::new(),
+        id: NumberOrString::Number(0),
+        meta: request.get_meta().clone(),
+        extensions: request.extensions().clone(),
+        peer,
+    };
+    service.handle_request(request, context).await
+}
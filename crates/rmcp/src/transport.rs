@@ -10,6 +10,11 @@
 //! 2. For tuple of sink `Tx` and stream `Rx`, type `(Tx, Rx)` are automatically implemented [`IntoTransport`] trait
 //! 3. For type that implement both [`tokio::io::AsyncRead`] and [`tokio::io::AsyncWrite`] trait, they are automatically implemented [`IntoTransport`] trait
 //! 4. For tuple of [`tokio::io::AsyncRead`] `R `and [`tokio::io::AsyncWrite`] `W`, type `(R, W)` are automatically implemented [`IntoTransport`] trait
+//! 5. For type that implement the [`Transport`] trait, they are automatically implemented [`IntoTransport`] trait
+//!
+//! ## Custom transport
+//! If none of the above fits, implement [`Transport`]. It only asks for sending, receiving and closing,
+//! so it's the easiest way to bring a new kind of connection to [`serve_client`](crate::serve_client) and [`serve_server`](crate::serve_server).
 //!
 //! ## Examples
 //!
@@ -40,16 +45,23 @@
 
 use futures::{Sink, Stream};
 
-use crate::service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
+use crate::{
+    model::Extensions,
+    service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage},
+};
 #[cfg(feature = "transport-child-process")]
 pub mod child_process;
 #[cfg(feature = "transport-child-process")]
 pub use child_process::TokioChildProcess;
 
+#[cfg(feature = "transport-async-rw")]
+pub mod chunked;
 #[cfg(feature = "transport-async-rw")]
 pub mod io;
+#[cfg(feature = "transport-async-rw")]
+pub use chunked::ChunkedTransport;
 #[cfg(feature = "transport-io")]
-pub use io::stdio;
+pub use io::{stdio, stdio_piped};
 
 #[cfg(feature = "__transport-sse")]
 pub mod sse;
@@ -61,6 +73,11 @@ pub mod sse_auth;
 #[cfg(all(feature = "__transport-sse", feature = "__auth"))]
 pub use sse_auth::{AuthorizedSseClient, create_authorized_transport};
 
+#[cfg(feature = "transport-tls")]
+pub mod tls;
+#[cfg(feature = "transport-tls")]
+pub use tls::{TlsClientTransport, TlsServerAcceptor};
+
 // #[cfg(feature = "tower")]
 // pub mod tower;
 
@@ -84,6 +101,82 @@ pub use streamable_http_server::axum::StreamableHttpServer;
 /// Common use codes
 pub mod common;
 
+pub mod mux;
+
+/// The minimal extension point for a custom transport.
+///
+/// Any type implementing this trait can be passed to [`serve_client`](crate::serve_client),
+/// [`serve_server`](crate::serve_server) or [`ServiceExt::serve`](crate::ServiceExt::serve).
+///
+/// [`Transport::receive`] must be cancel safe, the transport is driven by a single task which
+/// drops a pending `receive` whenever there is an outgoing message to send.
+///
+/// # Example
+///
+/// A transport over an arbitrary channel pair:
+///
+/// ```rust
+/// # use rmcp::{
+/// #     RoleServer,
+/// #     service::{RxJsonRpcMessage, TxJsonRpcMessage},
+/// #     transport::Transport,
+/// # };
+/// use tokio::sync::mpsc;
+///
+/// pub struct ChannelTransport {
+///     tx: mpsc::Sender<TxJsonRpcMessage<RoleServer>>,
+///     rx: mpsc::Receiver<RxJsonRpcMessage<RoleServer>>,
+/// }
+///
+/// impl Transport<RoleServer> for ChannelTransport {
+///     type Error = mpsc::error::SendError<TxJsonRpcMessage<RoleServer>>;
+///
+///     async fn send(&mut self, item: TxJsonRpcMessage<RoleServer>) -> Result<(), Self::Error> {
+///         self.tx.send(item).await
+///     }
+///
+///     async fn receive(&mut self) -> Option<RxJsonRpcMessage<RoleServer>> {
+///         self.rx.recv().await
+///     }
+///
+///     async fn close(&mut self) -> Result<(), Self::Error> {
+///         self.rx.close();
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait Transport<R>: Send + 'static
+where
+    R: ServiceRole,
+{
+    type Error: std::error::Error + Send + Sync + 'static;
+    /// Send a message to the remote peer
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<R>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + '_;
+    /// Receive the next message from the remote peer, `None` means the transport is closed
+    fn receive(&mut self) -> impl Future<Output = Option<RxJsonRpcMessage<R>>> + Send + '_;
+    /// Why [`receive`](Transport::receive) returned `None`, if the transport wasn't closed
+    /// cleanly. The service then closes with the
+    /// [`CloseReason`](crate::service::CloseReason) derived from it.
+    ///
+    /// Nothing by default.
+    fn take_receive_error(&mut self) -> Option<std::io::Error> {
+        None
+    }
+    /// Close the transport, no more messages will be sent or received
+    fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send + '_;
+    /// See [`IntoTransport::transport_info`]
+    fn transport_info(&self) -> Extensions {
+        Extensions::new()
+    }
+}
+
+/// Split a transport into a sink for outgoing messages and a stream of incoming messages.
+///
+/// An `Err` from the stream means it can't be read any more, the service quits with a
+/// [`CloseReason`](crate::service::CloseReason) derived from the error.
 pub trait IntoTransport<R, E, A>: Send + 'static
 where
     R: ServiceRole,
@@ -93,8 +186,43 @@ where
         self,
     ) -> (
         impl Sink<TxJsonRpcMessage<R>, Error = E> + Send + 'static,
-        impl Stream<Item = RxJsonRpcMessage<R>> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<R>, E>> + Send + 'static,
     );
+    /// What the transport knows about the connection, by type, taken before it's split and
+    /// then available from [`Peer::transport_info`](crate::Peer::transport_info).
+    ///
+    /// Nothing by default, the TCP and TLS transports give their [`SocketAddrs`].
+    fn transport_info(&self) -> Extensions {
+        Extensions::new()
+    }
+}
+
+/// The addresses of the socket under a transport, in its
+/// [`transport_info`](IntoTransport::transport_info)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SocketAddrs {
+    pub local: std::net::SocketAddr,
+    pub peer: std::net::SocketAddr,
+}
+
+#[cfg(any(feature = "transport-tcp", feature = "transport-tls"))]
+impl SocketAddrs {
+    /// The addresses of `stream`, `None` if it's no longer connected
+    pub fn of(stream: &tokio::net::TcpStream) -> Option<Self> {
+        Some(Self {
+            local: stream.local_addr().ok()?,
+            peer: stream.peer_addr().ok()?,
+        })
+    }
+
+    /// The transport info of a connection over `stream`
+    pub(crate) fn transport_info(stream: &tokio::net::TcpStream) -> Extensions {
+        let mut info = Extensions::new();
+        if let Some(addrs) = Self::of(stream) {
+            info.insert(addrs);
+        }
+        info
+    }
 }
 
 pub enum TransportAdapterStreamSink {}
@@ -110,9 +238,10 @@ where
         self,
     ) -> (
         impl Sink<TxJsonRpcMessage<Role>, Error = E> + Send + 'static,
-        impl Stream<Item = RxJsonRpcMessage<Role>> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<Role>, E>> + Send + 'static,
     ) {
-        self
+        use futures::StreamExt;
+        (self.0, self.1.map(Ok))
     }
 }
 
@@ -126,9 +255,74 @@ where
         self,
     ) -> (
         impl Sink<TxJsonRpcMessage<R>, Error = E> + Send + 'static,
-        impl Stream<Item = RxJsonRpcMessage<R>> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<R>, E>> + Send + 'static,
     ) {
         use futures::StreamExt;
-        self.split()
+        let (sink, stream) = self.split();
+        (sink, stream.map(Ok))
+    }
+}
+
+pub enum TransportAdapterTransport {}
+
+const TRANSPORT_CHANNEL_BUFFER_SIZE: usize = 16;
+
+impl<R, T> IntoTransport<R, std::io::Error, TransportAdapterTransport> for T
+where
+    T: Transport<R>,
+    R: ServiceRole,
+{
+    fn into_transport(
+        mut self,
+    ) -> (
+        impl Sink<TxJsonRpcMessage<R>, Error = std::io::Error> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<R>, std::io::Error>> + Send + 'static,
+    ) {
+        use futures::SinkExt;
+        let (sink_tx, mut sink_rx) =
+            tokio::sync::mpsc::channel::<TxJsonRpcMessage<R>>(TRANSPORT_CHANNEL_BUFFER_SIZE);
+        let (stream_tx, mut stream_rx) = tokio::sync::mpsc::channel::<
+            Result<RxJsonRpcMessage<R>, std::io::Error>,
+        >(TRANSPORT_CHANNEL_BUFFER_SIZE);
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    message = sink_rx.recv() => {
+                        let Some(message) = message else {
+                            break;
+                        };
+                        if let Err(error) = self.send(message).await {
+                            tracing::error!(%error, "fail to send message through transport");
+                            let _ = stream_tx.send(Err(std::io::Error::other(error))).await;
+                            break;
+                        }
+                    }
+                    message = self.receive() => {
+                        let Some(message) = message else {
+                            tracing::debug!("transport stream terminated");
+                            if let Some(error) = self.take_receive_error() {
+                                let _ = stream_tx.send(Err(error)).await;
+                            }
+                            break;
+                        };
+                        if stream_tx.send(Ok(message)).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+            if let Err(error) = self.close().await {
+                tracing::error!(%error, "fail to close transport");
+            }
+        });
+        let sink = tokio_util::sync::PollSender::new(sink_tx).sink_map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::BrokenPipe, "transport closed")
+        });
+        let stream = futures::stream::poll_fn(move |cx| stream_rx.poll_recv(cx));
+        (sink, stream)
+    }
+
+    fn transport_info(&self) -> Extensions {
+        Transport::<R>::transport_info(self)
     }
 }
@@ -81,7 +81,7 @@ impl<R: ServiceRole> IntoTransport<R, std::io::Error, ()> for TokioChildProcess
         self,
     ) -> (
         impl Sink<TxJsonRpcMessage<R>, Error = std::io::Error> + Send + 'static,
-        impl Stream<Item = RxJsonRpcMessage<R>> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<R>, std::io::Error>> + Send + 'static,
     ) {
         IntoTransport::<R, std::io::Error, super::io::TransportAdapterAsyncRW>::into_transport(
             self.split(),
@@ -0,0 +1,367 @@
+//! # Chunked transport
+//!
+//! A stream transport whose small messages aren't stuck behind a large one.
+//!
+//! Over [`AsyncRwTransport`](super::io::AsyncRwTransport) a message is written whole, so a
+//! cancellation sent while a multi-megabyte result is being written waits for all of it. Here
+//! every message is sent as length-prefixed chunks tagged with a message id, and the messages
+//! which fit in one chunk are written between the chunks of the large ones.
+//!
+//! Both sides must use this transport, the framing isn't newline delimited JSON:
+//!
+//! ```text
+//! +----------------+-------+----------------+---------+
+//! | message id u32 | flags | length u32     | payload |
+//! +----------------+-------+----------------+---------+
+//! ```
+//!
+//! The integers are big endian, the flags only use the lowest bit, set on the last chunk of a
+//! message.
+//!
+//! The messages being reassembled are limited in number and in total size, see
+//! [`ChunkedTransport::max_partial_messages`] and [`ChunkedTransport::max_partial_size`], a
+//! peer going over them is a protocol violation.
+//!
+//! ```rust,ignore
+//! let (read, write) = tokio::io::split(stream);
+//! let client = ().serve(ChunkedTransport::new(read, write)).await?;
+//! ```
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    marker::PhantomData,
+};
+
+use futures::{SinkExt, StreamExt};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    sync::mpsc,
+    task::JoinHandle,
+};
+use tokio_util::{
+    bytes::{Buf, BufMut, Bytes, BytesMut},
+    codec::{Decoder, Encoder, FramedRead, FramedWrite},
+};
+
+use super::Transport;
+use crate::service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
+
+/// A piece of a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    pub message: u32,
+    pub last: bool,
+    pub payload: Bytes,
+}
+
+/// Encode and decode the [`Chunk`]s, refusing a payload longer than
+/// [`ChunkCodec::MAX_PAYLOAD_SIZE`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChunkCodec;
+
+impl ChunkCodec {
+    const HEADER_SIZE: usize = 4 + 1 + 4;
+    const LAST_FLAG: u8 = 0b1;
+    /// The longest payload of a chunk, 16 MiB
+    pub const MAX_PAYLOAD_SIZE: usize = 16 * 1024 * 1024;
+}
+
+impl Decoder for ChunkCodec {
+    type Item = Chunk;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Chunk>, io::Error> {
+        if buf.len() < Self::HEADER_SIZE {
+            return Ok(None);
+        }
+        let length = u32::from_be_bytes([buf[5], buf[6], buf[7], buf[8]]) as usize;
+        if length > Self::MAX_PAYLOAD_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "chunk of {length} bytes exceeds the limit of {}",
+                    Self::MAX_PAYLOAD_SIZE
+                ),
+            ));
+        }
+        if buf.len() < Self::HEADER_SIZE + length {
+            buf.reserve(Self::HEADER_SIZE + length - buf.len());
+            return Ok(None);
+        }
+        let message = buf.get_u32();
+        let flags = buf.get_u8();
+        buf.advance(4);
+        let payload = buf.split_to(length).freeze();
+        Ok(Some(Chunk {
+            message,
+            last: flags & Self::LAST_FLAG != 0,
+            payload,
+        }))
+    }
+}
+
+impl Encoder<Chunk> for ChunkCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, chunk: Chunk, buf: &mut BytesMut) -> Result<(), io::Error> {
+        if chunk.payload.len() > Self::MAX_PAYLOAD_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "chunk payload too long",
+            ));
+        }
+        buf.reserve(Self::HEADER_SIZE + chunk.payload.len());
+        buf.put_u32(chunk.message);
+        buf.put_u8(if chunk.last { Self::LAST_FLAG } else { 0 });
+        buf.put_u32(chunk.payload.len() as u32);
+        buf.put_slice(&chunk.payload);
+        Ok(())
+    }
+}
+
+/// A transport over an [`AsyncRead`] and an [`AsyncWrite`] which interleaves the small
+/// messages between the chunks of the large ones.
+///
+/// The messages are written by a task spawned when the transport is created, `send` only
+/// queues them.
+pub struct ChunkedTransport<Role: ServiceRole, R: AsyncRead> {
+    frames: FramedRead<R, ChunkCodec>,
+    // the messages being reassembled, by id
+    partial: HashMap<u32, BytesMut>,
+    // the total size of `partial`
+    partial_size: usize,
+    max_partial_messages: usize,
+    max_partial_size: usize,
+    // why the chunks can't be read any more
+    receive_error: Option<io::Error>,
+    outbound: Option<mpsc::Sender<Bytes>>,
+    writer: Option<JoinHandle<io::Result<()>>>,
+    marker: PhantomData<fn() -> Role>,
+}
+
+impl<Role, R> ChunkedTransport<Role, R>
+where
+    Role: ServiceRole,
+    R: AsyncRead + Send + Unpin + 'static,
+{
+    /// The size of the chunks of a large message, 16 KiB
+    pub const DEFAULT_CHUNK_SIZE: usize = 16 * 1024;
+    /// The most messages being reassembled at once, 16
+    pub const DEFAULT_MAX_PARTIAL_MESSAGES: usize = 16;
+    /// The most bytes of the messages being reassembled, 64 MiB
+    pub const DEFAULT_MAX_PARTIAL_SIZE: usize = 64 * 1024 * 1024;
+    const OUTBOUND_BUFFER_SIZE: usize = 64;
+
+    pub fn new<W>(read: R, write: W) -> Self
+    where
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        Self::with_chunk_size(read, write, Self::DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Split the messages longer than `chunk_size` bytes, a smaller size lets the small
+    /// messages through sooner at the cost of more headers.
+    ///
+    /// # Panic
+    /// This will panic when `chunk_size` is zero or exceeds [`ChunkCodec::MAX_PAYLOAD_SIZE`].
+    pub fn with_chunk_size<W>(read: R, write: W, chunk_size: usize) -> Self
+    where
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        assert!(
+            (1..=ChunkCodec::MAX_PAYLOAD_SIZE).contains(&chunk_size),
+            "chunk size must be between 1 and {} bytes",
+            ChunkCodec::MAX_PAYLOAD_SIZE
+        );
+        let (outbound, outbound_rx) = mpsc::channel(Self::OUTBOUND_BUFFER_SIZE);
+        let writer = ChunkWriter {
+            frames: FramedWrite::new(write, ChunkCodec),
+            chunk_size,
+            next_message: 0,
+            large: VecDeque::new(),
+        };
+        Self {
+            frames: FramedRead::new(read, ChunkCodec),
+            partial: HashMap::new(),
+            partial_size: 0,
+            max_partial_messages: Self::DEFAULT_MAX_PARTIAL_MESSAGES,
+            max_partial_size: Self::DEFAULT_MAX_PARTIAL_SIZE,
+            receive_error: None,
+            outbound: Some(outbound),
+            writer: Some(tokio::spawn(writer.run(outbound_rx))),
+            marker: PhantomData,
+        }
+    }
+
+    /// Limit how many messages the peer can send chunks of at once
+    pub fn max_partial_messages(mut self, max_partial_messages: usize) -> Self {
+        self.max_partial_messages = max_partial_messages;
+        self
+    }
+
+    /// Limit the total size of the chunks received for the messages which aren't complete yet
+    pub fn max_partial_size(mut self, max_partial_size: usize) -> Self {
+        self.max_partial_size = max_partial_size;
+        self
+    }
+
+    /// Keep the chunk of a message which isn't complete yet
+    fn buffer(&mut self, chunk: Chunk) -> io::Result<()> {
+        if !self.partial.contains_key(&chunk.message)
+            && self.partial.len() >= self.max_partial_messages
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "more than {} messages are being reassembled",
+                    self.max_partial_messages
+                ),
+            ));
+        }
+        let partial_size = self.partial_size + chunk.payload.len();
+        if partial_size > self.max_partial_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "the messages being reassembled exceed {} bytes",
+                    self.max_partial_size
+                ),
+            ));
+        }
+        self.partial_size = partial_size;
+        self.partial
+            .entry(chunk.message)
+            .or_default()
+            .extend_from_slice(&chunk.payload);
+        Ok(())
+    }
+}
+
+struct ChunkWriter<W> {
+    frames: FramedWrite<W, ChunkCodec>,
+    chunk_size: usize,
+    next_message: u32,
+    // the large messages, their chunks are written in turn, the first message first
+    large: VecDeque<(u32, Bytes)>,
+}
+
+impl<W: AsyncWrite + Unpin> ChunkWriter<W> {
+    async fn run(mut self, mut outbound: mpsc::Receiver<Bytes>) -> io::Result<()> {
+        loop {
+            if self.large.is_empty() {
+                let Some(message) = outbound.recv().await else {
+                    break;
+                };
+                self.push(message).await?;
+            }
+            // the small messages queued meanwhile go before the next chunk
+            while let Ok(message) = outbound.try_recv() {
+                self.push(message).await?;
+            }
+            self.write_next_chunk().await?;
+            self.frames.flush().await?;
+        }
+        self.frames.get_mut().shutdown().await
+    }
+
+    async fn push(&mut self, message: Bytes) -> io::Result<()> {
+        let id = self.next_message;
+        self.next_message = self.next_message.wrapping_add(1);
+        if message.len() <= self.chunk_size {
+            self.frames
+                .feed(Chunk {
+                    message: id,
+                    last: true,
+                    payload: message,
+                })
+                .await
+        } else {
+            self.large.push_back((id, message));
+            Ok(())
+        }
+    }
+
+    async fn write_next_chunk(&mut self) -> io::Result<()> {
+        let Some((id, message)) = self.large.front_mut() else {
+            return Ok(());
+        };
+        let payload = message.split_to(self.chunk_size.min(message.len()));
+        let chunk = Chunk {
+            message: *id,
+            last: message.is_empty(),
+            payload,
+        };
+        if chunk.last {
+            self.large.pop_front();
+        }
+        self.frames.feed(chunk).await
+    }
+}
+
+impl<Role, R> Transport<Role> for ChunkedTransport<Role, R>
+where
+    Role: ServiceRole,
+    R: AsyncRead + Send + Unpin + 'static,
+{
+    type Error = io::Error;
+
+    async fn send(&mut self, item: TxJsonRpcMessage<Role>) -> Result<(), Self::Error> {
+        let message = serde_json::to_vec(&item)?;
+        let Some(outbound) = &self.outbound else {
+            return Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "transport closed",
+            ));
+        };
+        outbound
+            .send(message.into())
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the chunk writer stopped"))
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<Role>> {
+        loop {
+            let result = match self.frames.next().await? {
+                Ok(chunk) if !chunk.last => self.buffer(chunk).map(|()| None),
+                Ok(chunk) => Ok(Some(chunk)),
+                Err(error) => Err(error),
+            };
+            let chunk = match result {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => continue,
+                Err(error) => {
+                    tracing::error!(%error, "fail to read chunk");
+                    self.receive_error = Some(error);
+                    return None;
+                }
+            };
+            let message = match self.partial.remove(&chunk.message) {
+                Some(mut message) => {
+                    self.partial_size -= message.len();
+                    message.extend_from_slice(&chunk.payload);
+                    message.freeze()
+                }
+                None => chunk.payload,
+            };
+            match serde_json::from_slice(&message) {
+                Ok(message) => return Some(message),
+                Err(error) => {
+                    tracing::warn!(%error, "fail to decode chunked message, skipped");
+                }
+            }
+        }
+    }
+
+    fn take_receive_error(&mut self) -> Option<io::Error> {
+        self.receive_error.take()
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        // closing the queue lets the writer finish the queued messages and shut down
+        self.outbound.take();
+        match self.writer.take() {
+            Some(writer) => writer.await.map_err(io::Error::other)?,
+            None => Ok(()),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use std::{sync::Arc, time::Duration};
 
+use axum::response::sse::KeepAlive;
+
 pub type SessionId = Arc<str>;
 
 pub fn session_id() -> SessionId {
@@ -7,3 +9,11 @@ pub fn session_id() -> SessionId {
 }
 
 pub const DEFAULT_AUTO_PING_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The text of the `: keepalive` comment sent on idle sse streams
+pub const KEEP_ALIVE_COMMENT: &str = "keepalive";
+
+/// Send a comment on idle sse streams so proxies don't time them out, clients ignore comments.
+pub(crate) fn keep_alive(interval: Duration) -> KeepAlive {
+    KeepAlive::new().interval(interval).text(KEEP_ALIVE_COMMENT)
+}
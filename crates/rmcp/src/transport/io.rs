@@ -1,4 +1,4 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, sync::Arc};
 
 // use crate::schema::*;
 use futures::{Sink, SinkExt, Stream, StreamExt};
@@ -17,10 +17,60 @@ use crate::service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
 /// # StdIO Transport
 ///
 /// Create a pair of [`tokio::io::Stdin`] and [`tokio::io::Stdout`].
+///
+/// A warning is printed on stderr when stdout is a terminal, like when the server is run by
+/// hand: the messages are written there instead of to a client. Use [`stdio_piped`] to refuse
+/// to start instead.
+///
+/// Wrap the pair in an [`AsyncRwTransport`] to tune it, like its buffer capacities for big
+/// messages.
 pub fn stdio() -> (tokio::io::Stdin, tokio::io::Stdout) {
+    use std::io::IsTerminal;
+    let _ = check_stdout(
+        std::io::stdout().is_terminal(),
+        true,
+        &mut std::io::stderr(),
+    );
     (tokio::io::stdin(), tokio::io::stdout())
 }
 
+#[cfg(feature = "transport-io")]
+/// Like [`stdio`], but fail with [`std::io::ErrorKind::Unsupported`] when stdout is a terminal,
+/// unless `allow_terminal` is set, in which case it only warns.
+pub fn stdio_piped(allow_terminal: bool) -> std::io::Result<(tokio::io::Stdin, tokio::io::Stdout)> {
+    use std::io::IsTerminal;
+    check_stdout(
+        std::io::stdout().is_terminal(),
+        allow_terminal,
+        &mut std::io::stderr(),
+    )?;
+    Ok((tokio::io::stdin(), tokio::io::stdout()))
+}
+
+/// Warn on `stderr` or fail when stdout is a terminal
+#[cfg(feature = "transport-io")]
+fn check_stdout(
+    is_terminal: bool,
+    allow_terminal: bool,
+    stderr: &mut impl std::io::Write,
+) -> std::io::Result<()> {
+    use std::io::Write;
+    if !is_terminal {
+        return Ok(());
+    }
+    if !allow_terminal {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "stdout is a terminal, the stdio transport expects to be piped to a client",
+        ));
+    }
+    writeln!(
+        stderr,
+        "warning: stdout is a terminal, the MCP messages of the stdio transport are written to it \
+         instead of to a client"
+    )
+}
+
 pub enum TransportAdapterAsyncRW {}
 
 impl<Role, R, W> IntoTransport<Role, std::io::Error, TransportAdapterAsyncRW> for (R, W)
@@ -33,9 +83,182 @@ where
         self,
     ) -> (
         impl Sink<TxJsonRpcMessage<Role>, Error = std::io::Error> + Send + 'static,
-        impl Stream<Item = RxJsonRpcMessage<Role>> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<Role>, std::io::Error>> + Send + 'static,
+    ) {
+        IntoTransport::<Role, std::io::Error, TransportAdapterAsyncRW>::into_transport(
+            AsyncRwTransport::new(self.0, self.1),
+        )
+    }
+}
+
+/// Which way a frame goes over the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Received from the peer
+    Inbound,
+    /// Sent to the peer
+    Outbound,
+}
+
+/// A callback that sees every frame exactly as it is serialized on the wire.
+///
+/// The frame doesn't include the trailing newline. Outbound frames are observed before they are
+/// written, inbound frames are observed before they are parsed, so a malformed frame is observed
+/// too.
+#[derive(Clone)]
+pub struct WireObserver(Arc<dyn Fn(Direction, &[u8]) + Send + Sync>);
+
+impl WireObserver {
+    pub fn new(observer: impl Fn(Direction, &[u8]) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(observer))
+    }
+
+    fn observe(&self, direction: Direction, frame: &[u8]) {
+        (self.0)(direction, frame)
+    }
+}
+
+impl std::fmt::Debug for WireObserver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WireObserver").finish_non_exhaustive()
+    }
+}
+
+/// A newline delimited json transport over an async reader and writer.
+///
+/// This is what a `(reader, writer)` pair turns into, use it directly to tap the frames with
+/// [`AsyncRwTransport::wire_observer`].
+///
+/// ```rust,ignore
+/// let (read, write) = tokio::io::split(stream);
+/// let transport = AsyncRwTransport::new(read, write).wire_observer(|direction, frame| {
+///     eprintln!("{direction:?} {}", String::from_utf8_lossy(frame));
+/// });
+/// let client = ().serve(transport).await?;
+/// ```
+#[derive(Debug)]
+pub struct AsyncRwTransport<R, W> {
+    read: R,
+    write: W,
+    wire_observer: Option<WireObserver>,
+    content_limits: ContentLimits,
+    max_depth: usize,
+    stringify_big_integers: bool,
+    lenient: bool,
+    tolerant_version: bool,
+    read_buffer_capacity: usize,
+    write_buffer_capacity: usize,
+}
+
+impl<R, W> AsyncRwTransport<R, W> {
+    /// The initial capacity of the read buffer and the bytes buffered before a write, 8 KiB
+    pub const DEFAULT_BUFFER_CAPACITY: usize = 8 * 1024;
+
+    pub fn new(read: R, write: W) -> Self {
+        Self {
+            read,
+            write,
+            read_buffer_capacity: Self::DEFAULT_BUFFER_CAPACITY,
+            write_buffer_capacity: Self::DEFAULT_BUFFER_CAPACITY,
+            wire_observer: None,
+            content_limits: ContentLimits::unlimited(),
+            max_depth: usize::MAX,
+            stringify_big_integers: false,
+            lenient: false,
+            tolerant_version: false,
+        }
+    }
+
+    /// Call `observer` with every frame sent and received on this transport.
+    pub fn wire_observer(
+        mut self,
+        observer: impl Fn(Direction, &[u8]) + Send + Sync + 'static,
+    ) -> Self {
+        self.wire_observer = Some(WireObserver::new(observer));
+        self
+    }
+
+    /// Limit the content blocks of the messages received on this transport, they aren't by
+    /// default.
+    pub fn content_limits(mut self, content_limits: ContentLimits) -> Self {
+        self.content_limits = content_limits;
+        self
+    }
+
+    /// Limit the nesting of the messages received on this transport, it isn't by default, see
+    /// [`JsonRpcMessageCodec::with_max_depth`].
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Send the integers out of the javascript safe range as strings, see
+    /// [`JsonRpcMessageCodec::with_big_integers_as_strings`].
+    pub fn stringify_big_integers(mut self, stringify_big_integers: bool) -> Self {
+        self.stringify_big_integers = stringify_big_integers;
+        self
+    }
+
+    /// Skip the malformed lines received instead of closing the connection, see
+    /// [`JsonRpcMessageCodec::with_lenient`].
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Accept the messages of peers which send `"jsonrpc": "2"` or no version at all, see
+    /// [`JsonRpcMessageCodec::with_tolerant_version`].
+    pub fn tolerant_version(mut self, tolerant_version: bool) -> Self {
+        self.tolerant_version = tolerant_version;
+        self
+    }
+
+    /// The initial capacity of the read buffer, it still grows to fit a longer message.
+    ///
+    /// A larger buffer takes fewer reads, so fewer syscalls, for big messages.
+    pub fn read_buffer_capacity(mut self, capacity: usize) -> Self {
+        self.read_buffer_capacity = capacity;
+        self
+    }
+
+    /// How many bytes of encoded messages are buffered before they are written, a message
+    /// longer than that is still written whole.
+    pub fn write_buffer_capacity(mut self, capacity: usize) -> Self {
+        self.write_buffer_capacity = capacity;
+        self
+    }
+}
+
+impl<Role, R, W> IntoTransport<Role, std::io::Error, TransportAdapterAsyncRW>
+    for AsyncRwTransport<R, W>
+where
+    Role: ServiceRole,
+    R: AsyncRead + Send + 'static,
+    W: AsyncWrite + Send + 'static,
+{
+    fn into_transport(
+        self,
+    ) -> (
+        impl Sink<TxJsonRpcMessage<Role>, Error = std::io::Error> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<Role>, std::io::Error>> + Send + 'static,
     ) {
-        (from_async_write(self.1), from_async_read(self.0))
+        let mut decoder = JsonRpcMessageCodec::default()
+            .with_content_limits(self.content_limits)
+            .with_max_depth(self.max_depth)
+            .with_lenient(self.lenient)
+            .with_tolerant_version(self.tolerant_version);
+        let mut encoder = JsonRpcMessageCodec::default()
+            .with_big_integers_as_strings(self.stringify_big_integers);
+        if let Some(observer) = self.wire_observer {
+            decoder = decoder.with_wire_observer(observer.clone());
+            encoder = encoder.with_wire_observer(observer);
+        }
+        let stream = FramedRead::with_capacity(self.read, decoder, self.read_buffer_capacity)
+            .map(|result| result.map_err(std::io::Error::from));
+        let mut sink = FramedWrite::new(self.write, encoder);
+        sink.set_backpressure_boundary(self.write_buffer_capacity);
+        let sink = sink.sink_map_err(Into::into);
+        (sink, stream)
     }
 }
 
@@ -49,12 +272,20 @@ where
         self,
     ) -> (
         impl Sink<TxJsonRpcMessage<Role>, Error = std::io::Error> + Send + 'static,
-        impl Stream<Item = RxJsonRpcMessage<Role>> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<Role>, std::io::Error>> + Send + 'static,
     ) {
         IntoTransport::<Role, std::io::Error, TransportAdapterAsyncRW>::into_transport(
             tokio::io::split(self),
         )
     }
+
+    fn transport_info(&self) -> crate::model::Extensions {
+        #[cfg(feature = "transport-tcp")]
+        if let Some(stream) = (self as &dyn std::any::Any).downcast_ref::<tokio::net::TcpStream>() {
+            return super::SocketAddrs::transport_info(stream);
+        }
+        crate::model::Extensions::new()
+    }
 }
 
 pub fn from_async_read<T: DeserializeOwned, R: AsyncRead>(reader: R) -> impl Stream<Item = T> {
@@ -72,14 +303,147 @@ pub fn from_async_write<T: Serialize, W: AsyncWrite>(
     FramedWrite::new(writer, JsonRpcMessageCodec::<T>::default()).sink_map_err(Into::into)
 }
 
+/// Limits on the content carried by a single message, checked while decoding it.
+///
+/// The content blocks are the `content` and `contents` of a result, and the `content` of the
+/// messages of a prompt or a sampling request, their `text` counts in the text length. Other
+/// fields, like the arguments of a tool call, are never looked at. This complements the byte
+/// limit of [`JsonRpcMessageCodec::new_with_max_length`].
+///
+/// The codecs and transports don't check them unless asked to, `ContentLimits::default()`
+/// are the recommended limits:
+///
+/// ```rust,ignore
+/// let transport = AsyncRwTransport::new(read, write).content_limits(ContentLimits::default());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentLimits {
+    pub max_content_blocks: usize,
+    /// Total length of text in bytes
+    pub max_text_length: usize,
+}
+
+impl ContentLimits {
+    pub const DEFAULT_MAX_CONTENT_BLOCKS: usize = 1024;
+    pub const DEFAULT_MAX_TEXT_LENGTH: usize = 64 * 1024 * 1024;
+
+    pub const fn unlimited() -> Self {
+        Self {
+            max_content_blocks: usize::MAX,
+            max_text_length: usize::MAX,
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::unlimited()
+    }
+
+    fn check(&self, message: &serde_json::Value) -> Result<(), JsonRpcMessageCodecError> {
+        let mut usage = ContentUsage::default();
+        usage.measure(message);
+        if usage.content_blocks > self.max_content_blocks {
+            return Err(JsonRpcMessageCodecError::TooManyContentBlocks {
+                count: usage.content_blocks,
+                max: self.max_content_blocks,
+            });
+        }
+        if usage.text_length > self.max_text_length {
+            return Err(JsonRpcMessageCodecError::TextTooLong {
+                length: usage.text_length,
+                max: self.max_text_length,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for ContentLimits {
+    fn default() -> Self {
+        Self {
+            max_content_blocks: Self::DEFAULT_MAX_CONTENT_BLOCKS,
+            max_text_length: Self::DEFAULT_MAX_TEXT_LENGTH,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ContentUsage {
+    content_blocks: usize,
+    text_length: usize,
+}
+
+impl ContentUsage {
+    fn measure(&mut self, message: &serde_json::Value) {
+        if let serde_json::Value::Array(batch) = message {
+            batch.iter().for_each(|message| self.measure(message));
+            return;
+        }
+        for body in ["result", "params"]
+            .into_iter()
+            .filter_map(|key| message.get(key))
+        {
+            for content in ["content", "contents"]
+                .into_iter()
+                .filter_map(|key| body.get(key))
+            {
+                self.measure_blocks(content);
+            }
+            if let Some(serde_json::Value::Array(messages)) = body.get("messages") {
+                for content in messages.iter().filter_map(|message| message.get("content")) {
+                    self.measure_blocks(content);
+                }
+            }
+        }
+    }
+
+    fn measure_blocks(&mut self, content: &serde_json::Value) {
+        let blocks = match content {
+            serde_json::Value::Array(blocks) => blocks.as_slice(),
+            block @ serde_json::Value::Object(_) => std::slice::from_ref(block),
+            _ => return,
+        };
+        self.content_blocks += blocks.len();
+        for block in blocks {
+            // the text of a text block, of a resource, or of an embedded resource
+            let texts = [
+                block.get("text"),
+                block
+                    .get("resource")
+                    .and_then(|resource| resource.get("text")),
+            ];
+            for text in texts
+                .into_iter()
+                .flatten()
+                .filter_map(serde_json::Value::as_str)
+            {
+                self.text_length += text.len();
+            }
+        }
+    }
+}
+
+/// Frame the messages as newline delimited JSON, the framing of stdio.
+///
+/// The codec is a tokio [`Decoder`] and [`Encoder`], to build a [`Framed`](tokio_util::codec::Framed)
+/// stream for a custom transport. A line longer than [`max_length`](Self::max_length) is an
+/// error, a partial line is kept until the rest arrives.
 #[derive(Debug, Clone)]
 pub struct JsonRpcMessageCodec<T> {
     _marker: PhantomData<fn() -> T>,
     next_index: usize,
     max_length: usize,
     is_discarding: bool,
+    wire_observer: Option<WireObserver>,
+    content_limits: ContentLimits,
+    max_depth: usize,
+    stringify_big_integers: bool,
+    lenient: bool,
+    tolerant_version: bool,
 }
 
+/// The codec of newline delimited JSON, named after its framing
+pub type JsonRpcLineCodec<T> = JsonRpcMessageCodec<T>;
+
 impl<T> Default for JsonRpcMessageCodec<T> {
     fn default() -> Self {
         Self::new()
@@ -93,6 +457,12 @@ impl<T> JsonRpcMessageCodec<T> {
             next_index: 0,
             max_length: usize::MAX,
             is_discarding: false,
+            wire_observer: None,
+            content_limits: ContentLimits::unlimited(),
+            max_depth: usize::MAX,
+            stringify_big_integers: false,
+            lenient: false,
+            tolerant_version: false,
         }
     }
 
@@ -106,6 +476,172 @@ impl<T> JsonRpcMessageCodec<T> {
     pub fn max_length(&self) -> usize {
         self.max_length
     }
+
+    pub fn with_wire_observer(mut self, observer: WireObserver) -> Self {
+        self.wire_observer = Some(observer);
+        self
+    }
+
+    /// Limit the content blocks of the messages, see [`ContentLimits`]. Unlimited by default,
+    /// which skips the check.
+    pub fn with_content_limits(mut self, content_limits: ContentLimits) -> Self {
+        self.content_limits = content_limits;
+        self
+    }
+
+    pub fn content_limits(&self) -> ContentLimits {
+        self.content_limits
+    }
+
+    /// Reject the messages with arrays and objects nested deeper than `max_depth`, before
+    /// parsing them, so a malicious message can't exhaust the stack. Not limited by default,
+    /// serde_json still refuses to nest deeper than 128.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Encode the integers beyond ±2^53 as strings, so javascript peers don't lose precision
+    /// on them. Off by default.
+    pub fn with_big_integers_as_strings(mut self, stringify_big_integers: bool) -> Self {
+        self.stringify_big_integers = stringify_big_integers;
+        self
+    }
+
+    /// In lenient mode a line which isn't valid json, or not a valid message, is logged and
+    /// skipped, and decoding goes on with the next line. Off by default, a malformed line is
+    /// an error.
+    pub fn with_lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// In tolerant mode a received message with a `jsonrpc` version of `"2"`, `2`, or none at
+    /// all is logged and read as `"2.0"`; other versions such as `"1.0"` are still rejected.
+    /// Off by default, only `"2.0"` is accepted.
+    pub fn with_tolerant_version(mut self, tolerant_version: bool) -> Self {
+        self.tolerant_version = tolerant_version;
+        self
+    }
+
+    fn observe(&self, direction: Direction, frame: &[u8]) {
+        if let Some(observer) = &self.wire_observer {
+            observer.observe(direction, frame);
+        }
+    }
+}
+
+impl<T: DeserializeOwned> JsonRpcMessageCodec<T> {
+    fn parse(&self, line: &[u8]) -> Result<T, JsonRpcMessageCodecError> {
+        self.observe(Direction::Inbound, line);
+        if self.max_depth != usize::MAX && exceeds_depth(line, self.max_depth) {
+            return Err(JsonRpcMessageCodecError::NestingTooDeep {
+                max: self.max_depth,
+            });
+        }
+        if self.content_limits.is_unlimited() && !self.tolerant_version {
+            return serde_json::from_slice(line).map_err(JsonRpcMessageCodecError::Serde);
+        }
+        let mut value = serde_json::from_slice(line)?;
+        if self.tolerant_version {
+            normalize_jsonrpc_version(&mut value);
+        }
+        self.content_limits.check(&value)?;
+        serde_json::from_value(value).map_err(JsonRpcMessageCodecError::Serde)
+    }
+
+    /// Parse a line, `None` means a malformed line skipped in lenient mode.
+    fn parse_line(&self, line: &[u8]) -> Result<Option<T>, JsonRpcMessageCodecError> {
+        match self.parse(line) {
+            Ok(item) => Ok(Some(item)),
+            Err(JsonRpcMessageCodecError::Serde(error)) if self.lenient => {
+                tracing::warn!(
+                    %error,
+                    line = %String::from_utf8_lossy(line),
+                    "skip malformed line"
+                );
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// Whether the arrays and objects of a json text are nested deeper than `max_depth`, the
+/// brackets inside strings don't count.
+fn exceeds_depth(json: &[u8], max_depth: usize) -> bool {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for &byte in json {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > max_depth {
+                    return true;
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Rewrite the `jsonrpc` versions tolerated for non-compliant peers to `"2.0"`, in a message
+/// or each message of a batch.
+fn normalize_jsonrpc_version(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Array(values) => values.iter_mut().for_each(normalize_jsonrpc_version),
+        serde_json::Value::Object(object) => {
+            let tolerated = match object.get("jsonrpc") {
+                None => true,
+                Some(serde_json::Value::String(version)) => version == "2",
+                Some(serde_json::Value::Number(version)) => version.as_f64() == Some(2.0),
+                Some(_) => false,
+            };
+            if tolerated {
+                tracing::warn!(version = ?object.get("jsonrpc"), "normalize jsonrpc version to 2.0");
+                object.insert("jsonrpc".into(), "2.0".into());
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The largest integer a javascript number represents exactly, `Number.MAX_SAFE_INTEGER`.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+fn stringify_big_integers(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Number(number) => {
+            let is_big = match (number.as_u64(), number.as_i64()) {
+                (Some(n), _) => n > MAX_SAFE_INTEGER,
+                (None, Some(n)) => n.unsigned_abs() > MAX_SAFE_INTEGER,
+                (None, None) => false,
+            };
+            if is_big {
+                *value = serde_json::Value::String(number.to_string());
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(stringify_big_integers),
+        serde_json::Value::Object(object) => object.values_mut().for_each(stringify_big_integers),
+        _ => {}
+    }
 }
 
 fn without_carriage_return(s: &[u8]) -> &[u8] {
@@ -120,6 +656,14 @@ fn without_carriage_return(s: &[u8]) -> &[u8] {
 pub enum JsonRpcMessageCodecError {
     #[error("max line length exceeded")]
     MaxLineLengthExceeded,
+    #[error("too many content blocks, {count} exceeds the limit of {max}")]
+    TooManyContentBlocks { count: usize, max: usize },
+    #[error("text too long, {length} bytes exceeds the limit of {max}")]
+    TextTooLong { length: usize, max: usize },
+    #[error("json nested deeper than the limit of {max}")]
+    NestingTooDeep { max: usize },
+    #[error("message of {length} bytes exceeds the limit of {max}")]
+    MessageTooLong { length: usize, max: usize },
     #[error("serde error {0}")]
     Serde(#[from] serde_json::Error),
     #[error("io error {0}")]
@@ -129,7 +673,11 @@ pub enum JsonRpcMessageCodecError {
 impl From<JsonRpcMessageCodecError> for std::io::Error {
     fn from(value: JsonRpcMessageCodecError) -> Self {
         match value {
-            JsonRpcMessageCodecError::MaxLineLengthExceeded => {
+            JsonRpcMessageCodecError::MaxLineLengthExceeded
+            | JsonRpcMessageCodecError::TooManyContentBlocks { .. }
+            | JsonRpcMessageCodecError::TextTooLong { .. }
+            | JsonRpcMessageCodecError::NestingTooDeep { .. }
+            | JsonRpcMessageCodecError::MessageTooLong { .. } => {
                 std::io::Error::new(std::io::ErrorKind::InvalidData, value)
             }
             JsonRpcMessageCodecError::Serde(e) => e.into(),
@@ -182,9 +730,10 @@ impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
                     let line = buf.split_to(newline_index + 1);
                     let line = &line[..line.len() - 1];
                     let line = without_carriage_return(line);
-                    let item =
-                        serde_json::from_slice(line).map_err(JsonRpcMessageCodecError::Serde)?;
-                    return Ok(Some(item));
+                    match self.parse_line(line)? {
+                        Some(item) => return Ok(Some(item)),
+                        None => continue,
+                    }
                 }
                 (false, None) if buf.len() > self.max_length => {
                     // Reached the maximum length without finding a
@@ -214,28 +763,155 @@ impl<T: DeserializeOwned> Decoder for JsonRpcMessageCodec<T> {
                 } else {
                     let line = buf.split_to(buf.len());
                     let line = without_carriage_return(&line);
-                    let item =
-                        serde_json::from_slice(line).map_err(JsonRpcMessageCodecError::Serde)?;
-                    Some(item)
+                    self.parse_line(line)?
                 }
             }
         })
     }
 }
 
+impl<T: Serialize> JsonRpcMessageCodec<T> {
+    /// Append the JSON of `item`, without framing.
+    fn write_json(&self, item: &T, buf: &mut BytesMut) -> Result<(), JsonRpcMessageCodecError> {
+        let start = buf.len();
+        if self.stringify_big_integers {
+            let mut value = serde_json::to_value(item)?;
+            stringify_big_integers(&mut value);
+            serde_json::to_writer(buf.writer(), &value)?;
+        } else {
+            serde_json::to_writer(buf.writer(), item)?;
+        }
+        self.observe(Direction::Outbound, &buf[start..]);
+        Ok(())
+    }
+}
+
 impl<T: Serialize> Encoder<T> for JsonRpcMessageCodec<T> {
     type Error = JsonRpcMessageCodecError;
 
     fn encode(&mut self, item: T, buf: &mut BytesMut) -> Result<(), JsonRpcMessageCodecError> {
-        serde_json::to_writer(buf.writer(), &item)?;
+        self.write_json(&item, buf)?;
         buf.put_u8(b'\n');
         Ok(())
     }
 }
 
+/// Frame the messages as JSON prefixed by their length, a big endian `u32`.
+///
+/// Unlike lines, the frames don't need the JSON to be on a single line, and a reader can
+/// tell a message is too long from its prefix without buffering it. A message longer than
+/// [`max_length`](Self::max_length) bytes is an error, a partial frame is kept until the rest
+/// arrives.
+///
+/// The parsing options of a [`JsonRpcLineCodec`], like its content limits, carry over with
+/// [`From`], its line length becoming the message length.
+#[derive(Debug, Clone)]
+pub struct JsonRpcLengthDelimitedCodec<T> {
+    inner: JsonRpcMessageCodec<T>,
+}
+
+impl<T> Default for JsonRpcLengthDelimitedCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<JsonRpcMessageCodec<T>> for JsonRpcLengthDelimitedCodec<T> {
+    fn from(inner: JsonRpcMessageCodec<T>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T> JsonRpcLengthDelimitedCodec<T> {
+    const PREFIX_SIZE: usize = 4;
+
+    /// The longest message allowed without a limit, the largest length of the prefix
+    pub const MAX_LENGTH: usize = u32::MAX as usize;
+
+    pub fn new() -> Self {
+        Self::new_with_max_length(Self::MAX_LENGTH)
+    }
+
+    pub fn new_with_max_length(max_length: usize) -> Self {
+        JsonRpcMessageCodec::new_with_max_length(max_length.min(Self::MAX_LENGTH)).into()
+    }
+
+    pub fn max_length(&self) -> usize {
+        self.inner.max_length.min(Self::MAX_LENGTH)
+    }
+}
+
+impl<T: DeserializeOwned> Decoder for JsonRpcLengthDelimitedCodec<T> {
+    type Item = T;
+
+    type Error = JsonRpcMessageCodecError;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<T>, JsonRpcMessageCodecError> {
+        loop {
+            let Some(prefix) = buf.get(..Self::PREFIX_SIZE) else {
+                return Ok(None);
+            };
+            let length = u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize;
+            let max = self.max_length();
+            if length > max {
+                return Err(JsonRpcMessageCodecError::MessageTooLong { length, max });
+            }
+            if buf.len() < Self::PREFIX_SIZE + length {
+                buf.reserve(Self::PREFIX_SIZE + length - buf.len());
+                return Ok(None);
+            }
+            buf.advance(Self::PREFIX_SIZE);
+            let frame = buf.split_to(length);
+            // `None` is a malformed message skipped in lenient mode
+            if let Some(item) = self.inner.parse_line(&frame)? {
+                return Ok(Some(item));
+            }
+        }
+    }
+}
+
+impl<T: Serialize> Encoder<T> for JsonRpcLengthDelimitedCodec<T> {
+    type Error = JsonRpcMessageCodecError;
+
+    fn encode(&mut self, item: T, buf: &mut BytesMut) -> Result<(), JsonRpcMessageCodecError> {
+        let start = buf.len();
+        buf.put_u32(0);
+        self.inner.write_json(&item, buf)?;
+        let length = buf.len() - start - Self::PREFIX_SIZE;
+        let max = self.max_length();
+        if length > max {
+            buf.truncate(start);
+            return Err(JsonRpcMessageCodecError::MessageTooLong { length, max });
+        }
+        buf[start..start + Self::PREFIX_SIZE].copy_from_slice(&(length as u32).to_be_bytes());
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+
+    #[cfg(feature = "transport-io")]
+    #[test]
+    fn test_check_stdout() {
+        let mut stderr = Vec::new();
+        assert!(check_stdout(false, false, &mut stderr).is_ok());
+        assert!(check_stdout(false, true, &mut stderr).is_ok());
+        assert!(stderr.is_empty());
+
+        check_stdout(true, true, &mut stderr).unwrap();
+        assert!(
+            String::from_utf8(stderr)
+                .unwrap()
+                .starts_with("warning: stdout is a terminal")
+        );
+
+        let mut stderr = Vec::new();
+        let error = check_stdout(true, false, &mut stderr).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+        assert!(stderr.is_empty());
+    }
     #[tokio::test]
     async fn test_decode() {
         use futures::StreamExt;
@@ -311,4 +987,235 @@ mod test {
         // Make sure there are no extra lines
         assert!(lines.next().is_none());
     }
+
+    fn tool_result_line(blocks: usize, text: &str) -> BytesMut {
+        let content = vec![serde_json::json!({ "type": "text", "text": text }); blocks];
+        let message = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": { "content": content, "isError": false },
+        });
+        let mut line = serde_json::to_vec(&message).unwrap();
+        line.push(b'\n');
+        BytesMut::from(&line[..])
+    }
+
+    #[test]
+    fn test_content_block_limit() {
+        let mut codec = JsonRpcMessageCodec::<crate::model::ServerJsonRpcMessage>::default()
+            .with_content_limits(ContentLimits::default());
+        let mut buf = tool_result_line(5000, "a");
+        let result = codec.decode(&mut buf);
+        assert!(
+            matches!(
+                result,
+                Err(JsonRpcMessageCodecError::TooManyContentBlocks {
+                    count: 5000,
+                    max: ContentLimits::DEFAULT_MAX_CONTENT_BLOCKS,
+                })
+            ),
+            "{result:?}"
+        );
+        // the oversized message is consumed, the next one decodes fine
+        let mut buf = tool_result_line(10, "a");
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+
+        // unlimited by default
+        let mut codec = JsonRpcMessageCodec::<crate::model::ServerJsonRpcMessage>::default();
+        let mut buf = tool_result_line(5000, "a");
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_content_limits_ignore_tool_arguments() {
+        let mut codec = JsonRpcMessageCodec::<crate::model::ClientJsonRpcMessage>::default()
+            .with_content_limits(ContentLimits {
+                max_content_blocks: 1,
+                max_text_length: 8,
+            });
+        let message = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": {
+                "name": "upload",
+                "arguments": { "content": [1, 2, 3], "text": "a long text, but an argument" },
+            },
+        });
+        let mut line = serde_json::to_vec(&message).unwrap();
+        line.push(b'\n');
+        let mut buf = BytesMut::from(&line[..]);
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_text_length_limit() {
+        let mut codec = JsonRpcMessageCodec::<serde_json::Value>::default().with_content_limits(
+            ContentLimits {
+                max_text_length: 8,
+                ..Default::default()
+            },
+        );
+        let mut buf = tool_result_line(3, "abc");
+        let result = codec.decode(&mut buf);
+        assert!(
+            matches!(
+                result,
+                Err(JsonRpcMessageCodecError::TextTooLong { length: 9, max: 8 })
+            ),
+            "{result:?}"
+        );
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let depth = 10_000;
+        let nested = format!("{}1{}", "{\"a\":".repeat(depth), "}".repeat(depth));
+        let line = format!(r#"{{"jsonrpc":"2.0","id":1,"result":{nested}}}"#) + "\n";
+        let mut codec =
+            JsonRpcMessageCodec::<crate::model::ServerJsonRpcMessage>::default().with_max_depth(64);
+        let mut buf = BytesMut::from(line.as_bytes());
+        let result = codec.decode(&mut buf);
+        assert!(
+            matches!(
+                result,
+                Err(JsonRpcMessageCodecError::NestingTooDeep { max: 64 })
+            ),
+            "{result:?}"
+        );
+
+        // not limited by default, what serde_json accepts is decoded
+        let depth = 100;
+        let nested = format!("{}1{}", "[".repeat(depth), "]".repeat(depth));
+        let mut codec = JsonRpcMessageCodec::<serde_json::Value>::default();
+        let mut buf = BytesMut::from(format!("{nested}\n").as_bytes());
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+
+        // brackets in strings are not nesting
+        let mut codec = JsonRpcMessageCodec::<serde_json::Value>::default().with_max_depth(2);
+        let mut buf = BytesMut::from(&br#"{"text":"[[[{{{\"]]]","list":[1]}"#[..]);
+        buf.extend_from_slice(b"\n");
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        let mut buf = BytesMut::from(&b"{\"list\":[[1]]}\n"[..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(JsonRpcMessageCodecError::NestingTooDeep { max: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_tolerant_version() {
+        fn decode(tolerant_version: bool, version: &str) -> bool {
+            let mut codec = JsonRpcMessageCodec::<crate::model::ClientJsonRpcMessage>::default()
+                .with_tolerant_version(tolerant_version);
+            let line = format!(r#"{{{version}"id":1,"method":"ping"}}"#) + "\n";
+            let mut buf = BytesMut::from(line.as_bytes());
+            codec
+                .decode(&mut buf)
+                .is_ok_and(|message| message.is_some())
+        }
+        for tolerant_version in [false, true] {
+            assert!(decode(tolerant_version, r#""jsonrpc":"2.0","#));
+            assert!(!decode(tolerant_version, r#""jsonrpc":"1.0","#));
+        }
+        for version in [r#""jsonrpc":"2","#, r#""jsonrpc":2,"#, ""] {
+            assert!(!decode(false, version), "{version}");
+            assert!(decode(true, version), "{version}");
+        }
+    }
+
+    /// One message split in two reads, after a whole one
+    fn decode_split<C: Decoder<Item = serde_json::Value>>(mut codec: C, encoded: &[u8])
+    where
+        C::Error: std::fmt::Debug,
+    {
+        let (first, second) = encoded.split_at(encoded.len() / 2 + encoded.len() / 4);
+        let mut buf = BytesMut::from(first);
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        buf.extend_from_slice(second);
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_line_codec() {
+        let message = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" });
+        let mut codec = JsonRpcLineCodec::<serde_json::Value>::default();
+        let mut buf = BytesMut::new();
+        codec.encode(message.clone(), &mut buf).unwrap();
+        codec.encode(message.clone(), &mut buf).unwrap();
+        assert_eq!(buf.iter().filter(|&&byte| byte == b'\n').count(), 2);
+        decode_split(JsonRpcLineCodec::default(), &buf);
+
+        let mut codec = JsonRpcLineCodec::<serde_json::Value>::new_with_max_length(8);
+        let mut long = BytesMut::new();
+        JsonRpcLineCodec::default()
+            .encode(message, &mut long)
+            .unwrap();
+        assert!(matches!(
+            codec.decode(&mut long),
+            Err(JsonRpcMessageCodecError::MaxLineLengthExceeded)
+        ));
+    }
+
+    #[test]
+    fn test_length_delimited_codec() {
+        let message = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" });
+        let mut codec = JsonRpcLengthDelimitedCodec::<serde_json::Value>::default();
+        let mut buf = BytesMut::new();
+        codec.encode(message.clone(), &mut buf).unwrap();
+        let json = serde_json::to_vec(&message).unwrap();
+        assert_eq!(&buf[..4], (json.len() as u32).to_be_bytes());
+        assert_eq!(&buf[4..], json);
+        codec.encode(message.clone(), &mut buf).unwrap();
+        decode_split(JsonRpcLengthDelimitedCodec::default(), &buf);
+
+        // the prefix alone is enough to reject the message
+        let mut codec = JsonRpcLengthDelimitedCodec::<serde_json::Value>::new_with_max_length(8);
+        let mut prefix = BytesMut::from(&buf[..4]);
+        assert!(matches!(
+            codec.decode(&mut prefix),
+            Err(JsonRpcMessageCodecError::MessageTooLong { max: 8, .. })
+        ));
+        let mut encoded = BytesMut::new();
+        assert!(matches!(
+            codec.encode(message, &mut encoded),
+            Err(JsonRpcMessageCodecError::MessageTooLong { max: 8, .. })
+        ));
+        assert!(encoded.is_empty());
+    }
+
+    #[test]
+    fn test_stringify_big_integers() {
+        let message = serde_json::json!({
+            "id": 1,
+            "big": u64::MAX,
+            "negative": -(1i64 << 60),
+            "safe": MAX_SAFE_INTEGER,
+            "nested": [{ "big": MAX_SAFE_INTEGER + 1 }],
+            "float": 1e20,
+        });
+        let encode = |mut codec: JsonRpcMessageCodec<serde_json::Value>| {
+            let mut buf = BytesMut::new();
+            codec.encode(message.clone(), &mut buf).unwrap();
+            serde_json::from_slice::<serde_json::Value>(&buf).unwrap()
+        };
+
+        // untouched by default
+        assert_eq!(encode(JsonRpcMessageCodec::default()), message);
+
+        let encoded = encode(JsonRpcMessageCodec::default().with_big_integers_as_strings(true));
+        assert_eq!(
+            encoded,
+            serde_json::json!({
+                "id": 1,
+                "big": u64::MAX.to_string(),
+                "negative": (-(1i64 << 60)).to_string(),
+                "safe": MAX_SAFE_INTEGER,
+                "nested": [{ "big": (MAX_SAFE_INTEGER + 1).to_string() }],
+                "float": 1e20,
+            })
+        );
+    }
 }
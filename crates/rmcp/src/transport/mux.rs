@@ -0,0 +1,221 @@
+//! # Session multiplexing
+//!
+//! Run several MCP sessions over one upstream connection.
+//!
+//! Every message on the upstream connection is wrapped in a [`MuxFrame`] tagged with the id of
+//! the session it belongs to. A [`Multiplexer`] demultiplexes incoming frames into
+//! [`MuxSession`]s, and each of them is a transport that can be served as usual, so every
+//! logical session runs its own initialize handshake and gets its own [`Peer`](crate::Peer).
+//!
+//! ```rust,ignore
+//! // client side, open sessions by id
+//! let mux = Multiplexer::<RoleClient>::new(upstream_sink, upstream_stream);
+//! let first = ().serve(mux.open("first")).await?;
+//! let second = ().serve(mux.open("second")).await?;
+//!
+//! // server side, accept sessions as they show up
+//! let mut mux = Multiplexer::<RoleServer>::new(upstream_sink, upstream_stream);
+//! while let Some(session) = mux.accept().await {
+//!     tokio::spawn(MyServer::default().serve(session));
+//! }
+//! ```
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio_util::sync::PollSender;
+
+use super::IntoTransport;
+use crate::service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage, transport_error_into_io};
+
+pub type MuxSessionId = Arc<str>;
+
+/// A message on the upstream connection, tagged with its session id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MuxFrame<M> {
+    pub session: MuxSessionId,
+    pub message: M,
+}
+
+type SessionTx<R> = mpsc::Sender<Result<RxJsonRpcMessage<R>, std::io::Error>>;
+type SessionMap<R> = Arc<Mutex<HashMap<MuxSessionId, SessionTx<R>>>>;
+
+pub struct Multiplexer<R: ServiceRole> {
+    sessions: SessionMap<R>,
+    outbound_tx: mpsc::Sender<MuxFrame<TxJsonRpcMessage<R>>>,
+    accept_rx: mpsc::Receiver<MuxSession<R>>,
+}
+
+impl<R: ServiceRole> Multiplexer<R> {
+    const OUTBOUND_BUFFER_SIZE: usize = 64;
+    const SESSION_BUFFER_SIZE: usize = 64;
+    const ACCEPT_BUFFER_SIZE: usize = 16;
+
+    /// Start demultiplexing the upstream connection.
+    ///
+    /// Frames of a session that was never opened are dropped on the client side, and queued
+    /// for [`accept`](Self::accept) on the server side. An error of the upstream stream closes
+    /// every session with it.
+    pub fn new<Tx, Rx, E>(sink: Tx, stream: Rx) -> Self
+    where
+        Tx: Sink<MuxFrame<TxJsonRpcMessage<R>>, Error = E> + Send + 'static,
+        Rx: Stream<Item = Result<MuxFrame<RxJsonRpcMessage<R>>, E>> + Send + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let sessions = SessionMap::<R>::default();
+        let (outbound_tx, mut outbound_rx) = mpsc::channel(Self::OUTBOUND_BUFFER_SIZE);
+        let (accept_tx, accept_rx) = mpsc::channel(Self::ACCEPT_BUFFER_SIZE);
+        // ends once the multiplexer and all its sessions are dropped
+        tokio::spawn(async move {
+            let mut sink = std::pin::pin!(sink);
+            while let Some(frame) = outbound_rx.recv().await {
+                if let Err(error) = sink.send(frame).await {
+                    tracing::error!(%error, "fail to send frame to upstream");
+                    break;
+                }
+            }
+        });
+        // only a weak sender, so that it doesn't keep the outbound task alive
+        let driver_outbound_tx = outbound_tx.downgrade();
+        let driver_sessions = sessions.clone();
+        tokio::spawn(async move {
+            let mut stream = std::pin::pin!(stream);
+            loop {
+                let MuxFrame { session, message } = match stream.next().await {
+                    Some(Ok(frame)) => frame,
+                    Some(Err(error)) => {
+                        let error = transport_error_into_io(error);
+                        tracing::error!(%error, "fail to receive frame from upstream");
+                        let sessions = std::mem::take(
+                            &mut *driver_sessions.lock().expect("session map poisoned"),
+                        );
+                        for inbound_tx in sessions.into_values() {
+                            let error = std::io::Error::new(error.kind(), error.to_string());
+                            let _ = inbound_tx.try_send(Err(error));
+                        }
+                        break;
+                    }
+                    None => {
+                        tracing::info!("upstream stream terminated");
+                        break;
+                    }
+                };
+                let inbound_tx = driver_sessions
+                    .lock()
+                    .expect("session map poisoned")
+                    .get(&session)
+                    .cloned();
+                let inbound_tx = match inbound_tx {
+                    Some(inbound_tx) => inbound_tx,
+                    None if R::IS_CLIENT => {
+                        tracing::warn!(%session, "drop frame of a session never opened");
+                        continue;
+                    }
+                    None => {
+                        let Some(outbound_tx) = driver_outbound_tx.upgrade() else {
+                            tracing::debug!(%session, "multiplexer dropped, refuse new session");
+                            continue;
+                        };
+                        tracing::debug!(%session, "new session");
+                        let (inbound_tx, inbound_rx) = mpsc::channel(Self::SESSION_BUFFER_SIZE);
+                        let accepted = MuxSession {
+                            id: session.clone(),
+                            outbound_tx,
+                            inbound_rx,
+                        };
+                        if accept_tx.send(accepted).await.is_err() {
+                            tracing::debug!(%session, "multiplexer dropped, refuse new session");
+                            continue;
+                        }
+                        driver_sessions
+                            .lock()
+                            .expect("session map poisoned")
+                            .insert(session.clone(), inbound_tx.clone());
+                        inbound_tx
+                    }
+                };
+                if inbound_tx.send(Ok(message)).await.is_err() {
+                    tracing::debug!(%session, "session closed");
+                    driver_sessions
+                        .lock()
+                        .expect("session map poisoned")
+                        .remove(&session);
+                }
+            }
+            // close all the sessions
+            driver_sessions
+                .lock()
+                .expect("session map poisoned")
+                .clear();
+        });
+        Self {
+            sessions,
+            outbound_tx,
+            accept_rx,
+        }
+    }
+
+    /// Open a session with the given id, usually on the client side.
+    pub fn open(&self, id: impl Into<MuxSessionId>) -> MuxSession<R> {
+        let id = id.into();
+        let (inbound_tx, inbound_rx) = mpsc::channel(Self::SESSION_BUFFER_SIZE);
+        self.sessions
+            .lock()
+            .expect("session map poisoned")
+            .insert(id.clone(), inbound_tx);
+        MuxSession {
+            id,
+            outbound_tx: self.outbound_tx.clone(),
+            inbound_rx,
+        }
+    }
+
+    /// Wait for a session opened by the remote side, usually on the server side.
+    ///
+    /// Return `None` when the upstream connection is closed.
+    pub async fn accept(&mut self) -> Option<MuxSession<R>> {
+        self.accept_rx.recv().await
+    }
+}
+
+/// One logical session of a [`Multiplexer`], it can be served as a transport.
+pub struct MuxSession<R: ServiceRole> {
+    id: MuxSessionId,
+    outbound_tx: mpsc::Sender<MuxFrame<TxJsonRpcMessage<R>>>,
+    inbound_rx: mpsc::Receiver<Result<RxJsonRpcMessage<R>, std::io::Error>>,
+}
+
+impl<R: ServiceRole> MuxSession<R> {
+    pub fn id(&self) -> &MuxSessionId {
+        &self.id
+    }
+}
+
+impl<R: ServiceRole> IntoTransport<R, std::io::Error, ()> for MuxSession<R> {
+    fn into_transport(
+        self,
+    ) -> (
+        impl Sink<TxJsonRpcMessage<R>, Error = std::io::Error> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<R>, std::io::Error>> + Send + 'static,
+    ) {
+        let MuxSession {
+            id,
+            outbound_tx,
+            mut inbound_rx,
+        } = self;
+        let sink = PollSender::new(outbound_tx)
+            .sink_map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::BrokenPipe, "multiplexer closed")
+            })
+            .with(move |message| {
+                let session = id.clone();
+                futures::future::ready(Ok::<_, std::io::Error>(MuxFrame { session, message }))
+            });
+        let stream = futures::stream::poll_fn(move |cx| inbound_rx.poll_recv(cx));
+        (sink, stream)
+    }
+}
@@ -1,17 +1,26 @@
 //！ reference: https://html.spec.whatwg.org/multipage/server-sent-events.html
-use std::{collections::VecDeque, sync::Arc, time::Duration};
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use futures::{FutureExt, Sink, Stream, StreamExt, future::BoxFuture, stream::BoxStream};
 use reqwest::{
     Client as HttpClient, IntoUrl, Url,
-    header::{ACCEPT, HeaderValue},
+    header::{ACCEPT, HeaderMap, HeaderName, HeaderValue},
 };
 use sse_stream::{Error as SseError, Sse, SseStream};
 use thiserror::Error;
 
-use crate::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
+use crate::{
+    model::{ClientJsonRpcMessage, ServerJsonRpcMessage},
+    service::{Clock, TokioClock},
+};
 const MIME_TYPE: &str = "text/event-stream";
 const HEADER_LAST_EVENT_ID: &str = "Last-Event-ID";
+/// The user agent of [`ReqwestSseClient`] unless the http client is provided
+pub const DEFAULT_USER_AGENT: &str = concat!("rmcp/", env!("CARGO_PKG_VERSION"));
 
 #[derive(Error, Debug)]
 pub enum SseTransportError<E: std::error::Error + Send + Sync + 'static> {
@@ -36,6 +45,7 @@ enum SseTransportState<E: std::error::Error + Send + Sync + 'static> {
     Connected(BoxStream<'static, Result<Sse, SseError>>),
     Retrying {
         times: usize,
+        started: Instant,
         fut: SseStreamFuture<E>,
     },
     Fatal {
@@ -60,6 +70,19 @@ impl Default for SseTransportRetryConfig {
     }
 }
 
+/// What a retry predicate knows about the reconnection so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryContext {
+    /// The number of the reconnection attempt which just failed, starting from 1
+    pub attempt: usize,
+    /// Time since the connection was lost
+    pub elapsed: Duration,
+}
+
+/// Decide whether to keep reconnecting after a failed attempt
+pub type RetryPredicate<E> =
+    Arc<dyn Fn(&SseTransportError<E>, RetryContext) -> bool + Send + Sync + 'static>;
+
 impl From<reqwest::Error> for SseTransportError<reqwest::Error> {
     fn from(e: reqwest::Error) -> Self {
         SseTransportError::Transport(e)
@@ -85,16 +108,21 @@ pub struct RetryConfig {
 pub struct ReqwestSseClient {
     http_client: HttpClient,
     sse_url: Url,
+    headers: HeaderMap,
 }
 impl ReqwestSseClient {
     pub fn new<U>(url: U) -> Result<Self, SseTransportError<reqwest::Error>>
     where
         U: IntoUrl,
     {
+        let client = HttpClient::builder()
+            .user_agent(DEFAULT_USER_AGENT)
+            .build()?;
         let url = url.into_url()?;
         Ok(Self {
-            http_client: HttpClient::default(),
+            http_client: client,
             sse_url: url,
+            headers: HeaderMap::new(),
         })
     }
 
@@ -105,13 +133,14 @@ impl ReqwestSseClient {
     where
         U: IntoUrl,
     {
-        let mut client = HttpClient::builder();
+        let mut client = HttpClient::builder().user_agent(DEFAULT_USER_AGENT);
         client = client.timeout(timeout);
         let client = client.build()?;
         let url = url.into_url()?;
         Ok(Self {
             http_client: client,
             sse_url: url,
+            headers: HeaderMap::new(),
         })
     }
 
@@ -126,8 +155,24 @@ impl ReqwestSseClient {
         Ok(Self {
             http_client: client,
             sse_url: url,
+            headers: HeaderMap::new(),
         })
     }
+
+    /// Add `headers` to every request, the sse connection as well as the posted messages.
+    ///
+    /// They take precedence over the default headers of the http client, so a `User-Agent`
+    /// in there replaces [`DEFAULT_USER_AGENT`].
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Add a header to every request, see [`ReqwestSseClient::with_headers`].
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
 }
 
 impl SseClient<reqwest::Error> for ReqwestSseClient {
@@ -135,8 +180,12 @@ impl SseClient<reqwest::Error> for ReqwestSseClient {
         let client = self.http_client.clone();
         let sse_url = self.sse_url.as_ref().to_string();
         let last_event_id = last_event_id.clone();
+        let headers = self.headers.clone();
         let fut = async move {
-            let mut request_builder = client.get(&sse_url).header(ACCEPT, MIME_TYPE);
+            let mut request_builder = client
+                .get(&sse_url)
+                .headers(headers)
+                .header(ACCEPT, MIME_TYPE);
             if let Some(last_event_id) = last_event_id {
                 request_builder = request_builder.header(HEADER_LAST_EVENT_ID, last_event_id);
             }
@@ -166,9 +215,10 @@ impl SseClient<reqwest::Error> for ReqwestSseClient {
         let client = self.http_client.clone();
         let sse_url = self.sse_url.clone();
         let session_id = session_id.to_string();
+        let headers = self.headers.clone();
         Box::pin(async move {
             let uri = sse_url.join(&session_id).map_err(SseTransportError::from)?;
-            let request_builder = client.post(uri.as_ref()).json(&message);
+            let request_builder = client.post(uri.as_ref()).headers(headers).json(&message);
             request_builder
                 .send()
                 .await
@@ -193,6 +243,8 @@ pub struct SseTransport<C: SseClient<E>, E: std::error::Error + Send + Sync + 's
     #[allow(clippy::type_complexity)]
     request_queue: VecDeque<tokio::sync::oneshot::Receiver<Result<(), SseTransportError<E>>>>,
     pub retry_config: SseTransportRetryConfig,
+    retry_predicate: Option<RetryPredicate<E>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl SseTransport<ReqwestSseClient, reqwest::Error> {
@@ -235,9 +287,34 @@ impl<C: SseClient<E>, E: std::error::Error + Send + Sync + 'static> SseTransport
             session_id,
             request_queue: Default::default(),
             retry_config: Default::default(),
+            retry_predicate: None,
+            clock: Arc::new(TokioClock),
         })
     }
 
+    /// Only keep reconnecting while `predicate` returns true, on top of
+    /// [`SseTransportRetryConfig::max_times`].
+    ///
+    /// ```rust,ignore
+    /// // give up after 30 seconds, whatever the number of attempts
+    /// let transport = SseTransport::start(url)
+    ///     .await?
+    ///     .with_retry_predicate(|_error, context| context.elapsed < Duration::from_secs(30));
+    /// ```
+    pub fn with_retry_predicate(
+        mut self,
+        predicate: impl Fn(&SseTransportError<E>, RetryContext) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.retry_predicate = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Wait for the retry delays and measure [`RetryContext::elapsed`] with `clock`
+    pub fn with_clock(mut self, clock: impl Clock) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
     fn retry_connection(&self) -> SseStreamFuture<E> {
         let retry_duration = {
             let recommended_retry_duration = self
@@ -248,9 +325,13 @@ impl<C: SseClient<E>, E: std::error::Error + Send + Sync + 'static> SseTransport
                 .map(|d: Duration| d.max(config_retry_duration))
                 .unwrap_or(config_retry_duration)
         };
-        std::thread::sleep(retry_duration);
+        let delay = self.clock.sleep(retry_duration);
+        let client = self.client.clone();
         let last_event_id = self.last_event_id.clone();
-        self.client.connect(last_event_id)
+        Box::pin(async move {
+            delay.await;
+            client.connect(last_event_id).await
+        })
     }
 }
 
@@ -262,6 +343,7 @@ impl<C: SseClient<E>, E: std::error::Error + Send + Sync + 'static> Stream for S
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
         let retry_config = self.retry_config;
+        let now = self.clock.now();
         match &mut self.state {
             SseTransportState::Connected(event_stream) => {
                 let event = std::task::ready!(event_stream.poll_next_unpin(cx));
@@ -273,6 +355,8 @@ impl<C: SseClient<E>, E: std::error::Error + Send + Sync + 'static> Stream for S
                         if let Some(id) = event.id {
                             self.as_mut().last_event_id = Some(id);
                         }
+                        // the event stream buffers the reads until an event is complete, and
+                        // joins its data lines
                         if let Some(data) = event.data {
                             match serde_json::from_str(&data) {
                                 Ok(message) => std::task::Poll::Ready(Some(message)),
@@ -282,6 +366,7 @@ impl<C: SseClient<E>, E: std::error::Error + Send + Sync + 'static> Stream for S
                                 }
                             }
                         } else {
+                            // comments, like the keep-alive of the server, carry no data
                             self.poll_next(cx)
                         }
                     }
@@ -289,13 +374,21 @@ impl<C: SseClient<E>, E: std::error::Error + Send + Sync + 'static> Stream for S
                     Some(Err(e)) => {
                         tracing::error!(error = %e, "sse event stream encounter an error");
                         let fut = self.retry_connection();
-                        self.as_mut().state = SseTransportState::Retrying { times: 1, fut };
+                        self.as_mut().state = SseTransportState::Retrying {
+                            times: 1,
+                            started: now,
+                            fut,
+                        };
                         self.poll_next(cx)
                     }
                     None => std::task::Poll::Ready(None),
                 }
             }
-            SseTransportState::Retrying { fut, times } => {
+            SseTransportState::Retrying {
+                fut,
+                times,
+                started,
+            } => {
                 let retry_result = std::task::ready!(fut.poll_unpin(cx));
                 match retry_result {
                     Ok(stream) => {
@@ -312,9 +405,29 @@ impl<C: SseClient<E>, E: std::error::Error + Send + Sync + 'static> Stream for S
                                 return self.poll_next(cx);
                             }
                         }
-                        let times = *times + 1;
+                        let context = RetryContext {
+                            attempt: *times,
+                            elapsed: now.saturating_duration_since(*started),
+                        };
+                        let started = *started;
+                        if let Some(predicate) = &self.retry_predicate {
+                            if !predicate(&e, context) {
+                                self.as_mut().state = SseTransportState::Fatal {
+                                    reason: format!(
+                                        "stop retrying after {} times in {:?}: {}",
+                                        context.attempt, context.elapsed, e
+                                    ),
+                                };
+                                return self.poll_next(cx);
+                            }
+                        }
+                        let times = context.attempt + 1;
                         let fut = self.retry_connection();
-                        self.as_mut().state = SseTransportState::Retrying { times, fut };
+                        self.as_mut().state = SseTransportState::Retrying {
+                            times,
+                            started,
+                            fut,
+                        };
                         self.poll_next(cx)
                     }
                 }
@@ -386,3 +499,108 @@ impl<C: SseClient<E>, E: std::error::Error + Send + Sync + 'static> Sink<ClientJ
         self.poll_flush(cx)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+    use crate::model::ServerNotification;
+
+    #[derive(Clone, Default)]
+    struct UnreachableClient {
+        connects: Arc<AtomicUsize>,
+    }
+
+    impl SseClient<std::io::Error> for UnreachableClient {
+        fn connect(&self, _last_event_id: Option<String>) -> SseStreamFuture<std::io::Error> {
+            self.connects.fetch_add(1, Ordering::SeqCst);
+            futures::future::ready(Err(SseTransportError::UnexpectedEndOfStream)).boxed()
+        }
+
+        fn post(
+            &self,
+            _endpoint: &str,
+            _message: ClientJsonRpcMessage,
+        ) -> BoxFuture<'static, Result<(), SseTransportError<std::io::Error>>> {
+            futures::future::ready(Ok(())).boxed()
+        }
+    }
+
+    fn transport_of<C: SseClient<std::io::Error>>(
+        client: C,
+        state: SseTransportState<std::io::Error>,
+    ) -> SseTransport<C, std::io::Error> {
+        SseTransport {
+            client: Arc::new(client),
+            state,
+            last_event_id: None,
+            recommended_retry_duration_ms: None,
+            session_id: String::new(),
+            request_queue: Default::default(),
+            retry_config: SseTransportRetryConfig {
+                max_times: Some(1000),
+                min_duration: Duration::from_millis(10),
+            },
+            retry_predicate: None,
+            clock: Arc::new(TokioClock),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_predicate_time_budget() {
+        const BUDGET: Duration = Duration::from_millis(50);
+        let client = UnreachableClient::default();
+        let contexts = Arc::new(Mutex::new(Vec::new()));
+        let state = SseTransportState::Retrying {
+            times: 1,
+            started: Instant::now(),
+            fut: client.connect(None),
+        };
+        let mut transport = transport_of(client.clone(), state).with_retry_predicate({
+            let contexts = contexts.clone();
+            move |_error, context| {
+                contexts.lock().unwrap().push(context);
+                context.elapsed < BUDGET
+            }
+        });
+
+        assert!(transport.next().await.is_none());
+
+        let contexts = contexts.lock().unwrap();
+        let last = contexts.last().unwrap();
+        assert!(last.elapsed >= BUDGET);
+        assert!(last.attempt < 1000);
+        assert_eq!(last.attempt, contexts.len());
+        assert_eq!(client.connects.load(Ordering::SeqCst), contexts.len());
+    }
+
+    #[tokio::test]
+    async fn test_event_split_across_reads() {
+        // the event is cut inside a field name and inside the json, with its data on two lines
+        let chunks = [
+            "id: 7\ndat",
+            "a: {\"jsonrpc\":\"2.0\",\"method\":\n",
+            "data: \"notifications/tools/list_changed\"}\n\n",
+        ];
+        let body = futures::stream::iter(
+            chunks.map(|chunk| Ok::<_, std::io::Error>(tokio_util::bytes::Bytes::from(chunk))),
+        );
+        let state = SseTransportState::Connected(SseStream::from_byte_stream(body).boxed());
+        let mut transport = transport_of(UnreachableClient::default(), state);
+
+        let message = transport.next().await.expect("expect the message");
+        assert!(
+            matches!(
+                message.into_notification(),
+                Some(ServerNotification::ToolListChangedNotification(_))
+            ),
+            "expect the tool list changed notification"
+        );
+        assert_eq!(transport.last_event_id.as_deref(), Some("7"));
+        assert!(transport.next().await.is_none());
+    }
+}
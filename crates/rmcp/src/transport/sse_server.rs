@@ -6,7 +6,7 @@ use axum::{
     http::{StatusCode, request::Parts},
     response::{
         Response,
-        sse::{Event, KeepAlive, Sse},
+        sse::{Event, Sse},
     },
     routing::{get, post},
 };
@@ -19,7 +19,7 @@ use crate::{
     RoleServer, Service,
     model::ClientJsonRpcMessage,
     service::{RxJsonRpcMessage, TxJsonRpcMessage},
-    transport::common::axum::{DEFAULT_AUTO_PING_INTERVAL, SessionId, session_id},
+    transport::common::axum::{DEFAULT_AUTO_PING_INTERVAL, SessionId, keep_alive, session_id},
 };
 
 type TxStore =
@@ -140,7 +140,7 @@ async fn sse_handler(
         tracing::debug!(%session_id, "Closed session and cleaned up resources");
     });
 
-    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(ping_interval)))
+    Ok(Sse::new(stream).keep_alive(keep_alive(ping_interval)))
 }
 
 pub struct SseServerTransport {
@@ -217,6 +217,8 @@ pub struct SseServerConfig {
     pub sse_path: String,
     pub post_path: String,
     pub ct: CancellationToken,
+    /// The interval of the keep-alive comments on idle sse streams, defaults to
+    /// [`DEFAULT_AUTO_PING_INTERVAL`]
     pub sse_keep_alive: Option<Duration>,
 }
 
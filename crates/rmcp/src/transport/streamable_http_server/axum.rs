@@ -6,7 +6,7 @@ use axum::{
     http::{HeaderMap, HeaderValue, StatusCode, request::Parts},
     response::{
         IntoResponse, Response,
-        sse::{Event, KeepAlive, Sse},
+        sse::{Event, Sse},
     },
     routing::get,
 };
@@ -22,7 +22,7 @@ use crate::{
     RoleServer, Service,
     model::ClientJsonRpcMessage,
     transport::{
-        common::axum::{DEFAULT_AUTO_PING_INTERVAL, SessionId, session_id},
+        common::axum::{DEFAULT_AUTO_PING_INTERVAL, SessionId, keep_alive, session_id},
         streamable_http_server::session::HEADER_SESSION_ID,
     },
 };
@@ -115,7 +115,7 @@ async fn post_handler(
                         Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
                     });
                 Ok(Sse::new(stream)
-                    .keep_alive(KeepAlive::new().interval(app.sse_ping_interval))
+                    .keep_alive(keep_alive(app.sse_ping_interval))
                     .into_response())
             }
             _ => {
@@ -191,7 +191,7 @@ async fn get_handler(
                         .into_response()
                 })?;
                 let stream = receiver_as_stream(receiver);
-                Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(app.sse_ping_interval)))
+                Ok(Sse::new(stream).keep_alive(keep_alive(app.sse_ping_interval)))
             }
             None => {
                 let sm = app.session_manager.read().await;
@@ -211,7 +211,7 @@ async fn get_handler(
                         .into_response()
                 })?;
                 let stream = receiver_as_stream(receiver);
-                Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(app.sse_ping_interval)))
+                Ok(Sse::new(stream).keep_alive(keep_alive(app.sse_ping_interval)))
             }
         }
     } else {
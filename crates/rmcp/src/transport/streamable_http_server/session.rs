@@ -5,7 +5,7 @@ use std::{
     sync::Arc,
 };
 
-use futures::{Sink, SinkExt, Stream};
+use futures::{Sink, SinkExt, Stream, StreamExt};
 use thiserror::Error;
 use tokio::sync::{
     mpsc::{Receiver, Sender},
@@ -708,9 +708,9 @@ impl IntoTransport<RoleServer, SessionError, ()> for SessionTransport {
         self,
     ) -> (
         impl Sink<ServerJsonRpcMessage, Error = SessionError> + Send + 'static,
-        impl Stream<Item = ClientJsonRpcMessage> + Send + 'static,
+        impl Stream<Item = Result<ClientJsonRpcMessage, SessionError>> + Send + 'static,
     ) {
-        let stream = ReceiverStream::new(self.to_service_rx);
+        let stream = ReceiverStream::new(self.to_service_rx).map(Ok);
         let sink = PollSender::new(self.session_handle.event_tx.clone())
             .sink_map_err(|_| SessionError::SessionServiceTerminated)
             .with(async |m| Ok(SessionEvent::ServiceMessage(m)));
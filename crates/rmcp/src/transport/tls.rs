@@ -0,0 +1,215 @@
+//! # TLS transport
+//!
+//! Run a session over a TCP connection secured with [rustls](https://docs.rs/rustls), with
+//! optional client certificate authentication.
+//!
+//! ```rust,ignore
+//! // server side, only clients with a certificate signed by `client_roots` are accepted
+//! let acceptor = TlsServerAcceptor::with_client_auth(cert_chain, key, client_roots)?;
+//! let listener = tokio::net::TcpListener::bind("127.0.0.1:8443").await?;
+//! let (tcp, _) = listener.accept().await?;
+//! let server = MyServer::default().serve(acceptor.accept(tcp).await?).await?;
+//!
+//! // client side
+//! let config = TlsClientTransport::client_config(server_roots, Some((cert_chain, key)))?;
+//! let transport = TlsClientTransport::connect("127.0.0.1:8443", "localhost", config).await?;
+//! let client = ().serve(transport).await?;
+//! ```
+//!
+//! On the server side, the certificate chain of the client is inserted as [`PeerCertificates`]
+//! into the extensions of every message, so a handler reads it from
+//! [`RequestContext::extensions`](crate::service::RequestContext::extensions).
+use std::{io, sync::Arc};
+
+use futures::{Sink, Stream};
+use thiserror::Error;
+use tokio::net::{TcpStream, ToSocketAddrs};
+pub use tokio_rustls::rustls;
+use tokio_rustls::{
+    TlsAcceptor, TlsConnector, client,
+    rustls::{
+        ClientConfig, RootCertStore, ServerConfig,
+        crypto::CryptoProvider,
+        pki_types::{CertificateDer, PrivateKeyDer, ServerName},
+        server::{VerifierBuilderError, WebPkiClientVerifier},
+    },
+    server,
+};
+
+use super::{IntoTransport, io::TransportAdapterAsyncCombinedRW};
+use crate::service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
+
+#[derive(Debug, Error)]
+pub enum TlsError {
+    #[error("tls error: {0}")]
+    Rustls(#[from] rustls::Error),
+    #[error("fail to build the client certificate verifier: {0}")]
+    ClientVerifier(#[from] VerifierBuilderError),
+}
+
+/// The certificate chain presented by the peer, end entity first
+#[derive(Debug, Clone)]
+pub struct PeerCertificates(pub Arc<[CertificateDer<'static>]>);
+
+impl PeerCertificates {
+    pub fn end_entity(&self) -> Option<&CertificateDer<'static>> {
+        self.0.first()
+    }
+}
+
+fn crypto_provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+pub struct TlsClientTransport {
+    stream: client::TlsStream<TcpStream>,
+}
+
+impl TlsClientTransport {
+    /// A client config trusting `roots`, `client_auth` is the certificate chain and key sent to
+    /// a server asking for client certificates.
+    pub fn client_config(
+        roots: RootCertStore,
+        client_auth: Option<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)>,
+    ) -> Result<Arc<ClientConfig>, TlsError> {
+        let builder = ClientConfig::builder_with_provider(crypto_provider())
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(roots);
+        let config = match client_auth {
+            Some((cert_chain, key)) => builder.with_client_auth_cert(cert_chain, key)?,
+            None => builder.with_no_client_auth(),
+        };
+        Ok(Arc::new(config))
+    }
+
+    /// Connect to `addr` and verify its certificate against `server_name`.
+    pub async fn connect(
+        addr: impl ToSocketAddrs,
+        server_name: &str,
+        config: Arc<ClientConfig>,
+    ) -> io::Result<Self> {
+        let tcp = TcpStream::connect(addr).await?;
+        Self::from_tcp(tcp, server_name, config).await
+    }
+
+    pub async fn from_tcp(
+        tcp: TcpStream,
+        server_name: &str,
+        config: Arc<ClientConfig>,
+    ) -> io::Result<Self> {
+        let server_name = ServerName::try_from(server_name.to_owned())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let stream = TlsConnector::from(config).connect(server_name, tcp).await?;
+        Ok(Self { stream })
+    }
+
+    pub fn peer_certificates(&self) -> Option<&[CertificateDer<'static>]> {
+        self.stream.get_ref().1.peer_certificates()
+    }
+}
+
+impl<R: ServiceRole> IntoTransport<R, io::Error, ()> for TlsClientTransport {
+    fn into_transport(
+        self,
+    ) -> (
+        impl Sink<TxJsonRpcMessage<R>, Error = io::Error> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<R>, io::Error>> + Send + 'static,
+    ) {
+        IntoTransport::<R, io::Error, TransportAdapterAsyncCombinedRW>::into_transport(self.stream)
+    }
+
+    fn transport_info(&self) -> crate::model::Extensions {
+        super::SocketAddrs::transport_info(self.stream.get_ref().0)
+    }
+}
+
+#[derive(Clone)]
+pub struct TlsServerAcceptor {
+    acceptor: TlsAcceptor,
+}
+
+impl TlsServerAcceptor {
+    /// Accept any client, without asking for a certificate.
+    pub fn new(
+        cert_chain: Vec<CertificateDer<'static>>,
+        key: PrivateKeyDer<'static>,
+    ) -> Result<Self, TlsError> {
+        let config = ServerConfig::builder_with_provider(crypto_provider())
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_single_cert(cert_chain, key)?;
+        Ok(Self::from_config(Arc::new(config)))
+    }
+
+    /// Only accept the clients presenting a certificate signed by one of `client_roots`.
+    pub fn with_client_auth(
+        cert_chain: Vec<CertificateDer<'static>>,
+        key: PrivateKeyDer<'static>,
+        client_roots: RootCertStore,
+    ) -> Result<Self, TlsError> {
+        let provider = crypto_provider();
+        let verifier =
+            WebPkiClientVerifier::builder_with_provider(Arc::new(client_roots), provider.clone())
+                .build()?;
+        let config = ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()?
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(cert_chain, key)?;
+        Ok(Self::from_config(Arc::new(config)))
+    }
+
+    pub fn from_config(config: Arc<ServerConfig>) -> Self {
+        Self {
+            acceptor: TlsAcceptor::from(config),
+        }
+    }
+
+    /// Run the handshake on an accepted connection, it fails if the client certificate is
+    /// missing or not trusted.
+    pub async fn accept(&self, tcp: TcpStream) -> io::Result<TlsServerTransport> {
+        let stream = self.acceptor.accept(tcp).await?;
+        Ok(TlsServerTransport { stream })
+    }
+}
+
+pub struct TlsServerTransport {
+    stream: server::TlsStream<TcpStream>,
+}
+
+impl TlsServerTransport {
+    pub fn peer_certificates(&self) -> Option<&[CertificateDer<'static>]> {
+        self.stream.get_ref().1.peer_certificates()
+    }
+}
+
+#[cfg(feature = "server")]
+impl IntoTransport<crate::RoleServer, io::Error, ()> for TlsServerTransport {
+    fn into_transport(
+        self,
+    ) -> (
+        impl Sink<TxJsonRpcMessage<crate::RoleServer>, Error = io::Error> + Send + 'static,
+        impl Stream<Item = Result<RxJsonRpcMessage<crate::RoleServer>, io::Error>> + Send + 'static,
+    ) {
+        use futures::StreamExt;
+        let peer_certificates = self
+            .peer_certificates()
+            .map(|certificates| PeerCertificates(certificates.into()));
+        let (sink, stream) = IntoTransport::<
+            crate::RoleServer,
+            io::Error,
+            TransportAdapterAsyncCombinedRW,
+        >::into_transport(self.stream);
+        let stream = stream.map(move |message| {
+            let mut message = message?;
+            if let Some(peer_certificates) = &peer_certificates {
+                message.insert_extension(peer_certificates.clone());
+            }
+            Ok(message)
+        });
+        (sink, stream)
+    }
+
+    fn transport_info(&self) -> crate::model::Extensions {
+        super::SocketAddrs::transport_info(self.stream.get_ref().0)
+    }
+}
@@ -58,6 +58,13 @@ impl ClientHandler for TestClientHandler {
         self.peer = Some(peer);
     }
 
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            capabilities: ClientCapabilities::builder().enable_sampling().build(),
+            ..Default::default()
+        }
+    }
+
     async fn create_message(
         &self,
         params: CreateMessageRequestParam,
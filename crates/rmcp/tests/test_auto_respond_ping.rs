@@ -0,0 +1,75 @@
+use rmcp::{
+    Error as McpError, ServerHandler, ServiceError, ServiceExt,
+    model::{ClientRequest, PingRequest, ServerCapabilities, ServerInfo, ServerResult},
+    service::{PeerRequestOptions, RequestTiming},
+};
+use tokio::sync::{mpsc, oneshot::error::TryRecvError};
+
+fn ping() -> ClientRequest {
+    ClientRequest::PingRequest(PingRequest {
+        method: Default::default(),
+        extensions: Default::default(),
+    })
+}
+
+#[derive(Debug, Clone)]
+struct Unresponsive {
+    completed: mpsc::UnboundedSender<Result<ServerResult, McpError>>,
+}
+
+impl ServerHandler for Unresponsive {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    fn auto_respond_ping(&self) -> bool {
+        false
+    }
+
+    fn on_request_completed(&self, result: &Result<ServerResult, McpError>, _: &RequestTiming) {
+        let _ = self.completed.send(result.clone());
+    }
+}
+
+#[tokio::test]
+async fn test_ping_left_unanswered() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (completed_tx, mut completed_rx) = mpsc::unbounded_channel();
+    let server = Unresponsive {
+        completed: completed_tx,
+    };
+    let (server, client) =
+        tokio::try_join!(server.serve(server_transport), ().serve(client_transport))?;
+
+    let mut handle = client
+        .send_request_with_option(ping(), PeerRequestOptions::no_options())
+        .await?;
+    // handled after the ping, so a pong would have been received before the listing
+    client.list_all_tools().await?;
+    let listed = completed_rx.recv().await.expect("expect the listing");
+    assert!(
+        matches!(listed, Ok(ServerResult::ListToolsResult(_))),
+        "{listed:?}"
+    );
+    assert!(
+        matches!(handle.rx.try_recv(), Err(TryRecvError::Empty)),
+        "expect the ping to stay unanswered"
+    );
+
+    // the handler holds the ping until it's cancelled
+    client.peer().cancel(handle.id.clone(), None).await?;
+    let result = handle.await_response().await;
+    assert!(
+        matches!(result, Err(ServiceError::Cancelled { .. })),
+        "{result:?}"
+    );
+    let pinged = completed_rx.recv().await.expect("expect the ping");
+    assert!(pinged.is_err(), "{pinged:?}");
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
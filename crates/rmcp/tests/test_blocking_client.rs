@@ -0,0 +1,59 @@
+mod common;
+
+use common::calculator::Calculator;
+use rmcp::{
+    ServiceExt,
+    blocking::BlockingClient,
+    model::{CallToolRequestParam, object},
+};
+use serde_json::json;
+
+#[test]
+fn test_blocking_client_calls_tool() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    // the server runs on its own runtime, the client only drives the connection while blocking
+    let server = std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        runtime.block_on(async move {
+            let server = Calculator.serve(server_transport).await?;
+            server.waiting().await?;
+            anyhow::Ok(())
+        })
+    });
+
+    let client = BlockingClient::connect(client_transport)?;
+    assert_eq!(
+        client.peer_info().instructions.as_deref(),
+        Some("A simple calculator")
+    );
+    let tools = client.list_tools()?;
+    assert!(tools.iter().any(|tool| tool.name == "sum"));
+
+    let result = client.call_tool(CallToolRequestParam {
+        name: "sum".into(),
+        arguments: Some(object(json!({ "a": 1, "b": 2 })).into()),
+    })?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("3")
+    );
+
+    let result = client.block_on(|peer| async move {
+        peer.call_tool(CallToolRequestParam {
+            name: "sub".into(),
+            arguments: Some(object(json!({ "a": 5, "b": 2 })).into()),
+        })
+        .await
+    })?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("3")
+    );
+
+    // dropping the client closes the connection, which stops the server
+    drop(client);
+    server.join().expect("server thread panicked")?;
+    Ok(())
+}
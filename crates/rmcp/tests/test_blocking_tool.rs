@@ -0,0 +1,107 @@
+use std::time::{Duration, Instant};
+
+use rmcp::{
+    ServerHandler, ServiceError, ServiceExt,
+    model::{CallToolRequestParam, ErrorCode, ServerCapabilities, ServerInfo},
+    tool,
+};
+
+const SLOW_TOOL_DURATION: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+#[tool(tool_box)]
+impl Server {
+    #[tool(blocking, description = "Block the thread for a while")]
+    fn slow(&self) -> String {
+        std::thread::sleep(SLOW_TOOL_DURATION);
+        "slow".to_string()
+    }
+
+    #[tool(description = "Answer right away")]
+    async fn fast(&self) -> String {
+        "fast".to_string()
+    }
+
+    #[tool(blocking, description = "Always panic")]
+    fn panic(&self) -> String {
+        panic!("this tool always panics")
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+fn call(name: &'static str) -> CallToolRequestParam {
+    CallToolRequestParam {
+        name: name.into(),
+        arguments: None,
+    }
+}
+
+#[tokio::test]
+async fn test_blocking_tool_does_not_stall_runtime() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let slow = tokio::spawn({
+        let peer = client.peer().clone();
+        async move { peer.call_tool(call("slow")).await }
+    });
+    // give the slow call a head start
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    // the test runs on a single threaded runtime, a blocked worker would stall this call
+    let start = Instant::now();
+    let result = client.call_tool(call("fast")).await?;
+    assert!(start.elapsed() < SLOW_TOOL_DURATION / 2);
+    assert_eq!(
+        result.content[0].as_text().map(|t| t.text.as_str()),
+        Some("fast")
+    );
+
+    let result = slow.await??;
+    assert_eq!(
+        result.content[0].as_text().map(|t| t.text.as_str()),
+        Some("slow")
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_blocking_tool_panic() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let result = client.call_tool(call("panic")).await;
+    let Err(ServiceError::McpError(error)) = result else {
+        panic!("expect an internal error, got {result:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+
+    // the server is still alive
+    client.call_tool(call("fast")).await?;
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use rmcp::{
+    RoleServer, ServerHandler, ServiceExt,
+    model::{
+        ReadResourceRequestParam, ReadResourceResult, ResourceContents, ServerCapabilities,
+        ServerInfo,
+    },
+    service::RequestContext,
+    transport::io::AsyncRwTransport,
+};
+
+/// Answer a read with the text of the uri, so the request and the response are as big
+pub struct Echo;
+
+impl ServerHandler for Echo {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_resources().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        ReadResourceRequestParam { uri }: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, rmcp::Error> {
+        let text = uri.trim_start_matches("echo://").to_owned();
+        Ok(ReadResourceResult::new(vec![ResourceContents::text(
+            text, uri,
+        )]))
+    }
+}
+
+/// Read `rounds` resources of `size` bytes through transports with `capacity` byte buffers
+async fn echo(capacity: usize, size: usize, rounds: usize) -> anyhow::Result<()> {
+    let (server_stream, client_stream) = tokio::io::duplex(64 * 1024);
+    let transport = |stream| {
+        let (read, write) = tokio::io::split(stream);
+        AsyncRwTransport::new(read, write)
+            .read_buffer_capacity(capacity)
+            .write_buffer_capacity(capacity)
+    };
+    let (server, client) = tokio::try_join!(
+        Echo.serve(transport(server_stream)),
+        ().serve(transport(client_stream))
+    )?;
+
+    let text = "x".repeat(size);
+    for _ in 0..rounds {
+        let result = client
+            .read_resource(ReadResourceRequestParam {
+                uri: format!("echo://{text}"),
+            })
+            .await?;
+        let [ResourceContents::TextResourceContents { text: echoed, .. }] = &result.contents[..]
+        else {
+            panic!("expect text contents");
+        };
+        assert_eq!(echoed.len(), size);
+        assert!(echoed == &text);
+    }
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_large_message_with_small_buffers() -> anyhow::Result<()> {
+    echo(64, 1024 * 1024, 1).await
+}
@@ -0,0 +1,70 @@
+use rmcp::{
+    ServiceError,
+    model::{ClientRequest, NumberOrString, PingRequest, ServerInfo},
+    service::{PeerRequestOptions, serve_directly},
+};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+fn ping() -> ClientRequest {
+    ClientRequest::PingRequest(PingRequest {
+        method: Default::default(),
+        extensions: Default::default(),
+    })
+}
+
+#[tokio::test]
+async fn test_cancel_pending_request() -> anyhow::Result<()> {
+    let (client_transport, server_transport) = tokio::io::duplex(4096);
+    let client = serve_directly((), client_transport, ServerInfo::default()).await?;
+    let mut server_transport = BufReader::new(server_transport);
+    let mut line = String::new();
+
+    let handle = client
+        .send_request_with_option(ping(), PeerRequestOptions::no_options())
+        .await?;
+    let id = handle.id.clone();
+    server_transport.read_line(&mut line).await?;
+    let request: serde_json::Value = serde_json::from_str(&line)?;
+    assert_eq!(request["method"], "ping");
+
+    let pending = tokio::spawn(handle.await_response());
+    client
+        .peer()
+        .cancel(id.clone(), Some("changed my mind".into()))
+        .await?;
+    let result = pending.await?;
+    assert!(
+        matches!(
+            &result,
+            Err(ServiceError::Cancelled { reason }) if reason.as_deref() == Some("changed my mind")
+        ),
+        "{result:?}"
+    );
+
+    line.clear();
+    server_transport.read_line(&mut line).await?;
+    let notification: serde_json::Value = serde_json::from_str(&line)?;
+    assert_eq!(notification["method"], "notifications/cancelled");
+    assert_eq!(
+        notification["params"]["requestId"],
+        serde_json::to_value(&id)?
+    );
+    assert_eq!(notification["params"]["reason"], "changed my mind");
+
+    // the request is gone now, cancelling it again or an unknown one sends nothing
+    client.peer().cancel(id, None).await?;
+    client
+        .peer()
+        .cancel(NumberOrString::String("unknown".into()), None)
+        .await?;
+    let _handle = client
+        .send_request_with_option(ping(), PeerRequestOptions::no_options())
+        .await?;
+    line.clear();
+    server_transport.read_line(&mut line).await?;
+    let request: serde_json::Value = serde_json::from_str(&line)?;
+    assert_eq!(request["method"], "ping");
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::sync::{Arc, Mutex};
+
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler, ServiceError, ServiceExt,
+    model::{
+        ClientRequest, ListToolsRequest, ListToolsResult, PaginatedRequestParam,
+        ServerCapabilities, ServerInfo,
+    },
+    service::{PeerRequestOptions, RequestContext},
+};
+use tokio::sync::{Notify, oneshot};
+
+/// Never answers `tools/list`, it reports why it was cancelled instead
+#[derive(Clone)]
+struct Stuck {
+    started: Arc<Notify>,
+    reason: Arc<Mutex<Option<oneshot::Sender<Option<String>>>>>,
+}
+
+impl ServerHandler for Stuck {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        self.started.notify_one();
+        context.ct.cancelled().await;
+        if let Some(sender) = self.reason.lock().unwrap().take() {
+            let _ = sender.send(context.cancellation_reason().map(str::to_owned));
+        }
+        Err(McpError::internal_error("cancelled", None))
+    }
+}
+
+async fn cancel_list_tools(reason: Option<&str>) -> anyhow::Result<Option<String>> {
+    let (sender, receiver) = oneshot::channel();
+    let started = Arc::new(Notify::new());
+    let server = Stuck {
+        started: started.clone(),
+        reason: Arc::new(Mutex::new(Some(sender))),
+    };
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) =
+        tokio::try_join!(server.serve(server_transport), ().serve(client_transport))?;
+
+    let handle = client
+        .send_request_with_option(
+            ClientRequest::ListToolsRequest(ListToolsRequest {
+                method: Default::default(),
+                params: None,
+                extensions: Default::default(),
+            }),
+            PeerRequestOptions::no_options(),
+        )
+        .await?;
+    started.notified().await;
+    let id = handle.id.clone();
+    client.peer().cancel(id, reason.map(str::to_owned)).await?;
+    assert!(matches!(
+        handle.await_response().await,
+        Err(ServiceError::Cancelled { .. })
+    ));
+    let observed = receiver.await?;
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(observed)
+}
+
+#[tokio::test]
+async fn test_handler_observes_cancellation_reason() -> anyhow::Result<()> {
+    let reason = cancel_list_tools(Some("the user pressed stop")).await?;
+    assert_eq!(reason.as_deref(), Some("the user pressed stop"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cancellation_without_reason() -> anyhow::Result<()> {
+    assert_eq!(cancel_list_tools(None).await?, None);
+    Ok(())
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use common::calculator::Calculator;
+use rmcp::{
+    RoleClient, RoleServer, ServiceExt,
+    model::{
+        CallToolRequest, CallToolRequestParam, CancelledNotification, CancelledNotificationParam,
+        ClientJsonRpcMessage, ClientNotification, ClientRequest, NumberOrString, object,
+    },
+    service::CloseReason,
+    transport::{ChunkedTransport, Transport},
+};
+use serde_json::json;
+use tokio::io::AsyncWriteExt;
+
+const HUGE_SIZE: usize = 4 * 1024 * 1024;
+
+fn huge_request() -> ClientJsonRpcMessage {
+    ClientJsonRpcMessage::request(
+        ClientRequest::CallToolRequest(CallToolRequest {
+            method: Default::default(),
+            params: CallToolRequestParam {
+                name: "upload".into(),
+                arguments: Some(object(json!({ "data": "x".repeat(HUGE_SIZE) })).into()),
+            },
+            extensions: Default::default(),
+        }),
+        NumberOrString::Number(1),
+    )
+}
+
+fn cancellation() -> ClientJsonRpcMessage {
+    ClientJsonRpcMessage::notification(ClientNotification::from(CancelledNotification {
+        method: Default::default(),
+        params: CancelledNotificationParam {
+            request_id: NumberOrString::Number(1),
+            reason: Some("too slow".into()),
+        },
+        extensions: Default::default(),
+    }))
+}
+
+#[tokio::test]
+async fn test_cancellation_overtakes_huge_message() -> anyhow::Result<()> {
+    let (client_stream, server_stream) = tokio::io::duplex(64 * 1024);
+    let (read, write) = tokio::io::split(client_stream);
+    let mut client = ChunkedTransport::<RoleClient, _>::new(read, write);
+    let (read, write) = tokio::io::split(server_stream);
+    let mut server = ChunkedTransport::<RoleServer, _>::new(read, write);
+
+    client.send(huge_request()).await?;
+    client.send(cancellation()).await?;
+
+    let first = server.receive().await.expect("a message");
+    let Some(ClientNotification::CancelledNotification(cancelled)) = first.into_notification()
+    else {
+        panic!("expect the cancellation first");
+    };
+    assert_eq!(cancelled.params.request_id, NumberOrString::Number(1));
+
+    let second = server.receive().await.expect("a message");
+    let Some((ClientRequest::CallToolRequest(request), id)) = second.into_request() else {
+        panic!("expect the huge request after the cancellation");
+    };
+    assert_eq!(id, NumberOrString::Number(1));
+    let data = request.params.named_arguments().expect("arguments")["data"].clone();
+    assert_eq!(data.as_str().map(str::len), Some(HUGE_SIZE));
+
+    client.close().await?;
+    assert!(server.receive().await.is_none());
+    server.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_serve_over_chunked_transport() -> anyhow::Result<()> {
+    let (client_stream, server_stream) = tokio::io::duplex(4096);
+    let (read, write) = tokio::io::split(server_stream);
+    let server_transport = ChunkedTransport::with_chunk_size(read, write, 64);
+    let (read, write) = tokio::io::split(client_stream);
+    let client_transport = ChunkedTransport::with_chunk_size(read, write, 64);
+    let (server, client) = tokio::try_join!(
+        Calculator.serve(server_transport),
+        ().serve(client_transport)
+    )?;
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "sum".into(),
+            arguments: Some(object(json!({ "a": 1, "b": 2 })).into()),
+        })
+        .await?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("3")
+    );
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+/// A chunk as [`ChunkCodec`](rmcp::transport::chunked::ChunkCodec) writes it
+fn raw_chunk(message: u32, last: bool, payload: &[u8]) -> Vec<u8> {
+    let mut chunk = message.to_be_bytes().to_vec();
+    chunk.push(last as u8);
+    chunk.extend((payload.len() as u32).to_be_bytes());
+    chunk.extend(payload);
+    chunk
+}
+
+#[tokio::test]
+async fn test_partial_messages_are_limited() -> anyhow::Result<()> {
+    let (mut client_stream, server_stream) = tokio::io::duplex(64 * 1024);
+    let (read, write) = tokio::io::split(server_stream);
+    let mut server = ChunkedTransport::<RoleServer, _>::new(read, write).max_partial_messages(2);
+
+    for message in 0..3 {
+        client_stream
+            .write_all(&raw_chunk(message, false, b"{\"jsonrpc\""))
+            .await?;
+    }
+    assert!(server.receive().await.is_none());
+    let error = server.take_receive_error().expect("an error");
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(matches!(
+        CloseReason::from(error),
+        CloseReason::ProtocolViolation(_)
+    ));
+    server.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_partial_size_is_limited() -> anyhow::Result<()> {
+    let (mut client_stream, server_stream) = tokio::io::duplex(64 * 1024);
+    let (read, write) = tokio::io::split(server_stream);
+    let mut server = ChunkedTransport::<RoleServer, _>::new(read, write).max_partial_size(1024);
+
+    for _ in 0..2 {
+        client_stream
+            .write_all(&raw_chunk(0, false, &[b' '; 1000]))
+            .await?;
+    }
+    assert!(server.receive().await.is_none());
+    let error = server.take_receive_error().expect("an error");
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    server.close().await?;
+    Ok(())
+}
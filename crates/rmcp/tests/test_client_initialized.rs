@@ -0,0 +1,73 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use rmcp::{ServerHandler, ServiceExt, model::ServerJsonRpcMessage};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+#[derive(Clone, Default)]
+struct Counter {
+    initialized: Arc<AtomicUsize>,
+}
+
+impl ServerHandler for Counter {
+    async fn on_client_initialized(&self) {
+        self.initialized.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn test_on_client_initialized_fires_once_after_notification() -> anyhow::Result<()> {
+    let counter = Counter::default();
+    let (client_transport, server_transport) = tokio::io::duplex(4096);
+    let server = tokio::spawn(counter.clone().serve(server_transport));
+    let (client_read, mut client_write) = tokio::io::split(client_transport);
+    let mut client_read = BufReader::new(client_read);
+
+    client_write
+        .write_all(
+            concat!(
+                r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"#,
+                r#""protocolVersion":"2025-03-26","capabilities":{},"#,
+                r#""clientInfo":{"name":"raw","version":"1"}}}"#,
+                "\n"
+            )
+            .as_bytes(),
+        )
+        .await?;
+    let mut line = String::new();
+    client_read.read_line(&mut line).await?;
+    let response = serde_json::from_str::<ServerJsonRpcMessage>(&line)?;
+    assert!(response.into_response().is_some());
+
+    // answering initialize isn't enough
+    assert_eq!(counter.initialized.load(Ordering::SeqCst), 0);
+
+    // held until the client is initialized, then answered first
+    client_write
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"ping\"}\n")
+        .await?;
+    // a repeated notification is dropped
+    for _ in 0..2 {
+        client_write
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"method\":\"notifications/initialized\"}\n")
+            .await?;
+    }
+    client_write
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":3,\"method\":\"ping\"}\n")
+        .await?;
+    for expected_id in [2, 3] {
+        line.clear();
+        client_read.read_line(&mut line).await?;
+        let (_, id) = serde_json::from_str::<ServerJsonRpcMessage>(&line)?
+            .into_response()
+            .expect("expect the ping response");
+        assert_eq!(id, rmcp::model::NumberOrString::Number(expected_id));
+        assert_eq!(counter.initialized.load(Ordering::SeqCst), 1);
+    }
+
+    let server = server.await??;
+    server.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use common::calculator::Calculator;
+use rmcp::{
+    RoleServer, ServiceExt,
+    service::{ClientPool, ClientPoolError, RunningService},
+};
+
+type Servers = Arc<Mutex<Vec<(&'static str, RunningService<RoleServer, Calculator>)>>>;
+
+/// A pool connecting to in-memory calculators, keeping the servers to close them
+fn calculator_pool(
+    max_connections: usize,
+) -> (ClientPool<&'static str>, Servers, Arc<AtomicUsize>) {
+    let servers = Servers::default();
+    let connects = Arc::new(AtomicUsize::new(0));
+    let pool = ClientPool::new(max_connections, {
+        let servers = servers.clone();
+        let connects = connects.clone();
+        move |endpoint: &&'static str| {
+            let endpoint = *endpoint;
+            let servers = servers.clone();
+            connects.fetch_add(1, Ordering::SeqCst);
+            async move {
+                let (server_transport, client_transport) = tokio::io::duplex(4096);
+                let (server, client) = tokio::try_join!(
+                    Calculator.serve(server_transport),
+                    ().serve(client_transport)
+                )?;
+                servers.lock().unwrap().push((endpoint, server));
+                Ok::<_, std::io::Error>(client)
+            }
+        }
+    });
+    (pool, servers, connects)
+}
+
+#[tokio::test]
+async fn test_borrow_return_and_reuse() -> anyhow::Result<()> {
+    let (pool, _servers, connects) = calculator_pool(2);
+
+    let client = pool.get("a").await?;
+    assert_eq!(client.list_all_tools().await?.len(), 2);
+    drop(client);
+    assert_eq!(pool.idle_connections(), 1);
+
+    // the idle connection is handed out again
+    let a = pool.get("a").await?;
+    assert_eq!(connects.load(Ordering::SeqCst), 1);
+    assert_eq!(pool.idle_connections(), 0);
+    let b = pool.get("b").await?;
+    assert_eq!(connects.load(Ordering::SeqCst), 2);
+    assert_eq!(pool.open_connections(), 2);
+
+    // every connection is borrowed
+    let result = pool.get("c").await;
+    assert!(
+        matches!(result, Err(ClientPoolError::Exhausted { max: 2 })),
+        "expect the pool to be exhausted"
+    );
+
+    // an idle connection of another endpoint makes room
+    drop(b);
+    let c = pool.get("c").await?;
+    assert_eq!(c.key(), &"c");
+    assert_eq!(connects.load(Ordering::SeqCst), 3);
+    assert_eq!(pool.open_connections(), 2);
+    assert_eq!(pool.idle_connections(), 0);
+    drop((a, c));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_evict_closed_connection() -> anyhow::Result<()> {
+    let (pool, servers, connects) = calculator_pool(2);
+
+    drop(pool.get("a").await?);
+    let server = servers
+        .lock()
+        .unwrap()
+        .pop()
+        .map(|(_, server)| server)
+        .unwrap();
+    server.cancel().await?;
+
+    let mut evicted = 0;
+    for _ in 0..100 {
+        evicted = pool.evict_closed();
+        if evicted > 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(evicted, 1);
+    assert_eq!(pool.open_connections(), 0);
+
+    // a new connection replaces it
+    let client = pool.get("a").await?;
+    assert_eq!(connects.load(Ordering::SeqCst), 2);
+    assert_eq!(client.list_all_tools().await?.len(), 2);
+    Ok(())
+}
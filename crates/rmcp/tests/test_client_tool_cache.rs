@@ -0,0 +1,78 @@
+mod common;
+
+use common::calculator::Calculator;
+use rmcp::{
+    ServiceError, ServiceExt,
+    model::{CallToolRequestParam, ErrorCode, ServerInfo, Tool},
+    service::{ClientToolCache, serve_directly},
+};
+use serde_json::json;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+fn arguments(value: serde_json::Value) -> rmcp::model::JsonObject {
+    value.as_object().cloned().unwrap()
+}
+
+#[tokio::test]
+async fn test_fetch_and_validate() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Calculator.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let tools = ClientToolCache::fetch(&client).await?;
+    assert!(tools.get("sum").is_some());
+    tools.validate_args("sum", &arguments(json!({ "a": 1, "b": 2 })))?;
+    let error = tools
+        .validate_args("sum", &arguments(json!({ "a": "one", "b": 2 })))
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    assert!(
+        tools
+            .validate_args("product", &arguments(json!({})))
+            .is_err()
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invalid_call_is_not_sent() -> anyhow::Result<()> {
+    let (client_transport, server_transport) = tokio::io::duplex(4096);
+    let client = serve_directly((), client_transport, ServerInfo::default()).await?;
+    let schema = arguments(json!({
+        "type": "object",
+        "properties": { "a": { "type": "integer" }, "b": { "type": "integer" } },
+        "required": ["a", "b"],
+    }));
+    let tools = ClientToolCache::new([Tool::new("sum", "Calculate the sum", schema)]);
+
+    let result = tools
+        .call_tool(
+            &client,
+            CallToolRequestParam {
+                name: "sum".into(),
+                arguments: Some(arguments(json!({ "a": "one", "b": 2 })).into()),
+            },
+        )
+        .await;
+    assert!(
+        matches!(&result, Err(ServiceError::McpError(e)) if e.code == ErrorCode::INVALID_PARAMS),
+        "{result:?}"
+    );
+
+    // the first thing on the wire is the next request, the invalid call never left
+    let peer = client.peer().clone();
+    tokio::spawn(async move { peer.list_all_tools().await });
+    let mut server_transport = BufReader::new(server_transport);
+    let mut line = String::new();
+    server_transport.read_line(&mut line).await?;
+    assert!(line.contains("tools/list"), "{line}");
+
+    client.cancel().await?;
+    Ok(())
+}
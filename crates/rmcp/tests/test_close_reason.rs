@@ -0,0 +1,93 @@
+mod common;
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use common::calculator::Calculator;
+use rmcp::{
+    model::ClientInfo,
+    service::{CloseReason, serve_directly},
+};
+use tokio::io::{AsyncRead, AsyncWriteExt, ReadBuf};
+
+/// A reader which always times out
+struct TimedOutReader;
+
+impl AsyncRead for TimedOutReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Err(std::io::ErrorKind::TimedOut.into()))
+    }
+}
+
+#[tokio::test]
+async fn test_close_reason_cancelled() -> anyhow::Result<()> {
+    let (server_transport, _client_transport) = tokio::io::duplex(4096);
+    let server = serve_directly(Calculator, server_transport, ClientInfo::default()).await?;
+    let reason = server.cancel().await?;
+    assert!(matches!(reason, CloseReason::Cancelled), "{reason:?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_close_reason_peer_closed() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = serve_directly(Calculator, server_transport, ClientInfo::default()).await?;
+    drop(client_transport);
+    let reason = server.waiting().await?;
+    assert!(matches!(reason, CloseReason::Closed), "{reason:?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_close_reason_protocol_violation() -> anyhow::Result<()> {
+    let (server_transport, mut client_transport) = tokio::io::duplex(4096);
+    let server = serve_directly(Calculator, server_transport, ClientInfo::default()).await?;
+    client_transport.write_all(b"this is not json\n").await?;
+    let reason = server.waiting().await?;
+    assert!(
+        matches!(reason, CloseReason::ProtocolViolation(_)),
+        "{reason:?}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_close_reason_transport_error() -> anyhow::Result<()> {
+    let (server_read, _client_write) = tokio::io::duplex(4096);
+    let (server_write, client_read) = tokio::io::duplex(4096);
+    let server = serve_directly(
+        Calculator,
+        (server_read, server_write),
+        ClientInfo::default(),
+    )
+    .await?;
+    drop(client_read);
+    // nobody reads the other end any more, writing fails with broken pipe
+    assert!(server.notify_tool_list_changed().await.is_err());
+    let reason = server.waiting().await?;
+    assert!(
+        matches!(&reason, CloseReason::TransportError(e) if e.kind() == std::io::ErrorKind::BrokenPipe),
+        "{reason:?}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_close_reason_timeout() -> anyhow::Result<()> {
+    let (server_write, _client_read) = tokio::io::duplex(4096);
+    let server = serve_directly(
+        Calculator,
+        (TimedOutReader, server_write),
+        ClientInfo::default(),
+    )
+    .await?;
+    let reason = server.waiting().await?;
+    assert!(matches!(reason, CloseReason::Timeout), "{reason:?}");
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler, ServiceExt,
+    model::{
+        ArgumentInfo, CompleteRequestParam, CompleteResult, CompletionContext, CompletionInfo,
+        PromptReference, Reference, ResourceReference, ServerCapabilities, ServerInfo,
+    },
+    service::RequestContext,
+};
+
+/// Complete the `city` argument from the `country` already filled
+#[derive(Clone, Default)]
+struct CityCompleter {
+    received: Arc<Mutex<Option<CompletionContext>>>,
+}
+
+impl ServerHandler for CityCompleter {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_completions().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn complete(
+        &self,
+        request: CompleteRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CompleteResult, McpError> {
+        *self.received.lock().unwrap() = request.context.clone();
+        let values = match request.context_argument("country") {
+            Some("France") => vec!["Paris".to_string(), "Lyon".to_string()],
+            _ => vec![],
+        };
+        Ok(CompleteResult {
+            completion: CompletionInfo {
+                values,
+                total: None,
+                has_more: None,
+            },
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_completion_context() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let completer = CityCompleter::default();
+    tokio::spawn({
+        let completer = completer.clone();
+        async move {
+            let server = completer.serve(server_transport).await?;
+            server.waiting().await?;
+            anyhow::Ok(())
+        }
+    });
+    let client = ().serve(client_transport).await?;
+
+    let context = CompletionContext {
+        arguments: Some(BTreeMap::from([
+            ("country".to_string(), "France".to_string()),
+            ("language".to_string(), "fr".to_string()),
+        ])),
+    };
+    let result = client
+        .complete(CompleteRequestParam {
+            r#ref: Reference::Prompt(PromptReference {
+                name: "travel".to_string(),
+            }),
+            argument: ArgumentInfo {
+                name: "city".to_string(),
+                value: "".to_string(),
+            },
+            context: Some(context.clone()),
+        })
+        .await?;
+    assert_eq!(result.completion.values, vec!["Paris", "Lyon"]);
+    assert_eq!(*completer.received.lock().unwrap(), Some(context));
+
+    client.cancel().await?;
+    Ok(())
+}
+
+/// Complete the `{path}` of a file template from a fixed listing, two candidates at most
+#[derive(Clone)]
+struct FileCompleter;
+
+impl FileCompleter {
+    const FILES: &[&str] = &["src/lib.rs", "src/main.rs", "src/model.rs", "README.md"];
+    const MAX_VALUES: usize = 2;
+}
+
+impl ServerHandler for FileCompleter {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_completions().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn complete_resource(
+        &self,
+        uri_template: String,
+        request: CompleteRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CompletionInfo, McpError> {
+        if uri_template != "file:///{path}" || request.argument.name != "path" {
+            return Err(McpError::invalid_params("unknown template variable", None));
+        }
+        let matches: Vec<_> = Self::FILES
+            .iter()
+            .filter(|file| file.starts_with(&request.argument.value))
+            .map(|file| file.to_string())
+            .collect();
+        Ok(CompletionInfo {
+            total: Some(matches.len() as u32),
+            has_more: Some(matches.len() > Self::MAX_VALUES),
+            values: matches.into_iter().take(Self::MAX_VALUES).collect(),
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_complete_resource_template_variable() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = FileCompleter.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let complete = |value: &str| {
+        client.complete(CompleteRequestParam {
+            r#ref: Reference::Resource(ResourceReference {
+                uri: "file:///{path}".to_string(),
+            }),
+            argument: ArgumentInfo {
+                name: "path".to_string(),
+                value: value.to_string(),
+            },
+            context: None,
+        })
+    };
+    let result = complete("src/").await?;
+    assert_eq!(result.completion.values, vec!["src/lib.rs", "src/main.rs"]);
+    assert_eq!(result.completion.total, Some(3));
+    assert_eq!(result.completion.has_more, Some(true));
+
+    let result = complete("READ").await?;
+    assert_eq!(result.completion.values, vec!["README.md"]);
+    assert_eq!(result.completion.has_more, Some(false));
+
+    // prompts aren't completed by this handler
+    let result = client
+        .complete(CompleteRequestParam {
+            r#ref: Reference::Prompt(PromptReference {
+                name: "travel".to_string(),
+            }),
+            argument: ArgumentInfo {
+                name: "city".to_string(),
+                value: "".to_string(),
+            },
+            context: None,
+        })
+        .await;
+    assert!(result.is_err());
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use rmcp::{
+    ServiceError,
+    model::ServerInfo,
+    service::{CloseReason, serve_directly},
+};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+#[tokio::test]
+async fn test_pending_request_fails_when_peer_drops() -> anyhow::Result<()> {
+    let (client_transport, server_transport) = tokio::io::duplex(4096);
+    let client = serve_directly((), client_transport, ServerInfo::default()).await?;
+
+    let peer = client.peer().clone();
+    let pending = tokio::spawn(async move { peer.list_all_tools().await });
+
+    // the request reaches the server, which goes away without answering
+    let mut server_transport = BufReader::new(server_transport);
+    let mut request = String::new();
+    server_transport.read_line(&mut request).await?;
+    assert!(request.contains("tools/list"), "{request}");
+    drop(server_transport);
+
+    let result = tokio::time::timeout(Duration::from_secs(1), pending).await??;
+    assert!(
+        matches!(result, Err(ServiceError::ConnectionClosed)),
+        "{result:?}"
+    );
+    let reason = client.waiting().await?;
+    assert!(matches!(reason, CloseReason::Closed), "{reason:?}");
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use std::sync::{Arc, Mutex};
+
+use rmcp::{
+    ServerHandler,
+    model::{ServerCapabilities, ServerInfo},
+    service::ConnectionState,
+    testing::TestClient,
+    tool,
+};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Default)]
+pub struct Server {
+    states: Arc<Mutex<Vec<ConnectionState>>>,
+}
+
+/// The calls made on one connection
+#[derive(Debug, Clone, Default)]
+struct Calls(u32);
+
+#[tool(tool_box)]
+impl Server {
+    #[tool(description = "Count the calls made on this connection")]
+    fn count(&self, state: ConnectionState) -> String {
+        let calls = state.update(|calls: &mut Calls| {
+            calls.0 += 1;
+            calls.0
+        });
+        if calls == 1 {
+            self.states.lock().unwrap().push(state);
+        }
+        calls.to_string()
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+async fn count(client: &TestClient<Server>) -> anyhow::Result<String> {
+    let result = client.call_tool("count", Value::Null).await?;
+    Ok(result.content[0].as_text().unwrap().text.clone())
+}
+
+#[tokio::test]
+async fn test_state_is_kept_per_connection() -> anyhow::Result<()> {
+    let server = Server::default();
+    let first = TestClient::new(server.clone()).await?;
+    let second = TestClient::new(server.clone()).await?;
+    assert_eq!(count(&first).await?, "1");
+    assert_eq!(count(&first).await?, "2");
+    assert_eq!(count(&second).await?, "1");
+    assert_eq!(count(&first).await?, "3");
+    assert_eq!(count(&second).await?, "2");
+
+    first.close().await?;
+    second.close().await?;
+    let states = server.states.lock().unwrap();
+    assert_eq!(states.len(), 2);
+    assert!(states.iter().all(|state| state.get::<Calls>().is_none()));
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler, ServiceError, ServiceExt,
+    model::{
+        CallToolRequestParam, CallToolResult, ClientCapabilities, ClientInfo, Content,
+        ContentNegotiation, ErrorCode,
+    },
+    service::RequestContext,
+};
+
+/// Answer every tool call with a text and an image
+#[derive(Clone)]
+struct Server {
+    policy: ContentNegotiation,
+}
+
+impl ServerHandler for Server {
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        Ok(CallToolResult::success(vec![
+            Content::text("a cat"),
+            Content::image("aGk=", "image/png"),
+        ]))
+    }
+
+    fn content_negotiation(&self) -> ContentNegotiation {
+        self.policy
+    }
+}
+
+async fn call_with_text_only_client(
+    policy: ContentNegotiation,
+) -> anyhow::Result<Result<CallToolResult, ServiceError>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let client_info = ClientInfo {
+        capabilities: ClientCapabilities::default().with_content_types(["text"]),
+        ..Default::default()
+    };
+    let (server, client) = tokio::try_join!(
+        Server { policy }.serve(server_transport),
+        client_info.serve(client_transport)
+    )?;
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "draw".into(),
+            arguments: None,
+        })
+        .await;
+    client.cancel().await?;
+    server.waiting().await?;
+    Ok(result)
+}
+
+#[tokio::test]
+async fn test_unsupported_content_is_rejected() -> anyhow::Result<()> {
+    let result = call_with_text_only_client(ContentNegotiation::Error).await?;
+    let Err(ServiceError::McpError(error)) = result else {
+        panic!("expected an error, got {result:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INVALID_REQUEST);
+    assert!(error.message.contains("image"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unsupported_content_is_dropped() -> anyhow::Result<()> {
+    let result = call_with_text_only_client(ContentNegotiation::Drop).await??;
+    assert_eq!(result.content, vec![Content::text("a cat")]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unsupported_content_is_replaced() -> anyhow::Result<()> {
+    let result = call_with_text_only_client(ContentNegotiation::Placeholder).await??;
+    assert_eq!(
+        result.content,
+        vec![
+            Content::text("a cat"),
+            Content::text("[image content not supported by the client]"),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_undeclared_content_types_accept_everything() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = Server {
+        policy: ContentNegotiation::Error,
+    };
+    let (server, client) =
+        tokio::try_join!(server.serve(server_transport), ().serve(client_transport))?;
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "draw".into(),
+            arguments: None,
+        })
+        .await?;
+    assert_eq!(result.content.len(), 2);
+    client.cancel().await?;
+    server.waiting().await?;
+    Ok(())
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::calculator::Calculator;
+use rmcp::{
+    RoleClient, RoleServer, ServiceExt,
+    service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage},
+    transport::Transport,
+};
+use tokio::sync::mpsc;
+
+pub struct ChannelTransport<R: ServiceRole> {
+    tx: mpsc::Sender<TxJsonRpcMessage<R>>,
+    rx: mpsc::Receiver<RxJsonRpcMessage<R>>,
+}
+
+impl<R: ServiceRole> Transport<R> for ChannelTransport<R> {
+    type Error = std::io::Error;
+
+    async fn send(&mut self, item: TxJsonRpcMessage<R>) -> Result<(), Self::Error> {
+        self.tx
+            .send(item)
+            .await
+            .map_err(|_| std::io::Error::from(std::io::ErrorKind::BrokenPipe))
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<R>> {
+        self.rx.recv().await
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        self.rx.close();
+        Ok(())
+    }
+}
+
+fn channel_pair() -> (ChannelTransport<RoleClient>, ChannelTransport<RoleServer>) {
+    let (client_tx, server_rx) = mpsc::channel(16);
+    let (server_tx, client_rx) = mpsc::channel(16);
+    (
+        ChannelTransport {
+            tx: client_tx,
+            rx: client_rx,
+        },
+        ChannelTransport {
+            tx: server_tx,
+            rx: server_rx,
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_custom_transport_handshake() -> anyhow::Result<()> {
+    let (client_transport, server_transport) = channel_pair();
+    tokio::spawn(async move {
+        let server = Calculator.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+    assert_eq!(
+        client.peer_info().instructions.as_deref(),
+        Some("A simple calculator")
+    );
+    let tools = client.list_all_tools().await?;
+    assert_eq!(tools.len(), 2);
+    client.cancel().await?;
+    Ok(())
+}
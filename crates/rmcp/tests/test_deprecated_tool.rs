@@ -0,0 +1,108 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use rmcp::{
+    ServerHandler, ServiceExt,
+    model::{CallToolRequestParam, ServerCapabilities, ServerInfo},
+    tool,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+#[tool(tool_box)]
+impl Server {
+    #[tool(description = "Say hello", deprecated = "use greet instead")]
+    async fn hello(&self) -> String {
+        "hello".to_string()
+    }
+
+    #[tool(description = "Greet")]
+    async fn greet(&self) -> String {
+        "hello".to_string()
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Collect the formatted logs
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl LogBuffer {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+#[tokio::test]
+async fn test_deprecated_tool() -> anyhow::Result<()> {
+    let logs = LogBuffer::default();
+    // the runtime of the test is single threaded, so the server task logs here too
+    let _guard = tracing::subscriber::set_default(
+        tracing_subscriber::fmt()
+            .with_writer({
+                let logs = logs.clone();
+                move || logs.clone()
+            })
+            .with_ansi(false)
+            .finish(),
+    );
+
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let tools = client.list_all_tools().await?;
+    let hello = tools.iter().find(|tool| tool.name == "hello").unwrap();
+    assert_eq!(hello.deprecation(), Some("use greet instead"));
+    let greet = tools.iter().find(|tool| tool.name == "greet").unwrap();
+    assert!(greet.annotations.is_none());
+
+    client
+        .call_tool(CallToolRequestParam {
+            name: "greet".into(),
+            arguments: None,
+        })
+        .await?;
+    assert!(!logs.contents().contains("deprecated tool called"));
+    client
+        .call_tool(CallToolRequestParam {
+            name: "hello".into(),
+            arguments: None,
+        })
+        .await?;
+    let logs = logs.contents();
+    assert!(
+        logs.contains("WARN") && logs.contains("deprecated tool called: use greet instead"),
+        "{logs}"
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use rmcp::tool;
+
+// a name only known once the code runs, the macro can't check it
+const SUM: &str = "sum";
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+#[tool(tool_box)]
+impl Server {
+    #[tool(name = SUM, description = "Add two numbers")]
+    fn add(&self) -> String {
+        "add".to_string()
+    }
+
+    #[tool(name = SUM, description = "Add two numbers, merged from another module")]
+    fn plus(&self) -> String {
+        "plus".to_string()
+    }
+}
+
+#[test]
+#[should_panic(expected = "duplicate tool name `sum`")]
+fn test_duplicate_tool_name_panics() {
+    Server::tool_box();
+}
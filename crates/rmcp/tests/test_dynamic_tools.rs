@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use rmcp::{
+    ClientHandler, Peer, RoleClient, ServiceExt,
+    handler::server::tool::DynamicToolRegistry,
+    model::{CallToolRequestParam, CallToolResult, Content, Tool, object},
+};
+use serde_json::json;
+use tokio::sync::Notify;
+
+#[derive(Clone)]
+struct ListWatcher {
+    changed: Arc<Notify>,
+}
+
+impl ClientHandler for ListWatcher {
+    async fn on_tool_list_changed(&self) {
+        self.changed.notify_one();
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        None
+    }
+
+    fn set_peer(&mut self, _peer: Peer<RoleClient>) {}
+}
+
+fn echo_tool() -> Tool {
+    Tool::new(
+        "echo",
+        "Echo the message",
+        object(json!({
+            "type": "object",
+            "properties": { "message": { "type": "string" } },
+        })),
+    )
+}
+
+#[tokio::test]
+async fn test_register_call_and_unregister_at_runtime() -> anyhow::Result<()> {
+    let registry = DynamicToolRegistry::new();
+    let changed = Arc::new(Notify::new());
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        registry.clone().serve(server_transport),
+        ListWatcher {
+            changed: changed.clone(),
+        }
+        .serve(client_transport)
+    )?;
+    assert!(client.list_all_tools().await?.is_empty());
+
+    registry
+        .register(echo_tool(), |arguments| async move {
+            let message = arguments
+                .get("message")
+                .and_then(|message| message.as_str())
+                .unwrap_or_default()
+                .to_owned();
+            Ok(CallToolResult::success(vec![Content::text(message)]))
+        })
+        .await;
+    changed.notified().await;
+    let tools = client.list_all_tools().await?;
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0].name, "echo");
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "echo".into(),
+            arguments: Some(object(json!({ "message": "hello" })).into()),
+        })
+        .await?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("hello")
+    );
+
+    assert!(registry.unregister("echo").await);
+    changed.notified().await;
+    assert!(client.list_all_tools().await?.is_empty());
+    assert!(
+        client
+            .call_tool(CallToolRequestParam {
+                name: "echo".into(),
+                arguments: None,
+            })
+            .await
+            .is_err()
+    );
+    assert!(!registry.unregister("echo").await);
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
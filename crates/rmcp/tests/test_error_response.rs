@@ -0,0 +1,55 @@
+use rmcp::{
+    model::{ClientJsonRpcMessage, ClientRequest, ErrorCode, PingRequest, ServerInfo},
+    service::serve_directly,
+};
+use serde_json::json;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+#[tokio::test]
+async fn test_error_response_keeps_code_message_and_data() -> anyhow::Result<()> {
+    let (client_transport, server_transport) = tokio::io::duplex(4096);
+    let client = serve_directly((), client_transport, ServerInfo::default()).await?;
+    let (server_read, mut server_write) = tokio::io::split(server_transport);
+    let mut server_read = BufReader::new(server_read);
+
+    let pending = tokio::spawn({
+        let peer = client.peer().clone();
+        async move {
+            peer.send_request(ClientRequest::PingRequest(PingRequest {
+                method: Default::default(),
+                extensions: Default::default(),
+            }))
+            .await
+        }
+    });
+    let mut line = String::new();
+    server_read.read_line(&mut line).await?;
+    let (_, id) = serde_json::from_str::<ClientJsonRpcMessage>(&line)?
+        .into_request()
+        .expect("expect a request");
+    let data = json!({
+        "retryAfter": 30,
+        "quota": { "used": 100, "limit": 100 },
+        "reasons": ["rate limited", null],
+    });
+    let response = json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": -32001, "message": "quota exceeded", "data": data },
+    });
+    server_write
+        .write_all(format!("{response}\n").as_bytes())
+        .await?;
+
+    let error = pending
+        .await?
+        .expect_err("expect an error response")
+        .into_mcp_error()
+        .expect("expect the error of the peer");
+    assert_eq!(error.code, ErrorCode(-32001));
+    assert_eq!(error.message, "quota exceeded");
+    assert_eq!(error.data, Some(data));
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use rmcp::{
+    ServerHandler,
+    handler::server::tool::schema_for_type,
+    model::{ServerCapabilities, ServerInfo},
+    schemars::{self, JsonSchema, r#gen::SchemaGenerator, schema::Schema},
+    testing::TestClient,
+    tool,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Location {
+    pub city: String,
+    pub country: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct Page {
+    pub offset: u32,
+    pub limit: u32,
+}
+
+/// A wrapper whose schema refers to the definition of the page instead of inlining it
+#[derive(Debug, Deserialize)]
+#[serde(transparent)]
+pub struct PageRef(pub Page);
+
+impl JsonSchema for PageRef {
+    fn schema_name() -> String {
+        "PageRef".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        generator.subschema_for::<Page>()
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SearchRequest {
+    pub query: String,
+    #[serde(flatten)]
+    pub location: Location,
+    #[serde(flatten)]
+    pub page: PageRef,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Search;
+
+#[tool(tool_box)]
+impl Search {
+    #[tool(description = "Search near a location")]
+    fn search(&self, #[tool(aggr)] request: SearchRequest) -> String {
+        format!(
+            "{} in {} from {}",
+            request.query, request.location.city, request.page.0.offset
+        )
+    }
+
+    #[tool(description = "Describe a location")]
+    fn describe(
+        &self,
+        #[tool(param)] detailed: bool,
+        #[tool(param)]
+        #[serde(flatten)]
+        location: Location,
+    ) -> String {
+        match (detailed, location.country) {
+            (true, Some(country)) => format!("{}, {country}", location.city),
+            _ => location.city,
+        }
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Search {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+fn property_names(schema: &serde_json::Map<String, Value>) -> Vec<&str> {
+    let mut names = schema["properties"]
+        .as_object()
+        .expect("properties")
+        .keys()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    names.sort_unstable();
+    names
+}
+
+fn required_names(schema: &serde_json::Map<String, Value>) -> Vec<&str> {
+    let mut names = schema["required"]
+        .as_array()
+        .expect("required")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect::<Vec<_>>();
+    names.sort_unstable();
+    names
+}
+
+#[test]
+fn test_flattened_fields_at_top_level() {
+    let schema = schema_for_type::<SearchRequest>();
+    assert_eq!(
+        property_names(&schema),
+        ["city", "country", "limit", "offset", "query"]
+    );
+    assert_eq!(
+        required_names(&schema),
+        ["city", "limit", "offset", "query"]
+    );
+    assert!(schema.get("$ref").is_none());
+    assert!(schema.get("allOf").is_none());
+}
+
+#[tokio::test]
+async fn test_tool_with_flattened_arguments() -> anyhow::Result<()> {
+    let client = TestClient::new(Search).await?;
+    let tools = client.list_tools().await?;
+    let describe = tools
+        .iter()
+        .find(|tool| tool.name == "describe")
+        .expect("describe tool");
+    assert_eq!(
+        property_names(&describe.input_schema),
+        ["city", "country", "detailed"]
+    );
+    assert_eq!(required_names(&describe.input_schema), ["city", "detailed"]);
+
+    let result = client
+        .call_tool(
+            "search",
+            json!({ "query": "coffee", "city": "Lyon", "offset": 20, "limit": 10 }),
+        )
+        .await?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("coffee in Lyon from 20")
+    );
+    let result = client
+        .call_tool(
+            "describe",
+            json!({ "detailed": true, "city": "Lyon", "country": "France" }),
+        )
+        .await?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("Lyon, France")
+    );
+    client.close().await?;
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use rmcp::{
+    ServerHandler, ServiceError, ServiceExt,
+    model::{CallToolRequestParam, ErrorCode, ServerCapabilities, ServerInfo},
+    tool,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+#[tool(tool_box)]
+impl Server {
+    #[tool(description = "Always panic")]
+    async fn panic(&self) -> String {
+        panic!("this tool always panics")
+    }
+
+    #[tool(description = "Say hello")]
+    async fn hello(&self) -> String {
+        "hello".to_string()
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_handler_panic_is_internal_error() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "panic".into(),
+            arguments: None,
+        })
+        .await;
+    let Err(ServiceError::McpError(error)) = result else {
+        panic!("expect an internal error, got {result:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+    assert!(error.message.contains("this tool always panics"));
+
+    // the connection survives the panic
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "hello".into(),
+            arguments: None,
+        })
+        .await?;
+    assert_eq!(
+        result.content[0].as_text().map(|t| t.text.as_str()),
+        Some("hello")
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,353 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use common::calculator::Calculator;
+use rmcp::{
+    ClientHandler, Error as McpError, Peer, RoleClient, ServerHandler, ServiceError, ServiceExt,
+    model::{
+        ClientInfo, ClientRequest, Implementation, InitializeRequest, InitializeRequestParam,
+        InitializeResult, JsonRpcError, JsonRpcMessage, ProtocolVersion, ServerInfo,
+        ServerJsonRpcMessage,
+    },
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+#[tokio::test]
+async fn test_duplicate_initialize_rejected() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Calculator.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let result = client
+        .send_request(ClientRequest::InitializeRequest(InitializeRequest {
+            method: Default::default(),
+            params: ClientInfo::default(),
+            extensions: Default::default(),
+        }))
+        .await;
+    let Err(ServiceError::McpError(error)) = result else {
+        panic!("expect the second initialize to be rejected, got {result:?}");
+    };
+    assert_eq!(error, McpError::already_initialized());
+
+    // the session is still usable
+    let tools = client.list_all_tools().await?;
+    assert_eq!(tools.len(), 2);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_request_before_initialize_rejected() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Calculator.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let (read, mut write) = tokio::io::split(client_transport);
+    let mut read = BufReader::new(read);
+
+    let mut line = String::new();
+    write
+        .write_all(
+            br#"{"jsonrpc":"2.0","id":"early","method":"tools/call","params":{"name":"sum","arguments":{"a":1,"b":2}}}"#,
+        )
+        .await?;
+    write.write_all(b"\n").await?;
+    read.read_line(&mut line).await?;
+    let JsonRpcMessage::Error(JsonRpcError { error, .. }) =
+        serde_json::from_str::<ServerJsonRpcMessage>(&line)?
+    else {
+        panic!("expect the tool call to be rejected, got {line}");
+    };
+    assert_eq!(error, McpError::not_initialized());
+
+    // ping is allowed before initialize
+    line.clear();
+    write
+        .write_all(br#"{"jsonrpc":"2.0","id":"ping","method":"ping"}"#)
+        .await?;
+    write.write_all(b"\n").await?;
+    read.read_line(&mut line).await?;
+    let message = serde_json::from_str::<ServerJsonRpcMessage>(&line)?;
+    assert!(message.into_response().is_some(), "{line}");
+
+    // and the handshake can still happen
+    let client = ().serve((read, write)).await?;
+    let tools = client.list_all_tools().await?;
+    assert_eq!(tools.len(), 2);
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_request_pipelined_after_initialize() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Calculator.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let (read, mut write) = tokio::io::split(client_transport);
+    let mut lines = BufReader::new(read).lines();
+
+    // the tool call doesn't wait for the initialize response
+    write
+        .write_all(
+            concat!(
+                r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"protocolVersion":"2025-03-26","capabilities":{},"clientInfo":{"name":"fast","version":"1.0.0"}}}"#,
+                "\n",
+                r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"sum","arguments":{"a":1,"b":2}}}"#,
+                "\n",
+                r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+                "\n",
+            )
+            .as_bytes(),
+        )
+        .await?;
+
+    let line = lines
+        .next_line()
+        .await?
+        .expect("expect the initialize response");
+    let response = serde_json::from_str::<serde_json::Value>(&line)?;
+    assert_eq!(response["id"], 0, "{line}");
+    assert!(response["result"]["serverInfo"].is_object(), "{line}");
+    // the tool call is processed after the handshake
+    let line = lines
+        .next_line()
+        .await?
+        .expect("expect the tool call response");
+    let response = serde_json::from_str::<serde_json::Value>(&line)?;
+    assert_eq!(response["id"], 1, "{line}");
+    assert_eq!(response["result"]["content"][0]["text"], "3", "{line}");
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default)]
+struct InitializedRecorder {
+    server_infos: Arc<Mutex<Vec<ServerInfo>>>,
+    peer: Option<Peer<RoleClient>>,
+}
+
+impl ClientHandler for InitializedRecorder {
+    fn on_initialized(&self, server_info: &ServerInfo) {
+        self.server_infos.lock().unwrap().push(server_info.clone());
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
+}
+
+#[tokio::test]
+async fn test_client_on_initialized() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Calculator.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let recorder = InitializedRecorder::default();
+    let server_infos = recorder.server_infos.clone();
+    let client = recorder.serve(client_transport).await?;
+    // make sure the session is running
+    client.list_all_tools().await?;
+
+    let server_infos = server_infos.lock().unwrap().clone();
+    assert_eq!(server_infos.len(), 1);
+    assert_eq!(
+        server_infos[0].instructions.as_deref(),
+        Some("A simple calculator")
+    );
+    assert!(server_infos[0].capabilities.tools.is_some());
+    assert_eq!(&server_infos[0], client.peer_info());
+
+    client.cancel().await?;
+    Ok(())
+}
+
+/// Refuse the clients on a denylist, and greet the others by their name
+struct DenylistServer;
+
+impl DenylistServer {
+    const DENYLIST: &[&str] = &["legacy-client"];
+}
+
+impl ServerHandler for DenylistServer {
+    fn on_initialize(&self, params: &InitializeRequestParam) -> Result<InitializeResult, McpError> {
+        let name = &params.client_info.name;
+        if Self::DENYLIST.contains(&name.as_str()) {
+            return Err(McpError::invalid_request(
+                format!("client {name} is not supported"),
+                None,
+            ));
+        }
+        Ok(ServerInfo {
+            instructions: Some(format!("Hello, {name}")),
+            ..self.get_info()
+        })
+    }
+}
+
+fn client_info(name: &str) -> ClientInfo {
+    ClientInfo {
+        client_info: Implementation {
+            name: name.into(),
+            version: "1.0.0".into(),
+        },
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_on_initialize_rejects_denylisted_client() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::join!(
+        DenylistServer.serve(server_transport),
+        client_info("legacy-client").serve(client_transport)
+    );
+    let Err(error) = server else {
+        panic!("expect the server to refuse the client");
+    };
+    assert!(
+        error
+            .to_string()
+            .contains("client legacy-client is not supported"),
+        "{error}"
+    );
+    assert!(client.is_err(), "expect the client initialization to fail");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_on_initialize_tailors_the_response() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        DenylistServer.serve(server_transport),
+        client_info("inspector").serve(client_transport)
+    )?;
+    assert_eq!(
+        client.peer_info().instructions.as_deref(),
+        Some("Hello, inspector")
+    );
+    assert_eq!(server.peer_info().client_info.name, "inspector");
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unsupported_client_requests() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        Calculator.serve(server_transport),
+        ().serve(client_transport)
+    )?;
+
+    // the calculator only grants tools
+    let unsupported = client.peer_info().unsupported_client_requests(&[
+        "tools/list",
+        "tools/call",
+        "prompts/get",
+        "resources/read",
+    ]);
+    assert_eq!(unsupported, ["prompts/get", "resources/read"]);
+    assert!(
+        client
+            .peer_info()
+            .unsupported_client_requests(&["ping", "tools/call"])
+            .is_empty()
+    );
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+/// Only speaks the first protocol version
+struct OldServer;
+
+impl ServerHandler for OldServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            protocol_version: ProtocolVersion::V_2024_11_05,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Default)]
+struct VersionWatcher {
+    negotiated: Arc<Mutex<Vec<(ProtocolVersion, ProtocolVersion)>>>,
+}
+
+impl ClientHandler for VersionWatcher {
+    fn on_version_negotiated(&self, requested: &ProtocolVersion, negotiated: &ProtocolVersion) {
+        self.negotiated
+            .lock()
+            .unwrap()
+            .push((requested.clone(), negotiated.clone()));
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        None
+    }
+
+    fn set_peer(&mut self, _peer: Peer<RoleClient>) {}
+}
+
+#[tokio::test]
+async fn test_protocol_downgrade() -> anyhow::Result<()> {
+    let watcher = VersionWatcher::default();
+    let negotiated = watcher.negotiated.clone();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        OldServer.serve(server_transport),
+        watcher.serve(client_transport)
+    )?;
+
+    assert_eq!(
+        client.peer().protocol_version(),
+        &ProtocolVersion::V_2024_11_05
+    );
+    assert_eq!(
+        *negotiated.lock().unwrap(),
+        [(ProtocolVersion::LATEST, ProtocolVersion::V_2024_11_05)]
+    );
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_same_protocol_version_is_not_a_downgrade() -> anyhow::Result<()> {
+    let watcher = VersionWatcher::default();
+    let negotiated = watcher.negotiated.clone();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        Calculator.serve(server_transport),
+        watcher.serve(client_transport)
+    )?;
+
+    assert_eq!(client.peer().protocol_version(), &ProtocolVersion::LATEST);
+    assert!(negotiated.lock().unwrap().is_empty());
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use rmcp::{
+    ClientHandler, Peer, RoleClient, ServerHandler, ServiceExt,
+    model::{InstructionsChangedNotificationParam, ServerInfo},
+};
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            instructions: Some("Answer in English".into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Forward the new instructions
+pub struct InstructionsClient {
+    instructions_tx: mpsc::UnboundedSender<Option<String>>,
+}
+
+impl ClientHandler for InstructionsClient {
+    async fn on_instructions_changed(&self, params: InstructionsChangedNotificationParam) {
+        let _ = self.instructions_tx.send(params.instructions);
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        None
+    }
+
+    fn set_peer(&mut self, _peer: Peer<RoleClient>) {}
+}
+
+#[tokio::test]
+async fn test_instructions_changed_notification() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (instructions_tx, mut instructions_rx) = mpsc::unbounded_channel();
+    let (server, client) = tokio::try_join!(
+        Server.serve(server_transport),
+        InstructionsClient { instructions_tx }.serve(client_transport)
+    )?;
+    assert_eq!(
+        client.peer_info().instructions.as_deref(),
+        Some("Answer in English")
+    );
+
+    server
+        .notify_instructions_changed(Some("Answer in French".into()))
+        .await?;
+    assert_eq!(
+        instructions_rx.recv().await,
+        Some(Some("Answer in French".into()))
+    );
+    server.notify_instructions_changed(None).await?;
+    assert_eq!(instructions_rx.recv().await, Some(None));
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::calculator::Calculator;
+use rmcp::{
+    RoleServer,
+    model::{
+        CallToolRequest, CallToolRequestParam, ClientInfo, ClientRequest, ServerResult, object,
+    },
+    service::serve_directly,
+    testing::{NullTransport, invoke},
+};
+use serde_json::json;
+
+fn sum_request(a: i32, b: i32) -> ClientRequest {
+    ClientRequest::CallToolRequest(CallToolRequest {
+        method: Default::default(),
+        params: CallToolRequestParam {
+            name: "sum".into(),
+            arguments: Some(object(json!({ "a": a, "b": b })).into()),
+        },
+        extensions: Default::default(),
+    })
+}
+
+#[tokio::test]
+async fn test_invoke_returns_the_handler_result() -> anyhow::Result<()> {
+    let ServerResult::CallToolResult(result) = invoke(&Calculator, sum_request(1, 2)).await? else {
+        panic!("expect a tool result");
+    };
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("3")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invoke_returns_the_handler_error() -> anyhow::Result<()> {
+    let request = ClientRequest::CallToolRequest(CallToolRequest {
+        method: Default::default(),
+        params: CallToolRequestParam {
+            name: "missing".into(),
+            arguments: None,
+        },
+        extensions: Default::default(),
+    });
+    assert!(invoke(&Calculator, request).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_serve_over_null_transport() -> anyhow::Result<()> {
+    let server =
+        serve_directly::<RoleServer, _, _, _, _>(Calculator, NullTransport, ClientInfo::default())
+            .await?;
+    assert!(!server.peer().is_closed());
+    server.cancel().await?;
+    Ok(())
+}
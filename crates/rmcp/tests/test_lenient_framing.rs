@@ -0,0 +1,57 @@
+mod common;
+
+use common::calculator::Calculator;
+use rmcp::{
+    model::{ClientInfo, NumberOrString, ServerJsonRpcMessage},
+    service::{CloseReason, serve_directly},
+    transport::io::AsyncRwTransport,
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+const PING: &[u8] = br#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+const ANOTHER_PING: &[u8] = br#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#;
+
+#[tokio::test]
+async fn test_lenient_mode_skips_malformed_line() -> anyhow::Result<()> {
+    let (server_read, mut client_write) = tokio::io::duplex(4096);
+    let (server_write, client_read) = tokio::io::duplex(4096);
+    let transport = AsyncRwTransport::new(server_read, server_write).lenient(true);
+    let server = serve_directly(Calculator, transport, ClientInfo::default()).await?;
+
+    let mut client_read = BufReader::new(client_read);
+    client_write.write_all(PING).await?;
+    client_write.write_all(b"\nthis is not json\n").await?;
+    client_write.write_all(ANOTHER_PING).await?;
+    client_write.write_all(b"\n").await?;
+
+    for expected_id in [1, 2] {
+        let mut line = String::new();
+        client_read.read_line(&mut line).await?;
+        let message = serde_json::from_str::<ServerJsonRpcMessage>(&line)?;
+        let (_, id) = message.into_response().expect("expect a ping response");
+        assert_eq!(id, NumberOrString::Number(expected_id));
+    }
+
+    drop(client_write);
+    let reason = server.waiting().await?;
+    assert!(matches!(reason, CloseReason::Closed), "{reason:?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_strict_mode_closes_on_malformed_line() -> anyhow::Result<()> {
+    let (server_read, mut client_write) = tokio::io::duplex(4096);
+    let (server_write, _client_read) = tokio::io::duplex(4096);
+    let transport = AsyncRwTransport::new(server_read, server_write);
+    let server = serve_directly(Calculator, transport, ClientInfo::default()).await?;
+
+    client_write.write_all(b"this is not json\n").await?;
+    client_write.write_all(PING).await?;
+    client_write.write_all(b"\n").await?;
+    let reason = server.waiting().await?;
+    assert!(
+        matches!(reason, CloseReason::ProtocolViolation(_)),
+        "{reason:?}"
+    );
+    Ok(())
+}
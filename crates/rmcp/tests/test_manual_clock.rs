@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler, ServiceError,
+    model::{ClientRequest, PingRequest},
+    service::{PeerRequestOptions, RequestContext},
+    testing::{ManualClock, TestClient},
+};
+
+/// Never answer a ping
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+impl ServerHandler for Server {
+    async fn ping(&self, _context: RequestContext<RoleServer>) -> Result<(), McpError> {
+        std::future::pending().await
+    }
+}
+
+#[tokio::test]
+async fn test_timeout_with_manual_clock() -> anyhow::Result<()> {
+    const TIMEOUT: Duration = Duration::from_secs(3600);
+    let client = TestClient::new(Server).await?;
+    let clock = ManualClock::new();
+    let peer = client.peer().clone().with_clock(clock.clone());
+    let request = peer
+        .send_request_with_option(
+            ClientRequest::PingRequest(PingRequest {
+                method: Default::default(),
+                extensions: Default::default(),
+            }),
+            PeerRequestOptions {
+                timeout: Some(TIMEOUT),
+                meta: None,
+            },
+        )
+        .await?;
+    let response = tokio::spawn(request.await_response());
+    while clock.sleepers() == 0 {
+        tokio::task::yield_now().await;
+    }
+
+    clock.advance(TIMEOUT / 2);
+    tokio::task::yield_now().await;
+    assert!(!response.is_finished());
+
+    clock.advance(TIMEOUT / 2);
+    let result = response.await?;
+    assert!(
+        matches!(result, Err(ServiceError::Timeout { timeout }) if timeout == TIMEOUT),
+        "{result:?}"
+    );
+    client.close().await?;
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use futures::{SinkExt, StreamExt};
+use rmcp::{
+    RoleClient, RoleServer, ServerHandler, ServiceExt,
+    model::{
+        CallToolRequestParam, CallToolResult, Content, RequestId, ServerCapabilities, ServerInfo,
+        ServerJsonRpcMessage, ServerResult,
+    },
+    service::RequestContext,
+    transport::{
+        IntoTransport,
+        io::{from_async_read, from_async_write},
+        mux::{Multiplexer, MuxFrame},
+    },
+};
+
+/// Answer every tool call with the id of the request
+pub struct RequestIdServer;
+
+impl ServerHandler for RequestIdServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        Ok(CallToolResult::success(vec![Content::text(
+            context.id.to_string(),
+        )]))
+    }
+}
+
+#[tokio::test]
+async fn test_two_sessions_over_one_transport() -> anyhow::Result<()> {
+    let (client_upstream, server_upstream) = tokio::io::duplex(4096);
+    let (client_read, client_write) = tokio::io::split(client_upstream);
+    let (server_read, server_write) = tokio::io::split(server_upstream);
+
+    let mut server_mux = Multiplexer::<RoleServer>::new(
+        from_async_write(server_write),
+        from_async_read(server_read).map(Ok),
+    );
+    tokio::spawn(async move {
+        while let Some(session) = server_mux.accept().await {
+            tokio::spawn(async move {
+                let server = RequestIdServer.serve(session).await?;
+                server.waiting().await?;
+                anyhow::Ok(())
+            });
+        }
+    });
+
+    let client_mux = Multiplexer::<RoleClient>::new(
+        from_async_write(client_write),
+        from_async_read(client_read).map(Ok),
+    );
+    let first = ().serve(client_mux.open("first")).await?;
+    let second = ().serve(client_mux.open("second")).await?;
+
+    let call = CallToolRequestParam {
+        name: "request_id".into(),
+        arguments: None,
+    };
+    let first_id = first.call_tool(call.clone()).await?;
+    let second_id = second.call_tool(call).await?;
+    // each session has its own request id space, the initialize request took the first id
+    assert_eq!(first_id.content[0].as_text().unwrap().text, "1");
+    assert_eq!(second_id.content[0].as_text().unwrap().text, "1");
+
+    first.cancel().await?;
+    // the other session is not affected
+    second.list_all_tools().await?;
+    second.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_upstream_error_closes_the_sessions() -> anyhow::Result<()> {
+    let (upstream_tx, upstream_rx) = futures::channel::mpsc::unbounded();
+    let client_mux = Multiplexer::<RoleClient>::new(
+        futures::sink::drain().sink_map_err(|never| match never {}),
+        upstream_rx,
+    );
+    let (_sink, stream) =
+        IntoTransport::<RoleClient, std::io::Error, ()>::into_transport(client_mux.open("first"));
+    let mut stream = std::pin::pin!(stream);
+
+    // a client never accepts sessions, the frame is dropped
+    let response = ServerJsonRpcMessage::response(
+        ServerResult::InitializeResult(ServerInfo::default()),
+        RequestId::Number(1),
+    );
+    upstream_tx.unbounded_send(Ok(MuxFrame {
+        session: "unknown".into(),
+        message: response,
+    }))?;
+    upstream_tx.unbounded_send(Err(std::io::Error::new(
+        std::io::ErrorKind::ConnectionReset,
+        "upstream reset",
+    )))?;
+
+    let error = stream
+        .next()
+        .await
+        .expect("expect the upstream error")
+        .expect_err("expect the upstream error");
+    assert_eq!(error.kind(), std::io::ErrorKind::ConnectionReset);
+    assert!(stream.next().await.is_none());
+    Ok(())
+}
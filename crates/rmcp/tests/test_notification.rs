@@ -1,12 +1,15 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use rmcp::{
-    ClientHandler, Peer, RoleClient, ServerHandler, ServiceExt,
+    ClientHandler, Peer, RoleClient, RoleServer, ServerHandler, ServiceExt,
     model::{
-        ResourceUpdatedNotificationParam, ServerCapabilities, ServerInfo, SubscribeRequestParam,
+        AnnotateAble, JsonObject, ListPromptsResult, ListResourcesResult, ListToolsResult,
+        PaginatedRequestParam, Prompt, RawResource, Resource, ResourceUpdatedNotificationParam,
+        ServerCapabilities, ServerInfo, SubscribeRequestParam, Tool,
     },
+    service::{RequestContext, RunningService},
 };
-use tokio::sync::Notify;
+use tokio::sync::{Notify, mpsc};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 pub struct Server {}
@@ -99,3 +102,201 @@ async fn test_server_notification() -> anyhow::Result<()> {
     client.cancel().await?;
     Ok(())
 }
+
+#[derive(Clone, Default)]
+pub struct DynamicServer {
+    resources: Arc<Mutex<Vec<Resource>>>,
+    prompts: Arc<Mutex<Vec<Prompt>>>,
+    tools: Arc<Mutex<Vec<Tool>>>,
+}
+
+impl ServerHandler for DynamicServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_prompts()
+                .enable_resources()
+                .enable_tools()
+                .enable_prompts_list_changed()
+                .enable_resources_list_changed()
+                .enable_tool_list_changed()
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, rmcp::Error> {
+        Ok(ListResourcesResult {
+            resources: self.resources.lock().unwrap().clone(),
+            next_cursor: None,
+        })
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, rmcp::Error> {
+        Ok(ListPromptsResult {
+            prompts: self.prompts.lock().unwrap().clone(),
+            next_cursor: None,
+        })
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, rmcp::Error> {
+        Ok(ListToolsResult {
+            tools: self.tools.lock().unwrap().clone(),
+            next_cursor: None,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ListChanged {
+    Resources(Vec<String>),
+    Prompts(Vec<String>),
+    Tools(Vec<String>),
+}
+
+/// Re-fetch the list whenever the server says it changed
+pub struct ListChangedClient {
+    changed_tx: mpsc::UnboundedSender<ListChanged>,
+    peer: Option<Peer<RoleClient>>,
+}
+
+impl ListChangedClient {
+    fn peer(&self) -> Peer<RoleClient> {
+        self.peer.clone().expect("peer should be set")
+    }
+}
+
+impl ClientHandler for ListChangedClient {
+    async fn on_resource_list_changed(&self) {
+        let resources = self.peer().list_all_resources().await.unwrap();
+        let uris = resources.into_iter().map(|r| r.raw.uri).collect();
+        let _ = self.changed_tx.send(ListChanged::Resources(uris));
+    }
+
+    async fn on_prompt_list_changed(&self) {
+        let prompts = self.peer().list_all_prompts().await.unwrap();
+        let names = prompts.into_iter().map(|p| p.name).collect();
+        let _ = self.changed_tx.send(ListChanged::Prompts(names));
+    }
+
+    async fn on_tool_list_changed(&self) {
+        let tools = self.peer().list_all_tools().await.unwrap();
+        let names = tools.into_iter().map(|t| t.name.into_owned()).collect();
+        let _ = self.changed_tx.send(ListChanged::Tools(names));
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer.replace(peer);
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+}
+
+async fn serve_dynamic() -> anyhow::Result<(
+    RunningService<RoleServer, DynamicServer>,
+    RunningService<RoleClient, ListChangedClient>,
+    mpsc::UnboundedReceiver<ListChanged>,
+)> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (changed_tx, changed_rx) = mpsc::unbounded_channel();
+    let client = ListChangedClient {
+        changed_tx,
+        peer: None,
+    };
+    let (server, client) = tokio::try_join!(
+        DynamicServer::default().serve(server_transport),
+        client.serve(client_transport)
+    )?;
+    Ok((server, client, changed_rx))
+}
+
+#[tokio::test]
+async fn test_resource_list_changed_notification() -> anyhow::Result<()> {
+    let (server, client, mut changed_rx) = serve_dynamic().await?;
+    let capabilities = &client.peer_info().capabilities;
+    assert_eq!(
+        capabilities.resources.as_ref().unwrap().list_changed,
+        Some(true)
+    );
+
+    server
+        .service()
+        .resources
+        .lock()
+        .unwrap()
+        .push(RawResource::new("test://new-resource", "new resource").no_annotation());
+    server.notify_resource_list_changed().await?;
+
+    assert_eq!(
+        changed_rx.recv().await,
+        Some(ListChanged::Resources(vec!["test://new-resource".into()]))
+    );
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_prompt_list_changed_notification() -> anyhow::Result<()> {
+    let (server, client, mut changed_rx) = serve_dynamic().await?;
+    let capabilities = &client.peer_info().capabilities;
+    assert_eq!(
+        capabilities.prompts.as_ref().unwrap().list_changed,
+        Some(true)
+    );
+
+    server
+        .service()
+        .prompts
+        .lock()
+        .unwrap()
+        .push(Prompt::new("new-prompt", None::<String>, None));
+    server.notify_prompt_list_changed().await?;
+
+    assert_eq!(
+        changed_rx.recv().await,
+        Some(ListChanged::Prompts(vec!["new-prompt".into()]))
+    );
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tool_list_changed_notification() -> anyhow::Result<()> {
+    let (server, client, mut changed_rx) = serve_dynamic().await?;
+    let capabilities = &client.peer_info().capabilities;
+    assert_eq!(
+        capabilities.tools.as_ref().unwrap().list_changed,
+        Some(true)
+    );
+
+    server.service().tools.lock().unwrap().push(Tool::new(
+        "new-tool",
+        "a tool added at runtime",
+        JsonObject::default(),
+    ));
+    server.notify_tool_list_changed().await?;
+
+    assert_eq!(
+        changed_rx.recv().await,
+        Some(ListChanged::Tools(vec!["new-tool".into()]))
+    );
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
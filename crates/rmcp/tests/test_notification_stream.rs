@@ -0,0 +1,60 @@
+use futures::StreamExt;
+use rmcp::{
+    ServerHandler, ServiceExt,
+    model::{
+        LoggingLevel, LoggingMessageNotificationParam, NumberOrString, ProgressNotificationParam,
+        ProgressToken, ServerNotification,
+    },
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+impl ServerHandler for Server {}
+
+#[tokio::test]
+async fn test_notification_stream() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) =
+        tokio::try_join!(Server.serve(server_transport), ().serve(client_transport))?;
+    let mut notifications = client.peer().notifications();
+
+    for progress in 1..=3 {
+        server
+            .notify_progress(ProgressNotificationParam {
+                progress_token: ProgressToken(NumberOrString::Number(1)),
+                progress,
+                total: Some(3),
+                message: None,
+            })
+            .await?;
+    }
+    server
+        .notify_logging_message(LoggingMessageNotificationParam {
+            level: LoggingLevel::Info,
+            logger: None,
+            data: serde_json::json!("done"),
+        })
+        .await?;
+
+    for expected in 1..=3 {
+        let Some(ServerNotification::ProgressNotification(notification)) =
+            notifications.next().await
+        else {
+            panic!("expect a progress notification");
+        };
+        assert_eq!(notification.params.progress, expected);
+    }
+    let Some(ServerNotification::LoggingMessageNotification(notification)) =
+        notifications.next().await
+    else {
+        panic!("expect a logging notification");
+    };
+    assert_eq!(notification.params.data, "done");
+
+    // the stream ends with the service
+    server.cancel().await?;
+    client.waiting().await?;
+    assert!(notifications.next().await.is_none());
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::sync::{Arc, Mutex};
+
+use rmcp::{
+    ClientHandler, Peer, RoleClient,
+    model::{
+        ClientInfo, ClientJsonRpcMessage, ClientRequest, NumberOrString, PingRequest, RequestId,
+        ServerInfo,
+    },
+    service::serve_directly,
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// Record the ids of the orphan responses
+#[derive(Clone, Default)]
+struct OrphanRecorder {
+    orphans: Arc<Mutex<Vec<RequestId>>>,
+}
+
+impl ClientHandler for OrphanRecorder {
+    fn on_orphan_response(&self, id: &RequestId) {
+        self.orphans.lock().unwrap().push(id.clone());
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        None
+    }
+
+    fn set_peer(&mut self, _peer: Peer<RoleClient>) {}
+
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo::default()
+    }
+}
+
+#[tokio::test]
+async fn test_orphan_response_is_ignored() -> anyhow::Result<()> {
+    let (client_transport, server_transport) = tokio::io::duplex(4096);
+    let recorder = OrphanRecorder::default();
+    let client = serve_directly(recorder.clone(), client_transport, ServerInfo::default()).await?;
+    let (server_read, mut server_write) = tokio::io::split(server_transport);
+    let mut server_read = BufReader::new(server_read);
+
+    server_write
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":42,\"result\":{}}\n")
+        .await?;
+    server_write
+        .write_all(
+            b"{\"jsonrpc\":\"2.0\",\"id\":\"ghost\",\"error\":{\"code\":-32603,\"message\":\"late\"}}\n",
+        )
+        .await?;
+
+    // the connection is still healthy
+    let pending = tokio::spawn({
+        let peer = client.peer().clone();
+        async move {
+            peer.send_request(ClientRequest::PingRequest(PingRequest {
+                method: Default::default(),
+                extensions: Default::default(),
+            }))
+            .await
+        }
+    });
+    let mut line = String::new();
+    server_read.read_line(&mut line).await?;
+    let (_, id) = serde_json::from_str::<ClientJsonRpcMessage>(&line)?
+        .into_request()
+        .expect("expect a request");
+    server_write
+        .write_all(
+            serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": {} })
+                .to_string()
+                .as_bytes(),
+        )
+        .await?;
+    server_write.write_all(b"\n").await?;
+    pending.await??;
+
+    assert_eq!(
+        *recorder.orphans.lock().unwrap(),
+        vec![
+            NumberOrString::Number(42),
+            NumberOrString::String("ghost".into())
+        ]
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use rmcp::{
+    RoleServer, ServerHandler, ServiceExt,
+    model::{ListToolsResult, PaginatedRequestParam, ServerCapabilities, ServerInfo},
+    service::{RequestContext, RequestHandle},
+};
+use tokio::sync::mpsc;
+
+/// A server whose second page of tools never comes, it reports when it's requested and
+/// why it was cancelled.
+pub struct Server {
+    second_page: mpsc::UnboundedSender<()>,
+    cancelled: mpsc::UnboundedSender<Option<String>>,
+}
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, rmcp::Error> {
+        if request.and_then(|request| request.cursor).is_none() {
+            return Ok(ListToolsResult {
+                next_cursor: Some("2".into()),
+                tools: vec![],
+            });
+        }
+        let _ = self.second_page.send(());
+        context.ct.cancelled().await;
+        let _ = self
+            .cancelled
+            .send(context.cancellation_reason().map(str::to_owned));
+        Err(rmcp::Error::internal_error("cancelled", None))
+    }
+}
+
+#[tokio::test]
+async fn test_drop_list_all_cancels_the_current_page() -> anyhow::Result<()> {
+    let (second_page, mut second_page_rx) = mpsc::unbounded_channel();
+    let (cancelled, mut cancelled_rx) = mpsc::unbounded_channel();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        Server {
+            second_page,
+            cancelled,
+        }
+        .serve(server_transport),
+        ().serve(client_transport)
+    )?;
+
+    // the listing is dropped once the server got the request of the second page
+    tokio::select! {
+        result = client.list_all_tools() => panic!("expect the listing to hang, got {result:?}"),
+        _ = second_page_rx.recv() => {}
+    }
+
+    let reason = tokio::time::timeout(Duration::from_secs(5), cancelled_rx.recv())
+        .await?
+        .expect("server stopped");
+    assert_eq!(
+        reason.as_deref(),
+        Some(RequestHandle::<rmcp::RoleClient>::REQUEST_DROPPED_REASON)
+    );
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use std::{num::NonZeroUsize, time::Duration};
+
+use rmcp::{
+    ServiceError,
+    model::{ClientRequest, PingRequest, ServerInfo},
+    service::{PeerRequestOptions, PendingRequestsMode, serve_directly},
+};
+
+fn ping() -> ClientRequest {
+    ClientRequest::PingRequest(PingRequest {
+        method: Default::default(),
+        extensions: Default::default(),
+    })
+}
+
+#[tokio::test]
+async fn test_max_pending_requests_reject() -> anyhow::Result<()> {
+    // nobody answers on the other side
+    let (client_transport, _server_transport) = tokio::io::duplex(4096);
+    let client = serve_directly((), client_transport, ServerInfo::default()).await?;
+    let peer = client
+        .peer()
+        .clone()
+        .with_max_pending_requests(NonZeroUsize::new(2).unwrap(), PendingRequestsMode::Reject);
+
+    let first = peer
+        .send_request_with_option(ping(), PeerRequestOptions::no_options())
+        .await?;
+    let _second = peer
+        .send_request_with_option(ping(), PeerRequestOptions::no_options())
+        .await?;
+    let result = peer
+        .clone()
+        .send_request_with_option(ping(), PeerRequestOptions::no_options())
+        .await;
+    assert!(
+        matches!(result, Err(ServiceError::TooManyPendingRequests { max: 2 })),
+        "{result:?}"
+    );
+
+    // giving up on a request frees its slot
+    drop(first);
+    peer.send_request_with_option(ping(), PeerRequestOptions::no_options())
+        .await?;
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_max_pending_requests_wait() -> anyhow::Result<()> {
+    let (client_transport, _server_transport) = tokio::io::duplex(4096);
+    let client = serve_directly((), client_transport, ServerInfo::default()).await?;
+    let peer = client
+        .peer()
+        .clone()
+        .with_max_pending_requests(NonZeroUsize::MIN, PendingRequestsMode::Wait);
+
+    let first = peer
+        .send_request_with_option(ping(), PeerRequestOptions::no_options())
+        .await?;
+    let waiting = tokio::spawn({
+        let peer = peer.clone();
+        async move {
+            peer.send_request_with_option(ping(), PeerRequestOptions::no_options())
+                .await
+                .map(drop)
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!waiting.is_finished());
+
+    drop(first);
+    tokio::time::timeout(Duration::from_secs(1), waiting).await???;
+
+    client.cancel().await?;
+    Ok(())
+}
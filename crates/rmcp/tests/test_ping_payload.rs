@@ -0,0 +1,66 @@
+mod common;
+
+use common::calculator::Calculator;
+use rmcp::{ServiceError, ServiceExt, model::ServerInfo, service::serve_directly};
+use serde_json::json;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+#[tokio::test]
+async fn test_ping_payload_echoed() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        Calculator.serve(server_transport),
+        ().serve(client_transport)
+    )?;
+
+    client.peer().ping_with_payload(b"path integrity").await?;
+    // the client echoes the pings of the server as well
+    server.peer().ping_with_payload([0, 1, 254, 255]).await?;
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ping_payload_mismatch() -> anyhow::Result<()> {
+    let (client_transport, server_transport) = tokio::io::duplex(4096);
+    let client = serve_directly((), client_transport, ServerInfo::default()).await?;
+    let (read, mut write) = tokio::io::split(server_transport);
+    let mut lines = BufReader::new(read).lines();
+
+    let peer = client.peer().clone();
+    let ping = tokio::spawn(async move { peer.ping_with_payload(b"hello").await });
+    let line = lines.next_line().await?.expect("expect the ping");
+    let request: serde_json::Value = serde_json::from_str(&line)?;
+    assert_eq!(request["method"], "ping");
+    assert_eq!(request["params"]["_meta"]["pingPayload"], "aGVsbG8=");
+
+    let response = json!({
+        "jsonrpc": "2.0",
+        "id": request["id"],
+        "result": { "_meta": { "pingPayload": "aGVsbA==" } },
+    });
+    write.write_all(format!("{response}\n").as_bytes()).await?;
+    let result = ping.await?;
+    assert!(
+        matches!(result, Err(ServiceError::PingPayloadMismatch)),
+        "{result:?}"
+    );
+
+    // a peer which doesn't echo at all
+    let peer = client.peer().clone();
+    let ping = tokio::spawn(async move { peer.ping_with_payload(b"hello").await });
+    let line = lines.next_line().await?.expect("expect the ping");
+    let request: serde_json::Value = serde_json::from_str(&line)?;
+    let response = json!({ "jsonrpc": "2.0", "id": request["id"], "result": {} });
+    write.write_all(format!("{response}\n").as_bytes()).await?;
+    let result = ping.await?;
+    assert!(
+        matches!(result, Err(ServiceError::PingPayloadMismatch)),
+        "{result:?}"
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
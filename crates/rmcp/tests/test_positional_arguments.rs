@@ -0,0 +1,149 @@
+use rmcp::{
+    ServerHandler, ServiceError, ServiceExt,
+    model::{CallToolRequestParam, ErrorCode, RawContent, ServerCapabilities, ServerInfo},
+    schemars, tool,
+};
+use serde_json::json;
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct Range {
+    pub start: i64,
+    pub end: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Calculator;
+
+#[tool(tool_box)]
+impl Calculator {
+    #[tool(description = "Subtract b from a", positional)]
+    fn sub(&self, #[tool(param)] a: i64, #[tool(param)] b: i64) -> String {
+        (a - b).to_string()
+    }
+
+    #[tool(description = "The length of a range", positional)]
+    fn length(&self, #[tool(aggr)] range: Range) -> String {
+        (range.end - range.start).to_string()
+    }
+
+    #[tool(description = "Do nothing")]
+    fn noop(&self) {}
+
+    #[tool(description = "Add a and b")]
+    fn add(&self, #[tool(param)] a: i64, #[tool(param)] b: i64) -> String {
+        (a + b).to_string()
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Calculator {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[test]
+fn test_positional_arguments_serialization() {
+    let param: CallToolRequestParam =
+        serde_json::from_value(json!({ "name": "sub", "arguments": [5, 3] })).unwrap();
+    assert_eq!(
+        param.positional_arguments(),
+        Some(&vec![json!(5), json!(3)])
+    );
+    assert_eq!(
+        param,
+        CallToolRequestParam::positional("sub", [json!(5), json!(3)])
+    );
+    assert_eq!(
+        serde_json::to_value(&param).unwrap(),
+        json!({ "name": "sub", "arguments": [5, 3] })
+    );
+
+    let param: CallToolRequestParam =
+        serde_json::from_value(json!({ "name": "sub", "arguments": { "a": 5, "b": 3 } })).unwrap();
+    assert_eq!(param.positional_arguments(), None);
+
+    // an object is never taken for positional arguments, whatever its keys
+    let arguments = json!({ "name": "sub", "arguments": { "$positional": [5, 3] } });
+    let param: CallToolRequestParam = serde_json::from_value(arguments.clone()).unwrap();
+    assert_eq!(param.positional_arguments(), None);
+    assert_eq!(serde_json::to_value(&param).unwrap(), arguments);
+}
+
+#[tokio::test]
+async fn test_positional_arguments() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Calculator.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+    let text = |result: rmcp::model::CallToolResult| match &result.content[0].raw {
+        RawContent::Text(text) => text.text.clone(),
+        content => panic!("expect a text content, got {content:?}"),
+    };
+
+    // mapped in declaration order, `a` first
+    let result = client
+        .call_tool(CallToolRequestParam::positional(
+            "sub",
+            [json!(5), json!(3)],
+        ))
+        .await?;
+    assert_eq!(text(result), "2");
+
+    // a positional tool still accepts named arguments
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "sub".into(),
+            arguments: Some(rmcp::object!({ "b": 5, "a": 3 }).into()),
+        })
+        .await?;
+    assert_eq!(text(result), "-2");
+
+    let result = client
+        .call_tool(CallToolRequestParam::positional(
+            "length",
+            [json!(2), json!(7)],
+        ))
+        .await?;
+    assert_eq!(text(result), "5");
+
+    let result = client
+        .call_tool(CallToolRequestParam::positional("sub", [json!(5)]))
+        .await;
+    let Err(ServiceError::McpError(error)) = result else {
+        panic!("expect a missing argument to be rejected, got {result:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+
+    // the other tools don't opt in
+    let result = client
+        .call_tool(CallToolRequestParam::positional(
+            "add",
+            [json!(5), json!(3)],
+        ))
+        .await;
+    let Err(ServiceError::McpError(error)) = result else {
+        panic!("expect positional arguments to be rejected, got {result:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    assert_eq!(
+        error.message,
+        "this tool doesn't accept positional arguments"
+    );
+    let result = client
+        .call_tool(CallToolRequestParam::positional("noop", [json!(1)]))
+        .await;
+    let Err(ServiceError::McpError(error)) = result else {
+        panic!("expect positional arguments to be rejected, got {result:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+
+    client.cancel().await?;
+    Ok(())
+}
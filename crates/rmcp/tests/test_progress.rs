@@ -0,0 +1,326 @@
+use std::{
+    io,
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::task::AtomicWaker;
+use rmcp::{
+    Peer, RoleClient, ServerHandler, ServiceExt,
+    model::{
+        CallToolRequest, CallToolRequestParam, CallToolResult, ClientRequest, Content,
+        ProgressNotificationParam, ServerCapabilities, ServerInfo, ServerResult,
+    },
+    service::{ProgressValidation, RequestContext, RoleServer, ServiceError},
+};
+use tokio::{io::AsyncWrite, sync::Notify};
+
+#[derive(Debug, Clone, Default)]
+pub struct ProgressServer;
+
+impl ServerHandler for ProgressServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let progress_token = context
+            .meta
+            .get_progress_token()
+            .ok_or_else(|| rmcp::Error::invalid_params("missing progress token", None))?;
+        for progress in 1..=3 {
+            context
+                .peer
+                .notify_progress(ProgressNotificationParam {
+                    progress_token: progress_token.clone(),
+                    progress,
+                    total: Some(3),
+                    message: None,
+                })
+                .await
+                .map_err(|e| rmcp::Error::internal_error(e.to_string(), None))?;
+        }
+        Ok(CallToolResult::success(vec![Content::text("done")]))
+    }
+}
+
+#[tokio::test]
+async fn test_send_request_with_progress() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = ProgressServer.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let request = ClientRequest::CallToolRequest(CallToolRequest {
+        method: Default::default(),
+        params: CallToolRequestParam {
+            name: "slow".into(),
+            arguments: None,
+        },
+        extensions: Default::default(),
+    });
+    let result = client
+        .send_request_with_progress(request, {
+            let updates = updates.clone();
+            move |progress| {
+                updates
+                    .lock()
+                    .unwrap()
+                    .push((progress.progress, progress.total))
+            }
+        })
+        .await?;
+    assert!(matches!(result, ServerResult::CallToolResult(_)));
+    assert_eq!(
+        *updates.lock().unwrap(),
+        [(1, Some(3)), (2, Some(3)), (3, Some(3))]
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
+
+/// Holds every write while it's closed, like a client which stopped reading
+#[derive(Default)]
+struct Gate {
+    closed: AtomicBool,
+    waker: AtomicWaker,
+}
+
+impl Gate {
+    fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    fn open(&self) {
+        self.closed.store(false, Ordering::SeqCst);
+        self.waker.wake();
+    }
+}
+
+struct GatedWriter<W> {
+    inner: W,
+    gate: Arc<Gate>,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for GatedWriter<W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.gate.closed.load(Ordering::SeqCst) {
+            self.gate.waker.register(cx.waker());
+            if self.gate.closed.load(Ordering::SeqCst) {
+                return Poll::Pending;
+            }
+        }
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Report many more updates than the outbound buffer holds, then the terminal one
+#[derive(Debug, Clone, Default)]
+struct FloodingServer {
+    reported: Arc<Notify>,
+}
+
+impl FloodingServer {
+    const TOTAL: u32 = 5000;
+}
+
+impl ServerHandler for FloodingServer {
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        for progress in 0..=Self::TOTAL {
+            if progress == Self::TOTAL {
+                self.reported.notify_one();
+            }
+            context
+                .report_progress(progress, Some(Self::TOTAL), None)
+                .await
+                .map_err(|e| rmcp::Error::internal_error(e.to_string(), None))?;
+        }
+        Ok(CallToolResult::success(vec![Content::text("done")]))
+    }
+}
+
+#[tokio::test]
+async fn test_progress_coalesced_on_slow_transport() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server_read, server_write) = tokio::io::split(server_transport);
+    let gate = Arc::new(Gate::default());
+    let server = FloodingServer::default();
+    let reported = server.reported.clone();
+    tokio::spawn({
+        let gate = gate.clone();
+        async move {
+            let writer = GatedWriter {
+                inner: server_write,
+                gate,
+            };
+            let server = server.serve((server_read, writer)).await?;
+            server.waiting().await?;
+            anyhow::Ok(())
+        }
+    });
+    let client = ().serve(client_transport).await?;
+
+    gate.close();
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let request = ClientRequest::CallToolRequest(CallToolRequest {
+        method: Default::default(),
+        params: CallToolRequestParam {
+            name: "flood".into(),
+            arguments: None,
+        },
+        extensions: Default::default(),
+    });
+    let call = tokio::spawn({
+        let peer = client.peer().clone();
+        let updates = updates.clone();
+        async move {
+            peer.send_request_with_progress(request, move |progress| {
+                updates.lock().unwrap().push(progress.progress)
+            })
+            .await
+        }
+    });
+    // the handler isn't blocked by the transport which doesn't write anything
+    tokio::time::timeout(Duration::from_secs(5), reported.notified()).await?;
+    gate.open();
+    let result = call.await??;
+    assert!(matches!(result, ServerResult::CallToolResult(_)));
+
+    let updates = updates.lock().unwrap().clone();
+    assert!(
+        updates.len() < FloodingServer::TOTAL as usize,
+        "expect the intermediate updates to be coalesced, got {}",
+        updates.len()
+    );
+    assert!(updates.is_sorted_by(|a, b| a < b), "{updates:?}");
+    assert_eq!(updates.last(), Some(&FloodingServer::TOTAL));
+
+    client.cancel().await?;
+    Ok(())
+}
+
+/// Report a progress beyond its total, then one without total, checked as the tool name says
+#[derive(Debug, Clone, Default)]
+struct OverflowServer {
+    errors: Arc<Mutex<Vec<String>>>,
+}
+
+impl ServerHandler for OverflowServer {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let validation = match request.name.as_ref() {
+            "strict" => ProgressValidation::Strict,
+            _ => ProgressValidation::Lenient,
+        };
+        let peer = context.peer.clone().with_progress_validation(validation);
+        let progress_token = context.meta.get_progress_token().unwrap();
+        for (progress, total) in [(5, Some(3)), (7, None)] {
+            let result = peer
+                .notify_progress(ProgressNotificationParam {
+                    progress_token: progress_token.clone(),
+                    progress,
+                    total,
+                    message: None,
+                })
+                .await;
+            if let Err(error) = result {
+                assert!(matches!(error, ServiceError::InvalidProgress { .. }));
+                self.errors.lock().unwrap().push(error.to_string());
+            }
+        }
+        Ok(CallToolResult::success(vec![]))
+    }
+}
+
+/// Call a tool and collect its progress updates
+async fn call_with_progress(
+    peer: &Peer<RoleClient>,
+    name: &'static str,
+) -> anyhow::Result<Vec<(u32, Option<u32>)>> {
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let request = ClientRequest::CallToolRequest(CallToolRequest {
+        method: Default::default(),
+        params: CallToolRequestParam {
+            name: name.into(),
+            arguments: None,
+        },
+        extensions: Default::default(),
+    });
+    peer.send_request_with_progress(request, {
+        let updates = updates.clone();
+        move |progress| {
+            updates
+                .lock()
+                .unwrap()
+                .push((progress.progress, progress.total))
+        }
+    })
+    .await?;
+    let updates = updates.lock().unwrap().clone();
+    Ok(updates)
+}
+
+#[tokio::test]
+async fn test_progress_beyond_total() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = OverflowServer::default();
+    let errors = server.errors.clone();
+    tokio::spawn(async move {
+        let server = server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    // clamped to the total, and a progress without total isn't limited
+    assert_eq!(
+        call_with_progress(client.peer(), "lenient").await?,
+        [(3, Some(3)), (7, None)]
+    );
+    assert!(errors.lock().unwrap().is_empty());
+
+    assert_eq!(
+        call_with_progress(client.peer(), "strict").await?,
+        [(7, None)]
+    );
+    assert_eq!(*errors.lock().unwrap(), ["progress 5 exceeds its total 3"]);
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler, ServiceExt,
+    model::{
+        GetPromptRequestParam, GetPromptResult, PromptMessage, PromptMessageContent,
+        PromptMessageRole, ReadResourceRequestParam, ReadResourceResult, ResourceContents,
+        ServerCapabilities, ServerInfo,
+    },
+    service::RequestContext,
+};
+use serde_json::json;
+
+/// A prompt linking to the `str:///greeting` resource instead of embedding it
+#[derive(Clone)]
+struct Greeter;
+
+impl ServerHandler for Greeter {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_prompts()
+                .enable_resources()
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    async fn get_prompt(
+        &self,
+        _request: GetPromptRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        Ok(GetPromptResult {
+            description: None,
+            messages: vec![
+                PromptMessage::new_text(PromptMessageRole::User, "greet me"),
+                PromptMessage::new_resource_link(
+                    PromptMessageRole::User,
+                    "str:///greeting",
+                    "greeting",
+                ),
+            ],
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        match request.uri.as_str() {
+            "str:///greeting" => Ok(ReadResourceResult {
+                contents: vec![ResourceContents::text("hello", request.uri)],
+            }),
+            _ => Err(McpError::resource_not_found("resource not found", None)),
+        }
+    }
+}
+
+#[test]
+fn test_resource_link_serialization() {
+    let message =
+        PromptMessage::new_resource_link(PromptMessageRole::User, "str:///greeting", "greeting");
+    let value = serde_json::to_value(&message).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "role": "user",
+            "content": {
+                "type": "resource_link",
+                "uri": "str:///greeting",
+                "name": "greeting",
+            },
+        })
+    );
+    let decoded: PromptMessage = serde_json::from_value(value).unwrap();
+    assert!(matches!(
+        decoded.content,
+        PromptMessageContent::ResourceLink { link } if link.raw.uri == "str:///greeting"
+    ));
+}
+
+#[tokio::test]
+async fn test_resolve_embedded_resources() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Greeter.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let prompt = client
+        .get_prompt(GetPromptRequestParam {
+            name: "greeting".to_string(),
+            arguments: None,
+        })
+        .await?;
+    assert!(matches!(
+        prompt.messages[1].content,
+        PromptMessageContent::ResourceLink { .. }
+    ));
+
+    let prompt = client.resolve_embedded_resources(prompt).await?;
+    assert_eq!(prompt.messages.len(), 2);
+    assert!(matches!(
+        &prompt.messages[0].content,
+        PromptMessageContent::Text { text } if text == "greet me"
+    ));
+    let PromptMessageContent::Resource { resource } = &prompt.messages[1].content else {
+        panic!("the resource link is not resolved");
+    };
+    assert!(matches!(
+        &resource.resource,
+        ResourceContents::TextResourceContents { text, uri, .. }
+            if text == "hello" && uri == "str:///greeting"
+    ));
+
+    client.cancel().await?;
+    Ok(())
+}
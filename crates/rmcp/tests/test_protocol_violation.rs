@@ -0,0 +1,117 @@
+use std::io;
+
+use rmcp::{
+    ServerHandler, ServiceExt,
+    model::{RequestId, ServerInfo, ServerJsonRpcMessage, ServerResult},
+    service::{CloseReason, ProtocolViolation, serve_directly},
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+impl ServerHandler for Server {}
+
+/// The violation behind a failed initialization
+fn violation_of(error: &io::Error) -> &ProtocolViolation {
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{error}");
+    error
+        .get_ref()
+        .and_then(|error| error.downcast_ref::<ProtocolViolation>())
+        .unwrap_or_else(|| panic!("expect a protocol violation, got {error}"))
+}
+
+const INITIALIZE: &str = r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"protocolVersion":"2025-03-26","capabilities":{},"clientInfo":{"name":"raw","version":"1.0.0"}}}"#;
+
+#[tokio::test]
+async fn test_notification_before_initialize() -> anyhow::Result<()> {
+    let (server_transport, mut client_transport) = tokio::io::duplex(4096);
+    client_transport
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"method\":\"notifications/initialized\"}\n")
+        .await?;
+    let Err(error) = Server.serve(server_transport).await else {
+        panic!("expect the initialization to fail");
+    };
+
+    let violation = violation_of(&error);
+    assert_eq!(violation.expected, "the initialize request");
+    assert_eq!(violation.context, "initializing");
+    assert!(
+        violation.got.contains("notifications/initialized"),
+        "{violation}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_response_instead_of_initialized_notification() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (read, mut write) = tokio::io::split(client_transport);
+    let server = tokio::spawn(Server.serve(server_transport));
+
+    write
+        .write_all(format!("{INITIALIZE}\n").as_bytes())
+        .await?;
+    // the initialize response
+    BufReader::new(read).read_line(&mut String::new()).await?;
+    write
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{}}\n")
+        .await?;
+    let Err(error) = server.await? else {
+        panic!("expect the initialization to fail");
+    };
+
+    let violation = violation_of(&error);
+    assert_eq!(violation.expected, "the initialized notification");
+    assert!(violation.got.contains("\"result\""), "{violation}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_initialize_response_with_another_id() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (read, mut write) = tokio::io::split(server_transport);
+    let client = tokio::spawn(().serve(client_transport));
+
+    // the initialize request
+    BufReader::new(read).read_line(&mut String::new()).await?;
+    let response = ServerJsonRpcMessage::response(
+        ServerResult::InitializeResult(ServerInfo::default()),
+        RequestId::Number(7),
+    );
+    write
+        .write_all(format!("{}\n", serde_json::to_string(&response)?).as_bytes())
+        .await?;
+    let Err(error) = client.await? else {
+        panic!("expect the initialization to fail");
+    };
+
+    assert_eq!(
+        violation_of(&error),
+        &ProtocolViolation::new(
+            "the response to request 0",
+            "a response to request 7",
+            "initializing"
+        )
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_message_of_the_wrong_role() -> anyhow::Result<()> {
+    let (client_transport, mut server_transport) = tokio::io::duplex(4096);
+    let client = serve_directly((), client_transport, ServerInfo::default()).await?;
+    // a server can't send a tool call
+    server_transport
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"tools/call\",\"params\":{\"name\":\"sum\"}}\n")
+        .await?;
+
+    let reason = client.waiting().await?;
+    let CloseReason::ProtocolViolation(violation) = reason else {
+        panic!("expect a protocol violation, got {reason:?}");
+    };
+    assert_eq!(violation.expected, "a valid JSON-RPC message");
+    assert_eq!(violation.context, "receiving a message");
+    assert!(!violation.got.is_empty());
+    Ok(())
+}
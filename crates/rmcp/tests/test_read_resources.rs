@@ -0,0 +1,71 @@
+use rmcp::{
+    RoleServer, ServerHandler, ServiceExt,
+    model::{
+        ErrorCode, ReadResourceRequestParam, ReadResourceResult, ResourceContents,
+        ServerCapabilities, ServerInfo,
+    },
+    service::RequestContext,
+};
+
+pub struct Server;
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_resources().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        ReadResourceRequestParam { uri }: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, rmcp::Error> {
+        match uri.strip_prefix("test://") {
+            Some(name @ ("alpha" | "gamma")) => {
+                Ok(ReadResourceResult::new(vec![ResourceContents::text(
+                    name,
+                    uri.clone(),
+                )]))
+            }
+            _ => Err(rmcp::Error::resource_not_found(
+                format!("no resource at {uri}"),
+                None,
+            )),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_read_resources_with_a_missing_one() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) =
+        tokio::try_join!(Server.serve(server_transport), ().serve(client_transport))?;
+
+    let uris = ["test://alpha", "test://missing", "test://gamma"];
+    let entries = client
+        .read_resources(uris.iter().map(|uri| uri.to_string()).collect())
+        .await?;
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| entry.uri.as_str())
+            .collect::<Vec<_>>(),
+        uris
+    );
+    for (entry, expected) in [(&entries[0], "alpha"), (&entries[2], "gamma")] {
+        let contents = entry.clone().into_result().expect("the resource exists");
+        assert!(matches!(
+            &contents[..],
+            [ResourceContents::TextResourceContents { text, .. }] if text == expected
+        ));
+    }
+    let error = entries[1].clone().into_result().unwrap_err();
+    assert_eq!(error.code, ErrorCode::RESOURCE_NOT_FOUND);
+    assert_eq!(error.message, "no resource at test://missing");
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::calculator::Calculator;
+use futures::{StreamExt, stream::FuturesUnordered};
+use rmcp::{
+    ServiceExt,
+    model::{CallToolRequest, CallToolRequestParam, ClientRequest, ServerResult, object},
+};
+use serde_json::json;
+
+/// Which request a response belongs to, never sent to the server
+#[derive(Debug, Clone, PartialEq)]
+struct Correlation {
+    job: usize,
+    expected: i32,
+}
+
+fn sum(a: i32, b: i32) -> ClientRequest {
+    ClientRequest::CallToolRequest(CallToolRequest {
+        method: Default::default(),
+        params: CallToolRequestParam {
+            name: "sum".into(),
+            arguments: Some(object(json!({ "a": a, "b": b })).into()),
+        },
+        extensions: Default::default(),
+    })
+}
+
+#[tokio::test]
+async fn test_context_returns_with_response() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        Calculator.serve(server_transport),
+        ().serve(client_transport)
+    )?;
+
+    let mut pending = (0..16)
+        .map(|job| {
+            let (a, b) = (job as i32, 100 * job as i32);
+            let context = Correlation {
+                job,
+                expected: a + b,
+            };
+            client.send_request_with_context(sum(a, b), context)
+        })
+        .collect::<FuturesUnordered<_>>();
+    let mut jobs = Vec::new();
+    while let Some((result, context)) = pending.next().await {
+        let ServerResult::CallToolResult(result) = result? else {
+            panic!("expect a tool result for job {}", context.job);
+        };
+        let text = result.content[0].as_text().expect("text").text.clone();
+        assert_eq!(text, context.expected.to_string());
+        jobs.push(context.job);
+    }
+    drop(pending);
+    jobs.sort_unstable();
+    assert_eq!(jobs, (0..16).collect::<Vec<_>>());
+
+    // the context also comes back with an error
+    let (result, context) = client
+        .send_request_with_context(
+            ClientRequest::CallToolRequest(CallToolRequest {
+                method: Default::default(),
+                params: CallToolRequestParam {
+                    name: "missing".into(),
+                    arguments: None,
+                },
+                extensions: Default::default(),
+            }),
+            "missing tool",
+        )
+        .await;
+    assert!(result.is_err());
+    assert_eq!(context, "missing tool");
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
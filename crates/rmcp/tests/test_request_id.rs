@@ -0,0 +1,94 @@
+use std::sync::{Arc, Mutex};
+
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler,
+    model::{CallToolRequestParam, CallToolResult, RequestId},
+    service::{
+        RequestContext, RequestIdProvider, ShardedIdProvider, ShardedRequestId, StructuredRequestId,
+    },
+    testing::TestClient,
+};
+use serde_json::json;
+
+/// Record the id of each request it receives
+#[derive(Clone, Default)]
+struct IdServer {
+    ids: Arc<Mutex<Vec<RequestId>>>,
+}
+
+impl ServerHandler for IdServer {
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        self.ids.lock().unwrap().push(context.id);
+        Ok(CallToolResult::success(vec![]))
+    }
+}
+
+#[test]
+fn test_sharded_ids_round_trip() -> anyhow::Result<()> {
+    let provider = ShardedIdProvider::new(7);
+    let ids: Vec<RequestId> = (0..3).map(|_| provider.next_request_id()).collect();
+
+    let wire = serde_json::to_value(&ids)?;
+    assert_eq!(wire, json!(["7:0", "7:1", "7:2"]));
+
+    let ids: Vec<RequestId> = serde_json::from_value(wire)?;
+    let parsed: Vec<_> = ids.iter().map(ShardedRequestId::from_request_id).collect();
+    assert_eq!(
+        parsed,
+        (0..3)
+            .map(|sequence| Some(ShardedRequestId { shard: 7, sequence }))
+            .collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_foreign_ids_are_not_parsed() {
+    for id in [
+        RequestId::Number(1),
+        RequestId::String("7".into()),
+        RequestId::String("7:x".into()),
+        RequestId::String("a:1".into()),
+    ] {
+        assert_eq!(ShardedRequestId::from_request_id(&id), None, "{id}");
+    }
+}
+
+#[tokio::test]
+async fn test_peer_sends_sharded_ids() -> anyhow::Result<()> {
+    let client = TestClient::new(IdServer::default()).await?;
+    let peer = client
+        .peer()
+        .clone()
+        .with_request_id_provider(ShardedIdProvider::new(2));
+    for _ in 0..2 {
+        peer.call_tool(CallToolRequestParam {
+            name: "echo".into(),
+            arguments: None,
+        })
+        .await?;
+    }
+
+    let ids = client.handler().ids.lock().unwrap().clone();
+    assert_eq!(
+        ids.iter()
+            .map(ShardedRequestId::from_request_id)
+            .collect::<Vec<_>>(),
+        [
+            Some(ShardedRequestId {
+                shard: 2,
+                sequence: 0
+            }),
+            Some(ShardedRequestId {
+                shard: 2,
+                sequence: 1
+            }),
+        ]
+    );
+    client.close().await?;
+    Ok(())
+}
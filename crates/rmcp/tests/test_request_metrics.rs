@@ -0,0 +1,157 @@
+use std::{num::NonZeroUsize, sync::Arc, time::Duration};
+
+use rmcp::{
+    RoleServer, ServerHandler, ServiceExt,
+    model::{
+        CallToolRequest, CallToolRequestParam, CallToolResult, ClientRequest, ServerCapabilities,
+        ServerInfo,
+    },
+    service::{PeerRequestOptions, RequestContext, RequestMetrics},
+};
+use tokio::sync::Semaphore;
+
+const MAX_CONCURRENT_REQUESTS: usize = 2;
+
+/// A server whose tool calls wait for the test to release them
+#[derive(Clone)]
+pub struct Server {
+    gate: Arc<Semaphore>,
+}
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+
+    fn max_concurrent_requests(&self) -> Option<NonZeroUsize> {
+        NonZeroUsize::new(MAX_CONCURRENT_REQUESTS)
+    }
+
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        // one permit per call
+        self.gate.acquire().await.expect("gate closed").forget();
+        Ok(CallToolResult::success(vec![]))
+    }
+}
+
+async fn wait_for(metrics: &RequestMetrics, queued: usize, in_flight: usize) {
+    let reached = async {
+        while (metrics.queued(), metrics.in_flight()) != (queued, in_flight) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    if tokio::time::timeout(Duration::from_secs(5), reached)
+        .await
+        .is_err()
+    {
+        panic!(
+            "expect {queued} queued and {in_flight} in flight, got {} and {}",
+            metrics.queued(),
+            metrics.in_flight()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_queue_depth_reflects_the_backlog() -> anyhow::Result<()> {
+    let gate = Arc::new(Semaphore::new(0));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        Server { gate: gate.clone() }.serve(server_transport),
+        ().serve(client_transport)
+    )?;
+    let metrics = server.request_metrics().clone();
+    assert_eq!((metrics.queued(), metrics.in_flight()), (0, 0));
+
+    let calls = (0..5)
+        .map(|_| {
+            let peer = client.peer().clone();
+            tokio::spawn(async move {
+                peer.call_tool(CallToolRequestParam {
+                    name: "wait".into(),
+                    arguments: None,
+                })
+                .await
+            })
+        })
+        .collect::<Vec<_>>();
+    wait_for(&metrics, 3, MAX_CONCURRENT_REQUESTS).await;
+
+    // each finished call lets a queued one start
+    gate.add_permits(1);
+    wait_for(&metrics, 2, MAX_CONCURRENT_REQUESTS).await;
+
+    gate.add_permits(4);
+    for call in calls {
+        call.await??;
+    }
+    wait_for(&metrics, 0, 0).await;
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_request_cancelled_while_queued_never_runs() -> anyhow::Result<()> {
+    let gate = Arc::new(Semaphore::new(0));
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        Server { gate: gate.clone() }.serve(server_transport),
+        ().serve(client_transport)
+    )?;
+    let metrics = server.request_metrics().clone();
+
+    let calls = (0..MAX_CONCURRENT_REQUESTS)
+        .map(|_| {
+            let peer = client.peer().clone();
+            tokio::spawn(async move {
+                peer.call_tool(CallToolRequestParam {
+                    name: "wait".into(),
+                    arguments: None,
+                })
+                .await
+            })
+        })
+        .collect::<Vec<_>>();
+    wait_for(&metrics, 0, MAX_CONCURRENT_REQUESTS).await;
+
+    let queued = client
+        .peer()
+        .send_cancellable_request(
+            ClientRequest::CallToolRequest(CallToolRequest {
+                method: Default::default(),
+                params: CallToolRequestParam {
+                    name: "wait".into(),
+                    arguments: None,
+                },
+                extensions: Default::default(),
+            }),
+            PeerRequestOptions::no_options(),
+        )
+        .await?;
+    wait_for(&metrics, 1, MAX_CONCURRENT_REQUESTS).await;
+
+    // leaves the queue without running, its handler would be a third request in flight
+    queued.cancel(None).await?;
+    wait_for(&metrics, 0, MAX_CONCURRENT_REQUESTS).await;
+
+    gate.add_permits(MAX_CONCURRENT_REQUESTS);
+    for call in calls {
+        call.await??;
+    }
+    wait_for(&metrics, 0, 0).await;
+    // the handler of the cancelled request would have taken a permit of the gate
+    assert_eq!(gate.available_permits(), 0);
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
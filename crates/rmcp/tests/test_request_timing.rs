@@ -0,0 +1,59 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler,
+    model::{CallToolRequestParam, CallToolResult, Content, ServerResult},
+    service::{RequestContext, RequestTiming},
+    testing::TestClient,
+};
+use serde_json::Value;
+
+/// Record how long each tool took, as seen by the service loop
+#[derive(Clone, Default)]
+struct TimingServer {
+    durations: Arc<Mutex<Vec<(String, Duration)>>>,
+}
+
+impl ServerHandler for TimingServer {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if request.name == "slow" {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        Ok(CallToolResult::success(vec![Content::text(request.name)]))
+    }
+
+    fn on_request_completed(
+        &self,
+        result: &Result<ServerResult, McpError>,
+        timing: &RequestTiming,
+    ) {
+        if let Ok(ServerResult::CallToolResult(result)) = result {
+            let name = result.content[0].as_text().unwrap().text.clone();
+            self.durations.lock().unwrap().push((name, timing.elapsed));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_slow_handler_reports_longer_elapsed() -> anyhow::Result<()> {
+    let client = TestClient::new(TimingServer::default()).await?;
+    client.call_tool("fast", Value::Null).await?;
+    client.call_tool("slow", Value::Null).await?;
+
+    let durations = client.handler().durations.lock().unwrap().clone();
+    let [(fast, fast_elapsed), (slow, slow_elapsed)] = &durations[..] else {
+        panic!("expected two timings, got {durations:?}");
+    };
+    assert_eq!((fast.as_str(), slow.as_str()), ("fast", "slow"));
+    assert!(*slow_elapsed >= Duration::from_millis(100));
+    assert!(slow_elapsed > fast_elapsed);
+    client.close().await?;
+    Ok(())
+}
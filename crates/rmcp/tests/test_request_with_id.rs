@@ -0,0 +1,50 @@
+use rmcp::{
+    ServiceError,
+    model::{ClientJsonRpcMessage, ClientRequest, PingRequest, RequestId, ServerInfo},
+    service::serve_directly,
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+fn ping() -> ClientRequest {
+    ClientRequest::PingRequest(PingRequest {
+        method: Default::default(),
+        extensions: Default::default(),
+    })
+}
+
+#[tokio::test]
+async fn test_send_request_with_id() -> anyhow::Result<()> {
+    let (client_transport, server_transport) = tokio::io::duplex(4096);
+    let client = serve_directly((), client_transport, ServerInfo::default()).await?;
+    let (server_read, mut server_write) = tokio::io::split(server_transport);
+    let mut server_read = BufReader::new(server_read);
+
+    let id = RequestId::String("downstream-1".into());
+    let pending = tokio::spawn({
+        let peer = client.peer().clone();
+        let id = id.clone();
+        async move { peer.send_request_with_id(id, ping()).await }
+    });
+
+    let mut line = String::new();
+    server_read.read_line(&mut line).await?;
+    let request = serde_json::from_str::<ClientJsonRpcMessage>(&line)?;
+    let (_, request_id) = request.into_request().expect("expect a request");
+    assert_eq!(request_id, id);
+
+    // a second request can't take the same id while the first is pending
+    let result = client.send_request_with_id(id.clone(), ping()).await;
+    assert!(
+        matches!(&result, Err(ServiceError::DuplicateRequestId { id: duplicate }) if *duplicate == id),
+        "{result:?}"
+    );
+
+    server_write
+        .write_all(br#"{"jsonrpc":"2.0","id":"downstream-1","result":{}}"#)
+        .await?;
+    server_write.write_all(b"\n").await?;
+    pending.await??;
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+use rmcp::{
+    RoleServer, ServerHandler, ServiceExt,
+    model::{
+        ReadResourceRequestParam, ReadResourceResult, ResourceContents, ServerCapabilities,
+        ServerInfo,
+    },
+    service::RequestContext,
+};
+
+/// "café crème" in iso-8859-1, which isn't valid utf-8
+const LATIN1: &[u8] = b"caf\xe9 cr\xe8me";
+
+pub struct Server;
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_resources().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        ReadResourceRequestParam { uri }: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, rmcp::Error> {
+        let contents = match uri.as_str() {
+            "file:///latin1.txt" => ResourceContents::encoded_text(LATIN1, Some("iso-8859-1"), uri),
+            "file:///utf16.txt" => {
+                let mut bytes = vec![0xff, 0xfe];
+                bytes.extend("crème".encode_utf16().flat_map(u16::to_le_bytes));
+                ResourceContents::encoded_text(&bytes, None, uri)
+            }
+            _ => ResourceContents::encoded_text("crème".as_bytes(), None, uri),
+        };
+        Ok(ReadResourceResult::new(vec![contents]))
+    }
+}
+
+async fn read(
+    client: &rmcp::Peer<rmcp::RoleClient>,
+    uri: &str,
+) -> anyhow::Result<ResourceContents> {
+    let mut result = client
+        .read_resource(ReadResourceRequestParam { uri: uri.into() })
+        .await?;
+    Ok(result.contents.remove(0))
+}
+
+#[tokio::test]
+async fn test_decode_declared_charset() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) =
+        tokio::try_join!(Server.serve(server_transport), ().serve(client_transport))?;
+
+    // decoded, the text keeps its charset
+    let latin1 = read(client.peer(), "file:///latin1.txt").await?;
+    assert_eq!(
+        latin1,
+        ResourceContents::text_with_charset("café crème", "iso-8859-1", "file:///latin1.txt")
+    );
+
+    // a byte order mark is enough to tell utf-16 apart
+    let utf16 = read(client.peer(), "file:///utf16.txt").await?;
+    assert_eq!(utf16.decode_text().as_deref(), Some("crème"));
+
+    // utf-8 stays text
+    let utf8 = read(client.peer(), "file:///utf8.txt").await?;
+    assert!(matches!(
+        utf8,
+        ResourceContents::TextResourceContents { .. }
+    ));
+    assert_eq!(utf8.decode_text().as_deref(), Some("crème"));
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[test]
+fn test_undeclared_charset_falls_back_to_utf8() {
+    let contents = ResourceContents::encoded_text(LATIN1, None, "file:///latin1.txt");
+    let ResourceContents::BlobResourceContents { mime_type, .. } = &contents else {
+        panic!("expect a blob, got {contents:?}");
+    };
+    assert_eq!(mime_type.as_deref(), Some("text/plain"));
+    // not utf-8, and nothing tells the charset
+    assert_eq!(contents.decode_text(), None);
+
+    let contents = ResourceContents::encoded_text(LATIN1, Some("ebcdic"), "file:///latin1.txt");
+    let ResourceContents::BlobResourceContents { mime_type, .. } = &contents else {
+        panic!("expect a blob, got {contents:?}");
+    };
+    assert_eq!(mime_type.as_deref(), Some("text/plain; charset=ebcdic"));
+    assert_eq!(contents.decode_text(), None);
+}
+
+#[test]
+fn test_blob_decoded_in_the_charset_of_its_mime_type() {
+    let contents = ResourceContents::BlobResourceContents {
+        uri: "file:///latin1.txt".into(),
+        mime_type: Some("text/plain; charset=ISO-8859-1".into()),
+        blob: BASE64_STANDARD.encode(LATIN1),
+    };
+    assert_eq!(contents.decode_text().as_deref(), Some("café crème"));
+}
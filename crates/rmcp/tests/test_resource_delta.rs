@@ -0,0 +1,122 @@
+use std::sync::{Arc, Mutex};
+
+use rmcp::{
+    ClientHandler, Peer, RoleClient, RoleServer, ServerHandler, ServiceExt,
+    model::{
+        AnnotateAble, ListResourcesResult, PaginatedRequestParam, RawResource,
+        ResourceListDeltaNotificationParam, ServerCapabilities, ServerInfo,
+    },
+    service::{LiveResourceSet, RequestContext},
+};
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_resources().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, rmcp::Error> {
+        Ok(ListResourcesResult {
+            next_cursor: None,
+            resources: vec![RawResource::new("file:///initial", "initial").no_annotation()],
+        })
+    }
+}
+
+/// Apply the deltas to its resource set, and tell the test once each one is applied
+pub struct DeltaClient {
+    resources: Arc<Mutex<LiveResourceSet>>,
+    applied_tx: mpsc::UnboundedSender<()>,
+}
+
+impl ClientHandler for DeltaClient {
+    async fn on_resource_list_delta(&self, params: ResourceListDeltaNotificationParam) {
+        self.resources.lock().unwrap().apply(&params);
+        let _ = self.applied_tx.send(());
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        None
+    }
+
+    fn set_peer(&mut self, _peer: Peer<RoleClient>) {}
+}
+
+fn uris(resources: &Mutex<LiveResourceSet>) -> Vec<String> {
+    resources
+        .lock()
+        .unwrap()
+        .uris()
+        .map(str::to_owned)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_apply_add_then_remove_deltas() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let resources = Arc::new(Mutex::new(LiveResourceSet::default()));
+    let (applied_tx, mut applied_rx) = mpsc::unbounded_channel();
+    let (server, client) = tokio::try_join!(
+        Server.serve(server_transport),
+        DeltaClient {
+            resources: resources.clone(),
+            applied_tx,
+        }
+        .serve(client_transport)
+    )?;
+    *resources.lock().unwrap() = LiveResourceSet::fetch(client.peer()).await?;
+    assert_eq!(uris(&resources), ["file:///initial"]);
+
+    server
+        .notify_resource_list_delta(ResourceListDeltaNotificationParam {
+            added: vec!["file:///a".into(), "file:///b".into()],
+            removed: vec![],
+        })
+        .await?;
+    applied_rx.recv().await;
+    assert_eq!(
+        uris(&resources),
+        ["file:///a", "file:///b", "file:///initial"]
+    );
+
+    server
+        .notify_resource_list_delta(ResourceListDeltaNotificationParam {
+            added: vec![],
+            removed: vec!["file:///a".into(), "file:///initial".into()],
+        })
+        .await?;
+    applied_rx.recv().await;
+    assert_eq!(uris(&resources), ["file:///b"]);
+    assert!(!resources.lock().unwrap().contains("file:///a"));
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[test]
+fn test_delta_wire_format() -> anyhow::Result<()> {
+    let delta = ResourceListDeltaNotificationParam {
+        added: vec!["file:///a".into()],
+        removed: vec![],
+    };
+    assert_eq!(
+        serde_json::to_value(&delta)?,
+        serde_json::json!({ "added": ["file:///a"] })
+    );
+    let delta: ResourceListDeltaNotificationParam =
+        serde_json::from_value(serde_json::json!({ "removed": ["file:///a"] }))?;
+    assert_eq!(delta.removed, ["file:///a"]);
+    assert!(delta.added.is_empty());
+    Ok(())
+}
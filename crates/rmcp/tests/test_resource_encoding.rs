@@ -0,0 +1,80 @@
+use base64::engine::{Engine, general_purpose::STANDARD as BASE64_STANDARD};
+use rmcp::{
+    RoleServer, ServerHandler, ServiceExt,
+    model::{
+        ReadResourceRequestParam, ReadResourceResult, ResourceContents, ResourceEncoding,
+        ServerCapabilities, ServerInfo,
+    },
+    service::RequestContext,
+};
+
+const TEXT: &str = "héllo, wörld";
+
+pub struct Server;
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_resources().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        ReadResourceRequestParam { uri }: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, rmcp::Error> {
+        let contents = match uri.as_str() {
+            "test://binary" => ResourceContents::BlobResourceContents {
+                uri,
+                mime_type: None,
+                blob: BASE64_STANDARD.encode([0xff, 0xfe, 0x00]),
+            },
+            _ => ResourceContents::text(TEXT, uri),
+        };
+        Ok(ReadResourceResult::new(vec![contents]).with_encoding(context.meta.get_encoding()))
+    }
+}
+
+fn params(uri: &str) -> ReadResourceRequestParam {
+    ReadResourceRequestParam { uri: uri.into() }
+}
+
+#[tokio::test]
+async fn test_read_utf8_resource_as_text_and_blob() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) =
+        tokio::try_join!(Server.serve(server_transport), ().serve(client_transport))?;
+
+    let result = client
+        .read_resource_as(params("test://utf8"), ResourceEncoding::Text)
+        .await?;
+    let [ResourceContents::TextResourceContents { uri, text, .. }] = &result.contents[..] else {
+        panic!("expect text contents, got {:?}", result.contents);
+    };
+    assert_eq!(uri, "test://utf8");
+    assert_eq!(text, TEXT);
+
+    let result = client
+        .read_resource_as(params("test://utf8"), ResourceEncoding::Blob)
+        .await?;
+    let [ResourceContents::BlobResourceContents { uri, blob, .. }] = &result.contents[..] else {
+        panic!("expect blob contents, got {:?}", result.contents);
+    };
+    assert_eq!(uri, "test://utf8");
+    assert_eq!(BASE64_STANDARD.decode(blob)?, TEXT.as_bytes());
+
+    // bytes which aren't utf-8 stay a blob
+    let result = client
+        .read_resource_as(params("test://binary"), ResourceEncoding::Text)
+        .await?;
+    assert!(matches!(
+        &result.contents[..],
+        [ResourceContents::BlobResourceContents { .. }]
+    ));
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
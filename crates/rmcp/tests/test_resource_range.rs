@@ -0,0 +1,83 @@
+use rmcp::{
+    RoleServer, ServerHandler, ServiceExt,
+    model::{
+        ReadResourceRequestParam, ReadResourceResult, ResourceContents, ResourceRange,
+        ServerCapabilities, ServerInfo,
+    },
+    service::RequestContext,
+};
+
+fn synthetic_text() -> String {
+    (0..1000)
+        .map(|i| char::from(b'a' + (i % 26) as u8))
+        .collect()
+}
+
+pub struct Server;
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_resources().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        ReadResourceRequestParam { uri }: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, rmcp::Error> {
+        let result =
+            ReadResourceResult::new(vec![ResourceContents::text(synthetic_text(), uri.clone())]);
+        match uri.as_str() {
+            "test://ranged" => Ok(result.with_range(context.meta.get_range())),
+            _ => Ok(result),
+        }
+    }
+}
+
+fn text_of(result: &ReadResourceResult) -> &str {
+    match &result.contents[0] {
+        ResourceContents::TextResourceContents { text, .. } => text,
+        _ => panic!("expect text contents"),
+    }
+}
+
+#[tokio::test]
+async fn test_read_resource_range() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+    let range = ResourceRange::new(100, 100);
+
+    let result = client
+        .read_resource_range(
+            ReadResourceRequestParam {
+                uri: "test://ranged".into(),
+            },
+            range,
+        )
+        .await?;
+    assert_eq!(result.range(), Some(range));
+    assert_eq!(text_of(&result), &synthetic_text()[100..200]);
+
+    // the handler doesn't opt in, so the full contents come back without a range
+    let result = client
+        .read_resource_range(
+            ReadResourceRequestParam {
+                uri: "test://plain".into(),
+            },
+            range,
+        )
+        .await?;
+    assert_eq!(result.range(), None);
+    assert_eq!(text_of(&result), synthetic_text());
+
+    client.cancel().await?;
+    Ok(())
+}
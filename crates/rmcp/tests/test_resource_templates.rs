@@ -0,0 +1,80 @@
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler, ServiceExt,
+    model::{
+        ListResourceTemplatesResult, PaginatedRequestParam, ResourceTemplate, ServerCapabilities,
+        ServerInfo,
+    },
+    service::RequestContext,
+};
+
+/// Two templates listed one per page
+#[derive(Debug, Clone)]
+pub struct Server {
+    templates: Vec<ResourceTemplate>,
+}
+
+impl Server {
+    fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            templates: vec![
+                ResourceTemplate::builder("file:///{path}", "file")
+                    .mime_type("text/plain")
+                    .build()?,
+                ResourceTemplate::builder("db://{table}/{id}", "row")
+                    .description("A row of a table")
+                    .build()?,
+            ],
+        })
+    }
+}
+
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_resources().build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_resource_templates(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, McpError> {
+        let page = match request.and_then(|request| request.cursor) {
+            None => 0,
+            Some(cursor) => cursor
+                .parse()
+                .map_err(|_| McpError::invalid_params("invalid cursor", None))?,
+        };
+        let next_cursor = (page + 1 < self.templates.len()).then(|| (page + 1).to_string());
+        Ok(ListResourceTemplatesResult {
+            next_cursor,
+            resource_templates: self.templates[page..=page].to_vec(),
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_list_resource_templates() -> anyhow::Result<()> {
+    let server = Server::new()?;
+    let templates = server.templates.clone();
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let first_page = client.list_resource_templates(None).await?;
+    assert_eq!(first_page.resource_templates, templates[..1]);
+    assert_eq!(first_page.next_cursor.as_deref(), Some("1"));
+
+    assert_eq!(client.list_all_resource_templates().await?, templates);
+    // templates are not concrete resources
+    assert!(client.list_all_resources().await?.is_empty());
+
+    client.cancel().await?;
+    Ok(())
+}
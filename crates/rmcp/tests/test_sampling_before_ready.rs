@@ -0,0 +1,117 @@
+use std::sync::{Arc, Mutex};
+
+use rmcp::{
+    ClientHandler, Error as McpError, Peer, RoleClient, RoleServer, ServerHandler, ServiceError,
+    ServiceExt,
+    model::{
+        ClientCapabilities, ClientInfo, Content, CreateMessageRequestParam, CreateMessageResult,
+        Role, SamplingMessage,
+    },
+    service::RequestContext,
+};
+use tokio::sync::oneshot;
+
+type SampleResult = Result<CreateMessageResult, ServiceError>;
+
+/// Request sampling as soon as the client is initialized
+struct EagerServer {
+    peer: Option<Peer<RoleServer>>,
+    result: Arc<Mutex<Option<oneshot::Sender<SampleResult>>>>,
+}
+
+impl ServerHandler for EagerServer {
+    fn get_peer(&self) -> Option<Peer<RoleServer>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleServer>) {
+        self.peer = Some(peer);
+    }
+
+    async fn on_client_initialized(&self) {
+        let peer = self.peer.clone().expect("the peer is set before serving");
+        let sender = self.result.lock().unwrap().take();
+        tokio::spawn(async move {
+            let request = CreateMessageRequestParam::builder(100)
+                .user_message("Say hello")
+                .build()
+                .expect("a user message is given");
+            let result = peer.create_message(request).await;
+            if let Some(sender) = sender {
+                let _ = sender.send(result);
+            }
+        });
+    }
+}
+
+struct Sampler {
+    advertise_sampling: bool,
+}
+
+impl ClientHandler for Sampler {
+    async fn create_message(
+        &self,
+        _params: CreateMessageRequestParam,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, McpError> {
+        Ok(CreateMessageResult {
+            model: "mock".to_string(),
+            stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.to_string()),
+            message: SamplingMessage {
+                role: Role::Assistant,
+                content: Content::text("Hello"),
+            },
+        })
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        None
+    }
+
+    fn set_peer(&mut self, _peer: Peer<RoleClient>) {}
+
+    fn get_info(&self) -> ClientInfo {
+        let capabilities = if self.advertise_sampling {
+            ClientCapabilities::builder().enable_sampling().build()
+        } else {
+            ClientCapabilities::default()
+        };
+        ClientInfo {
+            capabilities,
+            ..Default::default()
+        }
+    }
+}
+
+async fn sample_right_after_initialize(advertise_sampling: bool) -> anyhow::Result<SampleResult> {
+    let (sender, receiver) = oneshot::channel();
+    let server = EagerServer {
+        peer: None,
+        result: Arc::new(Mutex::new(Some(sender))),
+    };
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        server.serve(server_transport),
+        Sampler { advertise_sampling }.serve(client_transport)
+    )?;
+    let result = receiver.await?;
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(result)
+}
+
+#[tokio::test]
+async fn test_sampling_requested_right_after_initialize() -> anyhow::Result<()> {
+    let result = sample_right_after_initialize(true).await??;
+    assert_eq!(result.model, "mock");
+    assert_eq!(result.message.role, Role::Assistant);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sampling_still_handled_when_not_advertised() -> anyhow::Result<()> {
+    // only a warning for now, it will be rejected once the warning has been out for a release
+    let result = sample_right_after_initialize(false).await??;
+    assert_eq!(result.model, "mock");
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use rmcp::{
+    ClientHandler, Error as McpError, Peer, RoleClient, ServerHandler, ServiceError, ServiceExt,
+    model::{
+        ClientCapabilities, ClientInfo, Content, CreateMessageRequestParam, CreateMessageResult,
+        ResourceContents, Role, SamplingMessage, SamplingResponseError,
+    },
+    service::RequestContext,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+impl ServerHandler for Server {}
+
+/// Answer every sampling request with the same message
+pub struct MockSampler {
+    message: SamplingMessage,
+}
+
+impl ClientHandler for MockSampler {
+    async fn create_message(
+        &self,
+        _params: CreateMessageRequestParam,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, McpError> {
+        Ok(CreateMessageResult {
+            model: "mock".to_string(),
+            stop_reason: Some(CreateMessageResult::STOP_REASON_END_TURN.to_string()),
+            message: self.message.clone(),
+        })
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        None
+    }
+
+    fn set_peer(&mut self, _peer: Peer<RoleClient>) {}
+
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            capabilities: ClientCapabilities::builder().enable_sampling().build(),
+            ..Default::default()
+        }
+    }
+}
+
+async fn sample(
+    message: SamplingMessage,
+) -> anyhow::Result<Result<CreateMessageResult, ServiceError>> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) = tokio::try_join!(
+        Server.serve(server_transport),
+        MockSampler { message }.serve(client_transport)
+    )?;
+    let request = CreateMessageRequestParam::builder(100)
+        .user_message("Say hello")
+        .build()?;
+    let result = server.create_message(request).await;
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(result)
+}
+
+#[tokio::test]
+async fn test_sampling_response_from_assistant() -> anyhow::Result<()> {
+    let result = sample(SamplingMessage {
+        role: Role::Assistant,
+        content: Content::text("hello"),
+    })
+    .await?;
+    assert_eq!(result?.message.content.as_text().unwrap().text, "hello");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sampling_response_with_user_role() -> anyhow::Result<()> {
+    let result = sample(SamplingMessage {
+        role: Role::User,
+        content: Content::text("hello"),
+    })
+    .await?;
+    let Err(ServiceError::InvalidSamplingResponse(error)) = result else {
+        panic!("expect the user message to be rejected, got {result:?}");
+    };
+    assert_eq!(error, SamplingResponseError::UnexpectedRole(Role::User));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sampling_response_with_resource() -> anyhow::Result<()> {
+    let result = sample(SamplingMessage {
+        role: Role::Assistant,
+        content: Content::resource(ResourceContents::text("hello", "str:///hello")),
+    })
+    .await?;
+    let Err(ServiceError::InvalidSamplingResponse(error)) = result else {
+        panic!("expect the resource to be rejected, got {result:?}");
+    };
+    assert_eq!(error, SamplingResponseError::UnsupportedContent("resource"));
+    Ok(())
+}
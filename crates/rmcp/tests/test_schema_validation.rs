@@ -0,0 +1,133 @@
+use rmcp::{
+    ServerHandler, ServiceError, ServiceExt,
+    model::{CallToolRequestParam, ErrorCode, ServerCapabilities, ServerInfo},
+    tool,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RepeatRequest {
+    /// How many times to repeat the word
+    #[schemars(range(min = 1, max = 10))]
+    pub times: u32,
+    pub word: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+#[tool(tool_box)]
+impl Server {
+    #[tool(description = "Repeat a word a few times")]
+    fn repeat(&self, #[tool(aggr)] RepeatRequest { times, word }: RepeatRequest) -> String {
+        vec![word; times as usize].join(" ")
+    }
+
+    #[tool(
+        description = "Repeat a word a few times, taking the arguments by position",
+        positional
+    )]
+    fn repeat_positional(
+        &self,
+        #[tool(aggr)] RepeatRequest { times, word }: RepeatRequest,
+    ) -> String {
+        vec![word; times as usize].join(" ")
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_out_of_range_argument_rejected() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    // serde alone would happily accept 42
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "repeat".into(),
+            arguments: serde_json::json!({ "times": 42, "word": "hey" })
+                .as_object()
+                .cloned()
+                .map(Into::into),
+        })
+        .await;
+    let Err(ServiceError::McpError(error)) = result else {
+        panic!("expect an invalid params error, got {result:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    let errors = error.data.unwrap()["errors"].as_array().unwrap().clone();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].as_str().unwrap().starts_with("/times"));
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "repeat".into(),
+            arguments: serde_json::json!({ "times": 2, "word": "hey" })
+                .as_object()
+                .cloned()
+                .map(Into::into),
+        })
+        .await?;
+    assert_eq!(
+        result.content[0].as_text().map(|t| t.text.as_str()),
+        Some("hey hey")
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_out_of_range_positional_argument_rejected() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    // checked once named after the fields of the request
+    let result = client
+        .call_tool(CallToolRequestParam::positional(
+            "repeat_positional",
+            [serde_json::json!(42), serde_json::json!("hey")],
+        ))
+        .await;
+    let Err(ServiceError::McpError(error)) = result else {
+        panic!("expect an invalid params error, got {result:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+    let errors = error.data.unwrap()["errors"].as_array().unwrap().clone();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].as_str().unwrap().starts_with("/times"));
+
+    let result = client
+        .call_tool(CallToolRequestParam::positional(
+            "repeat_positional",
+            [serde_json::json!(2), serde_json::json!("hey")],
+        ))
+        .await?;
+    assert_eq!(
+        result.content[0].as_text().map(|t| t.text.as_str()),
+        Some("hey hey")
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use rmcp::{
+    ServerHandler,
+    model::ClientInfo,
+    service::{CloseReason, ShutdownOptions, serve_directly},
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+const NOTIFICATION_COUNT: usize = 200;
+
+#[derive(Debug, Clone, Default)]
+struct RootsWatcher {
+    changes: Arc<AtomicUsize>,
+}
+
+impl ServerHandler for RootsWatcher {
+    async fn on_roots_list_changed(&self) {
+        self.changes.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Flood the server with notifications once it started shutting down, return how many it handled
+async fn flood_during_shutdown(drain_notifications: bool) -> anyhow::Result<usize> {
+    let (server_transport, client_transport) = tokio::io::duplex(64 * 1024);
+    let watcher = RootsWatcher::default();
+    let changes = watcher.changes.clone();
+    let server = serve_directly(watcher, server_transport, ClientInfo::default()).await?;
+    let shutdown = tokio::spawn(server.shutdown(ShutdownOptions {
+        drain_notifications,
+        ..Default::default()
+    }));
+
+    // the server closes its side once it's shutting down
+    let (mut client_read, mut client_write) = tokio::io::split(client_transport);
+    let mut output = Vec::new();
+    client_read.read_to_end(&mut output).await?;
+    assert!(output.is_empty());
+
+    for _ in 0..NOTIFICATION_COUNT {
+        client_write
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"method\":\"notifications/roots/list_changed\"}\n")
+            .await?;
+    }
+    client_write.shutdown().await?;
+    drop(client_write);
+
+    let reason = shutdown.await??;
+    assert!(matches!(reason, CloseReason::Cancelled), "{reason:?}");
+    Ok(changes.load(Ordering::SeqCst))
+}
+
+#[tokio::test]
+async fn test_shutdown_drains_notifications() -> anyhow::Result<()> {
+    assert_eq!(flood_during_shutdown(true).await?, NOTIFICATION_COUNT);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_shutdown_drops_notifications() -> anyhow::Result<()> {
+    assert_eq!(flood_during_shutdown(false).await?, 0);
+    Ok(())
+}
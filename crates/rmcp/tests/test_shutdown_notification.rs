@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use rmcp::{
+    ClientHandler, Peer, RoleClient, ServerHandler, ServiceExt,
+    model::{ClientInfo, ServerJsonRpcMessage, ServerNotification},
+    service::{ShutdownOptions, serve_directly},
+};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    sync::mpsc,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+impl ServerHandler for Server {}
+
+/// Tell the test when the server announced its shutdown
+pub struct ShutdownClient {
+    shutdown_tx: mpsc::UnboundedSender<()>,
+}
+
+impl ClientHandler for ShutdownClient {
+    async fn on_peer_shutdown(&self) {
+        let _ = self.shutdown_tx.send(());
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        None
+    }
+
+    fn set_peer(&mut self, _peer: Peer<RoleClient>) {}
+}
+
+fn options() -> ShutdownOptions {
+    ShutdownOptions {
+        timeout: Duration::from_millis(100),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_shutdown_notification_is_the_last_message() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = serve_directly(Server, server_transport, ClientInfo::default()).await?;
+    let (client_read, _client_write) = tokio::io::split(client_transport);
+    let mut lines = BufReader::new(client_read).lines();
+
+    let shutdown = tokio::spawn(server.shutdown(options()));
+    let mut messages = Vec::new();
+    while let Some(line) = lines.next_line().await? {
+        messages.push(serde_json::from_str::<ServerJsonRpcMessage>(&line)?);
+    }
+    // the transport is closed right after the notification
+    assert!(matches!(
+        messages
+            .pop()
+            .and_then(ServerJsonRpcMessage::into_notification),
+        Some(ServerNotification::ShutdownNotification(_))
+    ));
+    shutdown.await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_peer_shutdown_hook() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (shutdown_tx, mut shutdown_rx) = mpsc::unbounded_channel();
+    let (server, client) = tokio::try_join!(
+        Server.serve(server_transport),
+        ShutdownClient { shutdown_tx }.serve(client_transport)
+    )?;
+    server.shutdown(options()).await?;
+    assert_eq!(shutdown_rx.recv().await, Some(()));
+    client.waiting().await?;
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use reqwest::header::{HeaderName, HeaderValue};
+use rmcp::transport::{
+    SseTransport,
+    sse::{DEFAULT_USER_AGENT, ReqwestSseClient},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+/// Accept one sse connection, answer with the endpoint event and return the request headers,
+/// along with the connection to keep it open
+async fn accept_sse_connection(
+    listener: TcpListener,
+) -> anyhow::Result<(Vec<String>, BufReader<TcpStream>)> {
+    let (stream, _) = listener.accept().await?;
+    let mut stream = BufReader::new(stream);
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        stream.read_line(&mut line).await?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        headers.push(line.to_ascii_lowercase());
+    }
+    stream
+        .write_all(
+            b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\r\nevent: endpoint\ndata: /message?sessionId=1\n\n",
+        )
+        .await?;
+    stream.flush().await?;
+    Ok((headers, stream))
+}
+
+#[tokio::test]
+async fn test_custom_headers() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/sse", listener.local_addr()?);
+    let server = tokio::spawn(accept_sse_connection(listener));
+
+    let client = ReqwestSseClient::new(url.as_str())?.with_header(
+        HeaderName::from_static("x-tenant"),
+        HeaderValue::from_static("acme"),
+    );
+    let _transport = SseTransport::start_with_client(client).await?;
+
+    let (headers, _connection) = server.await??;
+    assert!(
+        headers.contains(&"x-tenant: acme".to_string()),
+        "{headers:?}"
+    );
+    assert!(
+        headers.contains(&format!(
+            "user-agent: {}",
+            DEFAULT_USER_AGENT.to_ascii_lowercase()
+        )),
+        "{headers:?}"
+    );
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+use rmcp::transport::{SseServer, SseTransport, sse_server::SseServerConfig};
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+const KEEP_ALIVE: Duration = Duration::from_millis(50);
+
+async fn start_server() -> anyhow::Result<(SseServer, String)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let bind = listener.local_addr()?;
+    let (server, router) = SseServer::new(SseServerConfig {
+        bind,
+        sse_path: "/sse".to_string(),
+        post_path: "/message".to_string(),
+        ct: CancellationToken::new(),
+        sse_keep_alive: Some(KEEP_ALIVE),
+    });
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    Ok((server, format!("http://{bind}/sse")))
+}
+
+#[tokio::test]
+async fn test_keepalive_comments() -> anyhow::Result<()> {
+    let (_server, url) = start_server().await?;
+    let mut response = reqwest::get(&url).await?;
+    let mut body = String::new();
+    let _ = tokio::time::timeout(KEEP_ALIVE * 5, async {
+        while let Ok(Some(chunk)) = response.chunk().await {
+            body.push_str(&String::from_utf8_lossy(&chunk));
+        }
+    })
+    .await;
+    let keepalives = body.matches(": keepalive\n").count();
+    assert!(keepalives >= 2, "{body}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_keepalive_ignored_by_client() -> anyhow::Result<()> {
+    let (_server, url) = start_server().await?;
+    let mut transport = SseTransport::start(url.as_str()).await?;
+    // several keep-alive intervals pass without any message
+    let next = tokio::time::timeout(KEEP_ALIVE * 5, transport.next()).await;
+    assert!(next.is_err(), "{next:?}");
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler,
+    model::{
+        GetPromptRequestParam, GetPromptResult, PromptMessage, PromptMessageContent,
+        PromptMessageRole, ReadResourceRequestParam, ReadResourceResult, ResourceContents,
+        ServerCapabilities, ServerInfo,
+    },
+    service::RequestContext,
+    testing::TestClient,
+    tool,
+};
+use serde_json::json;
+
+/// A sample handler with a tool, a resource and a prompt
+#[derive(Debug, Clone, Default)]
+pub struct Notebook;
+
+#[tool(tool_box)]
+impl Notebook {
+    #[tool(description = "Join two words")]
+    fn join(&self, #[tool(param)] left: String, #[tool(param)] right: String) -> String {
+        format!("{left} {right}")
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Notebook {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .enable_prompts()
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        match request.uri.as_str() {
+            "note:///todo" => Ok(ReadResourceResult {
+                contents: vec![ResourceContents::text("write tests", request.uri)],
+            }),
+            _ => Err(McpError::resource_not_found("no such note", None)),
+        }
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        let topic = request
+            .arguments
+            .and_then(|arguments| arguments.get("topic")?.as_str().map(str::to_owned))
+            .ok_or_else(|| McpError::invalid_params("missing topic", None))?;
+        Ok(GetPromptResult {
+            description: None,
+            messages: vec![PromptMessage::new_text(
+                PromptMessageRole::User,
+                format!("Take a note about {topic}"),
+            )],
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_call_tool() -> anyhow::Result<()> {
+    let client = TestClient::new(Notebook).await?;
+    let result = client
+        .call_tool("join", json!({ "left": "hello", "right": "world" }))
+        .await?;
+    assert_eq!(result.content[0].as_text().unwrap().text, "hello world");
+    assert!(client.call_tool("missing", json!(null)).await.is_err());
+    client.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_tools() -> anyhow::Result<()> {
+    let client = TestClient::new(Notebook).await?;
+    let tools = client.list_tools().await?;
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0].name, "join");
+    client.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_resource() -> anyhow::Result<()> {
+    let client = TestClient::new(Notebook).await?;
+    let contents = client.read_resource("note:///todo").await?;
+    assert!(matches!(
+        &contents[..],
+        [ResourceContents::TextResourceContents { text, .. }] if text == "write tests"
+    ));
+    assert!(client.read_resource("note:///missing").await.is_err());
+    client.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_prompt() -> anyhow::Result<()> {
+    let client = TestClient::new(Notebook).await?;
+    let prompt = client
+        .get_prompt("note", json!({ "topic": "rust" }).as_object().cloned())
+        .await?;
+    assert!(matches!(
+        &prompt.messages[0].content,
+        PromptMessageContent::Text { text } if text == "Take a note about rust"
+    ));
+    assert!(client.get_prompt("note", None).await.is_err());
+    client.close().await?;
+    Ok(())
+}
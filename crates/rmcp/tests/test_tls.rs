@@ -0,0 +1,127 @@
+use std::sync::{Arc, Mutex};
+
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, ExtendedKeyUsagePurpose, IsCa, KeyPair,
+};
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler, ServiceExt,
+    model::{ClientRequest, PingRequest},
+    service::RequestContext,
+    transport::{
+        TlsClientTransport, TlsServerAcceptor,
+        tls::{
+            PeerCertificates,
+            rustls::{
+                RootCertStore,
+                pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+            },
+        },
+    },
+};
+use tokio::net::TcpListener;
+
+struct Issued {
+    cert: CertificateDer<'static>,
+    key: PrivateKeyDer<'static>,
+}
+
+fn issue(
+    name: &str,
+    usage: ExtendedKeyUsagePurpose,
+    ca: &Certificate,
+    ca_key: &KeyPair,
+) -> anyhow::Result<Issued> {
+    let key = KeyPair::generate()?;
+    let mut params = CertificateParams::new(vec![name.to_owned()])?;
+    params.extended_key_usages = vec![usage];
+    let cert = params.signed_by(&key, ca, ca_key)?;
+    Ok(Issued {
+        cert: cert.der().clone(),
+        key: PrivatePkcs8KeyDer::from(key.serialize_der()).into(),
+    })
+}
+
+/// Record the client certificate seen by every ping
+#[derive(Clone, Default)]
+struct CertRecorder {
+    seen: Arc<Mutex<Vec<Option<CertificateDer<'static>>>>>,
+}
+
+impl ServerHandler for CertRecorder {
+    async fn ping(&self, context: RequestContext<RoleServer>) -> Result<(), McpError> {
+        let cert = context
+            .extensions
+            .get::<PeerCertificates>()
+            .and_then(|certificates| certificates.end_entity().cloned());
+        self.seen.lock().unwrap().push(cert);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_tls_with_client_auth() -> anyhow::Result<()> {
+    let ca_key = KeyPair::generate()?;
+    let mut ca_params = CertificateParams::new(Vec::<String>::new())?;
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca = ca_params.self_signed(&ca_key)?;
+    let mut roots = RootCertStore::empty();
+    roots.add(ca.der().clone())?;
+
+    let server_identity = issue(
+        "localhost",
+        ExtendedKeyUsagePurpose::ServerAuth,
+        &ca,
+        &ca_key,
+    )?;
+    let client_identity = issue("client", ExtendedKeyUsagePurpose::ClientAuth, &ca, &ca_key)?;
+
+    let acceptor = TlsServerAcceptor::with_client_auth(
+        vec![server_identity.cert],
+        server_identity.key,
+        roots.clone(),
+    )?;
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let recorder = CertRecorder::default();
+    let server_handle = tokio::spawn({
+        let recorder = recorder.clone();
+        async move {
+            // the first client has no certificate and is rejected
+            let (tcp, _) = listener.accept().await?;
+            assert!(acceptor.accept(tcp).await.is_err());
+
+            let (tcp, _) = listener.accept().await?;
+            let server = recorder.serve(acceptor.accept(tcp).await?).await?;
+            server.waiting().await?;
+            anyhow::Ok(())
+        }
+    });
+
+    let anonymous = TlsClientTransport::client_config(roots.clone(), None)?;
+    if let Ok(transport) = TlsClientTransport::connect(addr, "localhost", anonymous).await {
+        // with TLS 1.3 the rejection only shows up once the client reads
+        assert!(().serve(transport).await.is_err());
+    }
+
+    let config = TlsClientTransport::client_config(
+        roots,
+        Some((vec![client_identity.cert.clone()], client_identity.key)),
+    )?;
+    let transport = TlsClientTransport::connect(addr, "localhost", config).await?;
+    assert!(transport.peer_certificates().is_some());
+    let client = ().serve(transport).await?;
+    client
+        .send_request(ClientRequest::PingRequest(PingRequest {
+            method: Default::default(),
+            extensions: Default::default(),
+        }))
+        .await?;
+    client.cancel().await?;
+    server_handle.await??;
+
+    assert_eq!(
+        *recorder.seen.lock().unwrap(),
+        vec![Some(client_identity.cert)]
+    );
+    Ok(())
+}
@@ -100,3 +100,26 @@ async fn test_tool_macros_with_generics() {
 }
 
 impl GetWeatherRequest {}
+
+#[derive(Debug, Clone, Default)]
+pub struct EchoServer;
+
+#[tool(tool_box)]
+impl EchoServer {
+    #[tool(description = "Echo the input")]
+    async fn echo(&self, #[tool(param)] input: String) -> String {
+        input
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for EchoServer {}
+
+#[test]
+fn test_tool_box_supported_methods() {
+    let methods = EchoServer.supported_methods();
+    assert!(methods.contains(&"tools/list"), "{methods:?}");
+    assert!(methods.contains(&"tools/call"), "{methods:?}");
+    assert!(methods.contains(&"ping"), "{methods:?}");
+    assert!(!methods.contains(&"prompts/list"), "{methods:?}");
+}
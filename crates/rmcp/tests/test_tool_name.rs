@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use rmcp::{ServerHandler, testing::TestClient, tool};
+use serde_json::json;
+
+#[derive(Debug, Clone, Default)]
+pub struct FileServer;
+
+#[tool(tool_box)]
+impl FileServer {
+    #[tool(name = "fs.read_file", description = "Read a file")]
+    async fn read_file(&self, #[tool(param)] path: String) -> String {
+        format!("content of {path}")
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for FileServer {}
+
+pub trait Storage: Send + Sync + 'static {
+    fn get(&self, key: &str) -> String;
+}
+
+pub struct MemoryStorage;
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> String {
+        format!("value of {key}")
+    }
+}
+
+pub struct StorageServer<S: Storage> {
+    storage: Arc<S>,
+}
+
+#[tool(tool_box)]
+impl<S: Storage> StorageServer<S> {
+    #[tool(name = "kv.get", description = "Get a value")]
+    async fn get(&self, #[tool(param)] key: String) -> String {
+        self.storage.get(&key)
+    }
+}
+
+#[tool(tool_box)]
+impl<S: Storage> ServerHandler for StorageServer<S> {}
+
+fn text(result: &rmcp::model::CallToolResult) -> Option<&str> {
+    result.content[0].as_text().map(|text| text.text.as_str())
+}
+
+#[tokio::test]
+async fn test_call_tool_by_custom_name() -> anyhow::Result<()> {
+    let client = TestClient::new(FileServer).await?;
+
+    let tools = client.list_tools().await?;
+    assert_eq!(
+        tools
+            .iter()
+            .map(|tool| tool.name.as_ref())
+            .collect::<Vec<_>>(),
+        ["fs.read_file"]
+    );
+    let result = client
+        .call_tool("fs.read_file", json!({ "path": "notes.txt" }))
+        .await?;
+    assert_eq!(text(&result), Some("content of notes.txt"));
+    // the function name isn't a tool name
+    assert!(
+        client
+            .call_tool("read_file", json!({ "path": "notes.txt" }))
+            .await
+            .is_err()
+    );
+
+    client.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_call_tool_by_custom_name_with_generics() -> anyhow::Result<()> {
+    let client = TestClient::new(StorageServer {
+        storage: Arc::new(MemoryStorage),
+    })
+    .await?;
+
+    let tools = client.list_tools().await?;
+    assert_eq!(
+        tools
+            .iter()
+            .map(|tool| tool.name.as_ref())
+            .collect::<Vec<_>>(),
+        ["kv.get"]
+    );
+    let result = client.call_tool("kv.get", json!({ "key": "a" })).await?;
+    assert_eq!(text(&result), Some("value of a"));
+    assert!(
+        client
+            .call_tool("get", json!({ "key": "a" }))
+            .await
+            .is_err()
+    );
+
+    client.close().await?;
+    Ok(())
+}
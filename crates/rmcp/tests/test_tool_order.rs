@@ -0,0 +1,52 @@
+use rmcp::{
+    ServerHandler,
+    model::{ServerCapabilities, ServerInfo},
+    testing::TestClient,
+    tool,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+#[tool(tool_box)]
+impl Server {
+    #[tool(description = "Declared first")]
+    fn zulu(&self) -> String {
+        "zulu".to_string()
+    }
+
+    #[tool(description = "Declared second")]
+    fn alpha(&self) -> String {
+        "alpha".to_string()
+    }
+
+    #[tool(description = "Declared third")]
+    fn mike(&self) -> String {
+        "mike".to_string()
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_tools_are_listed_in_declaration_order() -> anyhow::Result<()> {
+    let client = TestClient::new(Server).await?;
+    for _ in 0..5 {
+        let tools = client.list_tools().await?;
+        let names = tools
+            .iter()
+            .map(|tool| tool.name.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["zulu", "alpha", "mike"]);
+    }
+    client.close().await?;
+    Ok(())
+}
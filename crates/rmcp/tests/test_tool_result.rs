@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use rmcp::{
+    ServerHandler, ServiceError, ServiceExt,
+    handler::server::wrapper::Json,
+    model::{
+        CallToolRequestParam, CallToolResult, Content, ErrorCode, ServerCapabilities, ServerInfo,
+    },
+    tool,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+#[tool(tool_box)]
+impl Server {
+    #[tool(description = "Return a map keyed by tuples, which json can't represent")]
+    fn tuple_keys(&self) -> Json<HashMap<(i32, i32), String>> {
+        Json(HashMap::from([((0, 0), "origin".to_string())]))
+    }
+
+    #[tool(description = "Return a NaN")]
+    fn nan(&self) -> Json<f64> {
+        Json(f64::NAN)
+    }
+
+    #[tool(description = "Do nothing")]
+    fn noop(&self) {}
+
+    #[tool(description = "Run out of paper")]
+    fn print(&self) -> Result<String, String> {
+        Err("out of paper".to_string())
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_non_serializable_tool_result() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "tuple_keys".into(),
+            arguments: None,
+        })
+        .await;
+    let Err(ServiceError::McpError(error)) = result else {
+        panic!("expect an internal error, got {result:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+
+    // json has no way to write a NaN, it must not be sent as `null`
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "nan".into(),
+            arguments: None,
+        })
+        .await;
+    let Err(ServiceError::McpError(error)) = result else {
+        panic!("expect an internal error, got {result:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+
+    // the connection is still usable afterwards
+    let tools = client.list_all_tools().await?;
+    assert_eq!(tools.len(), 4);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NoTools;
+
+impl ServerHandler for NoTools {}
+
+#[tokio::test]
+async fn test_tool_error_is_a_result() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    // the tool ran and failed, the call itself succeeded
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "print".into(),
+            arguments: None,
+        })
+        .await?;
+    assert!(result.is_tool_error());
+    assert_eq!(
+        result.content[0].as_text().map(|t| t.text.as_str()),
+        Some("out of paper")
+    );
+    // a result that can't be serialized is not the tool's failure
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "nan".into(),
+            arguments: None,
+        })
+        .await;
+    let Err(ServiceError::McpError(error)) = result else {
+        panic!("expect an internal error, got {result:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_method_not_found_is_an_error() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = NoTools.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "print".into(),
+            arguments: None,
+        })
+        .await;
+    let Err(ServiceError::McpError(error)) = result else {
+        panic!("expect the call to fail, got {result:?}");
+    };
+    assert_eq!(error.code, ErrorCode::METHOD_NOT_FOUND);
+    client.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tool_without_content_succeeds() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "noop".into(),
+            arguments: None,
+        })
+        .await?;
+    assert_eq!(result, CallToolResult::empty_success());
+    assert_eq!(result.is_error, Some(false));
+    assert_eq!(result.content, [Content::text("")]);
+    assert_eq!(
+        serde_json::to_value(&result)?,
+        serde_json::json!({ "content": [{ "type": "text", "text": "" }], "isError": false })
+    );
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use rmcp::{
+    ServerHandler, ServiceError, ServiceExt,
+    model::{CallToolRequestParam, ErrorCode, ServerCapabilities, ServerInfo},
+    tool,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+#[tool(tool_box)]
+impl Server {
+    #[tool(description = "Answer at once", timeout_ms = 100)]
+    async fn fast(&self) -> String {
+        "fast".to_string()
+    }
+
+    #[tool(description = "Never answer in time", timeout_ms = 100)]
+    async fn slow(&self) -> String {
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        "slow".to_string()
+    }
+
+    #[tool(description = "Take longer than the others' limit")]
+    async fn unlimited(&self) -> String {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        "unlimited".to_string()
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for Server {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            ..Default::default()
+        }
+    }
+}
+
+fn call(name: &'static str) -> CallToolRequestParam {
+    CallToolRequestParam {
+        name: name.into(),
+        arguments: None,
+    }
+}
+
+#[tokio::test]
+async fn test_per_tool_timeout() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Server.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    client.call_tool(call("fast")).await?;
+    client.call_tool(call("unlimited")).await?;
+
+    let result = client.call_tool(call("slow")).await;
+    let Err(ServiceError::McpError(error)) = result else {
+        panic!("expect the slow tool to time out, got {result:?}");
+    };
+    assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+    assert_eq!(error.message, "tool timed out after 100ms");
+    assert_eq!(error.data, Some(serde_json::json!({ "timeoutMs": 100 })));
+
+    client.cancel().await?;
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use std::sync::{Arc, Mutex};
+
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler, ServiceExt,
+    model::{ClientRequest, PingRequest, TraceContext, extract_trace_context},
+    service::{RequestContext, current_trace_context, with_trace_context},
+};
+
+/// Record the trace context of every ping
+#[derive(Clone, Default)]
+struct TraceRecorder {
+    received: Arc<Mutex<Vec<(Option<TraceContext>, Option<TraceContext>)>>>,
+}
+
+impl ServerHandler for TraceRecorder {
+    async fn ping(&self, context: RequestContext<RoleServer>) -> Result<(), McpError> {
+        self.received.lock().unwrap().push((
+            extract_trace_context(&context.meta),
+            current_trace_context(),
+        ));
+        Ok(())
+    }
+}
+
+fn ping() -> ClientRequest {
+    ClientRequest::PingRequest(PingRequest {
+        method: Default::default(),
+        extensions: Default::default(),
+    })
+}
+
+#[tokio::test]
+async fn test_trace_context_propagation() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let recorder = TraceRecorder::default();
+    tokio::spawn({
+        let recorder = recorder.clone();
+        async move {
+            let server = recorder.serve(server_transport).await?;
+            server.waiting().await?;
+            anyhow::Ok(())
+        }
+    });
+    let client = ().serve(client_transport).await?;
+
+    let context = TraceContext::new("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+        .expect("valid traceparent")
+        .with_tracestate("rojo=00f067aa0ba902b7");
+    with_trace_context(context.clone(), client.send_request(ping())).await?;
+    // without a current context nothing is injected
+    client.send_request(ping()).await?;
+
+    let received = recorder.received.lock().unwrap().clone();
+    assert_eq!(
+        received,
+        vec![(Some(context.clone()), Some(context)), (None, None),]
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
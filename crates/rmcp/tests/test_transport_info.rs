@@ -0,0 +1,58 @@
+use rmcp::{ServerHandler, ServiceExt, transport::SocketAddrs};
+use tokio::net::{TcpListener, TcpStream};
+
+#[derive(Debug, Clone, Default)]
+pub struct Server;
+
+impl ServerHandler for Server {}
+
+#[tokio::test]
+async fn test_socket_addrs_over_tcp() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let server_addr = listener.local_addr()?;
+    let stream = TcpStream::connect(server_addr).await?;
+    let client_addr = stream.local_addr()?;
+    let (server, client) = tokio::try_join!(
+        async {
+            let (stream, _) = listener.accept().await?;
+            Server.serve(stream).await
+        },
+        ().serve(stream)
+    )?;
+
+    assert_eq!(
+        client.peer().transport_info().get::<SocketAddrs>(),
+        Some(&SocketAddrs {
+            local: client_addr,
+            peer: server_addr,
+        })
+    );
+    assert_eq!(
+        server.peer().transport_info().get::<SocketAddrs>(),
+        Some(&SocketAddrs {
+            local: server_addr,
+            peer: client_addr,
+        })
+    );
+
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_no_socket_addrs_over_memory() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let (server, client) =
+        tokio::try_join!(Server.serve(server_transport), ().serve(client_transport))?;
+    assert!(
+        client
+            .peer()
+            .transport_info()
+            .get::<SocketAddrs>()
+            .is_none()
+    );
+    client.cancel().await?;
+    server.cancel().await?;
+    Ok(())
+}
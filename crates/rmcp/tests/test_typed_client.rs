@@ -0,0 +1,52 @@
+mod common;
+
+use common::calculator::Calculator;
+use rmcp::{ServiceExt, handler::client::typed::TypedToolError, mcp_client, model::CallToolResult};
+
+#[mcp_client]
+trait CalculatorApi {
+    /// Calculate the sum of two numbers
+    async fn sum(&self, a: i32, b: i32) -> i32;
+    #[tool(name = "sub")]
+    async fn difference(&self, a: i32, b: i32) -> i32;
+    /// Not served by the calculator
+    async fn product(&self, a: i32, b: i32) -> i32;
+    /// The result is a text which isn't a boolean
+    #[tool(name = "sum")]
+    async fn sum_as_bool(&self, a: i32, b: i32) -> bool;
+}
+
+#[tokio::test]
+async fn test_typed_client() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Calculator.serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+    let calculator = CalculatorApiClient::new(client.peer().clone());
+
+    assert_eq!(calculator.sum(1, 2).await?, 3);
+    assert_eq!(calculator.difference(5, 7).await?, -2);
+
+    let error = calculator.product(2, 3).await.unwrap_err();
+    assert!(matches!(error, TypedToolError::Service(_)), "{error}");
+    let error = calculator.sum_as_bool(1, 2).await.unwrap_err();
+    assert!(
+        matches!(error, TypedToolError::InvalidResult { tool: "sum", .. }),
+        "{error}"
+    );
+
+    client.cancel().await?;
+    Ok(())
+}
+
+#[test]
+fn test_tool_error_keeps_the_result() {
+    let error = TypedToolError::Tool {
+        tool: "sum",
+        result: CallToolResult::error(vec![]),
+    };
+    assert_eq!(error.to_string(), "tool sum failed");
+}
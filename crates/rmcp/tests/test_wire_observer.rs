@@ -0,0 +1,57 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use common::calculator::Calculator;
+use rmcp::{
+    ServiceExt,
+    model::{
+        ClientJsonRpcMessage, ClientNotification, ClientRequest, ServerJsonRpcMessage, ServerResult,
+    },
+    transport::io::{AsyncRwTransport, Direction},
+};
+
+#[tokio::test]
+async fn test_wire_observer_records_handshake() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let server = Calculator::default().serve(server_transport).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+
+    let frames = Arc::new(Mutex::new(Vec::<(Direction, Vec<u8>)>::new()));
+    let (read, write) = tokio::io::split(client_transport);
+    let transport = AsyncRwTransport::new(read, write).wire_observer({
+        let frames = frames.clone();
+        move |direction, frame| frames.lock().unwrap().push((direction, frame.to_vec()))
+    });
+    let client = ().serve(transport).await?;
+
+    let frames = frames.lock().unwrap().clone();
+    let directions = frames.iter().map(|(d, _)| *d).collect::<Vec<_>>();
+    assert_eq!(
+        directions,
+        [Direction::Outbound, Direction::Inbound, Direction::Outbound]
+    );
+
+    let request: ClientJsonRpcMessage = serde_json::from_slice(&frames[0].1)?;
+    let Some((ClientRequest::InitializeRequest(_), id)) = request.into_request() else {
+        panic!("expect an initialize request");
+    };
+    let response: ServerJsonRpcMessage = serde_json::from_slice(&frames[1].1)?;
+    let Some((ServerResult::InitializeResult(result), response_id)) = response.into_response()
+    else {
+        panic!("expect an initialize result");
+    };
+    assert_eq!(response_id, id);
+    assert_eq!(&result, client.peer_info());
+    let notification: ClientJsonRpcMessage = serde_json::from_slice(&frames[2].1)?;
+    assert!(matches!(
+        notification.into_notification(),
+        Some(ClientNotification::InitializedNotification(_))
+    ));
+
+    client.cancel().await?;
+    Ok(())
+}
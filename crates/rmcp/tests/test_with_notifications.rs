@@ -0,0 +1,86 @@
+use rmcp::{
+    Error as McpError, Peer, RoleServer, Service,
+    model::{
+        ClientInfo, ClientNotification, ClientRequest, LoggingLevel, LoggingMessageNotification,
+        LoggingMessageNotificationParam, ServerInfo, ServerNotification, ServerResult,
+    },
+    service::{RequestContext, WithNotifications, serve_directly},
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+fn log(data: &str) -> ServerNotification {
+    ServerNotification::LoggingMessageNotification(LoggingMessageNotification {
+        method: Default::default(),
+        params: LoggingMessageNotificationParam {
+            level: LoggingLevel::Info,
+            logger: None,
+            data: data.into(),
+        },
+        extensions: Default::default(),
+    })
+}
+
+/// Answer every request with two log notifications ahead of the response
+struct Server;
+
+impl Service<RoleServer> for Server {
+    async fn handle_request(
+        &self,
+        _request: ClientRequest,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ServerResult, McpError> {
+        Ok(ServerResult::empty(()))
+    }
+
+    async fn handle_request_with_notifications(
+        &self,
+        request: ClientRequest,
+        context: RequestContext<RoleServer>,
+    ) -> Result<WithNotifications<ServerResult, ServerNotification>, McpError> {
+        let response = self.handle_request(request, context).await?;
+        Ok(WithNotifications::new(response)
+            .with_notification(log("first"))
+            .with_notification(log("second")))
+    }
+
+    async fn handle_notification(&self, _notification: ClientNotification) -> Result<(), McpError> {
+        Ok(())
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleServer>> {
+        None
+    }
+
+    fn set_peer(&mut self, _peer: Peer<RoleServer>) {}
+
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::default()
+    }
+}
+
+#[tokio::test]
+async fn test_notifications_before_response() -> anyhow::Result<()> {
+    let (server_transport, client_transport) = tokio::io::duplex(4096);
+    let server = serve_directly(Server, server_transport, ClientInfo::default()).await?;
+    let (client_read, mut client_write) = tokio::io::split(client_transport);
+    let mut client_read = BufReader::new(client_read);
+
+    client_write
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"ping\"}\n")
+        .await?;
+    let mut messages = Vec::new();
+    for _ in 0..3 {
+        let mut line = String::new();
+        client_read.read_line(&mut line).await?;
+        messages.push(serde_json::from_str::<serde_json::Value>(&line)?);
+    }
+    assert_eq!(messages[0]["method"], "notifications/message");
+    assert_eq!(messages[0]["params"]["data"], "first");
+    assert_eq!(messages[1]["method"], "notifications/message");
+    assert_eq!(messages[1]["params"]["data"], "second");
+    assert_eq!(messages[2]["id"], 1);
+    assert_eq!(messages[2]["result"], serde_json::json!({}));
+
+    server.cancel().await?;
+    Ok(())
+}
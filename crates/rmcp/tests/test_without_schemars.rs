@@ -0,0 +1,43 @@
+//! Run with `cargo test --no-default-features --features testing,macros,permissive-schema`, the
+//! tools are still generated without `schemars`, with a permissive schema.
+#![cfg(not(feature = "schemars"))]
+mod common;
+
+use common::calculator::Calculator;
+use rmcp::testing::TestClient;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_tools_have_a_permissive_schema() -> anyhow::Result<()> {
+    let client = TestClient::new(Calculator).await?;
+
+    let tools = client.list_tools().await?;
+    let mut names = tools
+        .iter()
+        .map(|tool| tool.name.as_ref())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["sub", "sum"]);
+    for tool in &tools {
+        assert_eq!(
+            serde_json::Value::Object(tool.input_schema.as_ref().clone()),
+            json!({ "type": "object" }),
+            "schema of {}",
+            tool.name
+        );
+    }
+
+    let result = client.call_tool("sum", json!({ "a": 1, "b": 2 })).await?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("3")
+    );
+    let result = client.call_tool("sub", json!({ "a": 3, "b": 1 })).await?;
+    assert_eq!(
+        result.content[0].as_text().map(|text| text.text.as_str()),
+        Some("2")
+    );
+
+    client.close().await?;
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use std::{
+    io,
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    task::{Context, Poll},
+};
+
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler, ServiceError, ServiceExt,
+    model::{ClientRequest, PingRequest},
+    service::{CloseReason, PeerRequestOptions, RequestContext},
+};
+use tokio::{io::AsyncWrite, sync::Notify};
+
+/// A writer which breaks once `budget` bytes went through, possibly in the middle of a message
+struct BreakingWriter<W> {
+    inner: W,
+    budget: Arc<AtomicUsize>,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for BreakingWriter<W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let budget = self.budget.load(Ordering::SeqCst);
+        if budget == 0 {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        let len = buf.len().min(budget);
+        let written = std::task::ready!(Pin::new(&mut self.inner).poll_write(cx, &buf[..len]))?;
+        self.budget.fetch_sub(written, Ordering::SeqCst);
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Never answer a ping, so it stays pending on the client
+#[derive(Clone, Default)]
+struct Server {
+    received: Arc<Notify>,
+}
+
+impl ServerHandler for Server {
+    async fn ping(&self, _context: RequestContext<RoleServer>) -> Result<(), McpError> {
+        self.received.notify_one();
+        std::future::pending().await
+    }
+}
+
+fn ping() -> ClientRequest {
+    ClientRequest::PingRequest(PingRequest {
+        method: Default::default(),
+        extensions: Default::default(),
+    })
+}
+
+#[tokio::test]
+async fn test_write_error_closes_connection() -> anyhow::Result<()> {
+    let (client_stream, server_stream) = tokio::io::duplex(4096);
+    let (client_read, client_write) = tokio::io::split(client_stream);
+    let budget = Arc::new(AtomicUsize::new(usize::MAX));
+    let client_transport = (
+        client_read,
+        BreakingWriter {
+            inner: client_write,
+            budget: budget.clone(),
+        },
+    );
+    let server = Server::default();
+    let received = server.received.clone();
+    let server_handle = tokio::spawn(async move {
+        let server = server.serve(server_stream).await?;
+        server.waiting().await?;
+        anyhow::Ok(())
+    });
+    let client = ().serve(client_transport).await?;
+
+    let pending = client
+        .send_cancellable_request(ping(), PeerRequestOptions::no_options())
+        .await?;
+    received.notified().await;
+
+    // the next message only gets its first bytes out
+    budget.store(10, Ordering::SeqCst);
+    let peer = client.peer().clone();
+    let failed = peer.send_request(ping()).await;
+    assert!(
+        matches!(&failed, Err(ServiceError::Transport(error)) if error.kind() == io::ErrorKind::BrokenPipe),
+        "{failed:?}"
+    );
+    assert!(matches!(
+        pending.await_response().await,
+        Err(ServiceError::ConnectionClosed)
+    ));
+    assert!(matches!(
+        peer.send_request(ping()).await,
+        Err(ServiceError::ConnectionClosed)
+    ));
+
+    let reason = client.waiting().await?;
+    assert!(
+        matches!(&reason, CloseReason::TransportError(error) if error.kind() == io::ErrorKind::BrokenPipe),
+        "{reason:?}"
+    );
+    server_handle.await??;
+    Ok(())
+}
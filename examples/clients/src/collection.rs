@@ -38,7 +38,10 @@ async fn main() -> Result<()> {
         let _tool_result = service
             .call_tool(CallToolRequestParam {
                 name: "git_status".into(),
-                arguments: serde_json::json!({ "repo_path": "." }).as_object().cloned(),
+                arguments: serde_json::json!({ "repo_path": "." })
+                    .as_object()
+                    .cloned()
+                    .map(Into::into),
             })
             .await?;
     }
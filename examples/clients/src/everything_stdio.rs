@@ -40,7 +40,7 @@ async fn main() -> Result<()> {
     let tool_result = service
         .call_tool(CallToolRequestParam {
             name: "echo".into(),
-            arguments: Some(object!({ "message": "hi from rmcp" })),
+            arguments: Some(object!({ "message": "hi from rmcp" }).into()),
         })
         .await?;
     tracing::info!("Tool result for echo: {tool_result:#?}");
@@ -49,7 +49,7 @@ async fn main() -> Result<()> {
     let tool_result = service
         .call_tool(CallToolRequestParam {
             name: "longRunningOperation".into(),
-            arguments: Some(object!({ "duration": 3, "steps": 1 })),
+            arguments: Some(object!({ "duration": 3, "steps": 1 }).into()),
         })
         .await?;
     tracing::info!("Tool result for longRunningOperation: {tool_result:#?}");
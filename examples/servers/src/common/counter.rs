@@ -120,15 +120,15 @@ impl ServerHandler for Counter {
         match uri.as_str() {
             "str:////Users/to/some/path/" => {
                 let cwd = "/Users/to/some/path/";
-                Ok(ReadResourceResult {
-                    contents: vec![ResourceContents::text(cwd, uri)],
-                })
+                Ok(ReadResourceResult::new(vec![ResourceContents::text(
+                    cwd, uri,
+                )]))
             }
             "memo://insights" => {
                 let memo = "Business Intelligence Memo\n\nAnalysis has revealed 5 key insights ...";
-                Ok(ReadResourceResult {
-                    contents: vec![ResourceContents::text(memo, uri)],
-                })
+                Ok(ReadResourceResult::new(vec![ResourceContents::text(
+                    memo, uri,
+                )]))
             }
             _ => Err(McpError::resource_not_found(
                 "resource_not_found",
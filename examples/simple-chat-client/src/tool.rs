@@ -52,7 +52,7 @@ impl Tool for McpToolAdapter {
 
     async fn call(&self, args: Value) -> Result<CallToolResult> {
         let arguments = match args {
-            Value::Object(map) => Some(map),
+            Value::Object(map) => Some(map.into()),
             _ => None,
         };
         println!("arguments: {:?}", arguments);
@@ -48,10 +48,13 @@ async fn main() -> anyhow::Result<()> {
                 .peer()
                 .call_tool(rmcp::model::CallToolRequestParam {
                     name: sum_tool.name.clone(),
-                    arguments: Some(rmcp::object!({
-                        "a": 10,
-                        "b": 20
-                    })),
+                    arguments: Some(
+                        rmcp::object!({
+                            "a": 10,
+                            "b": 20
+                        })
+                        .into(),
+                    ),
                 })
                 .await?;
 